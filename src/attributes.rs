@@ -0,0 +1,560 @@
+use bevy::{math::DVec3, prelude::*};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{
+    blocks::{BlockReference, Blocks},
+    dimensions::place_label,
+    events::AttributeEvent,
+    font_resource::GlobalFont,
+    input_field::{FieldInput, InputFieldKind},
+    layers::{LayerId, Layers},
+    render_origin::RenderOrigin,
+    selection::Selection,
+};
+
+/// Файл, в который по умолчанию извлекаются значения атрибутов
+pub const ATTRIBUTES_CSV_FILE: &str = "attributes.csv";
+
+/// Путь файла извлечения: относительный путь отсчитывается от текущего
+/// каталога (при `cargo run` — корень проекта)
+pub fn csv_path(text: &str) -> PathBuf {
+    std::path::absolute(text).unwrap_or_else(|_| PathBuf::from(text))
+}
+
+/// Определение атрибута: в чертеже показывает тег, в определении блока задает
+/// подсказку и значение по умолчанию для каждой вставки
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct AttributeDefinition {
+    pub tag: String,
+    pub prompt: String,
+    pub default: String,
+    /// Скрытые атрибуты не подписываются на чертеже, но извлекаются
+    pub visible: bool,
+    /// Точка подписи; в определении блока — от базовой точки
+    pub position: DVec3,
+    pub height: f64,
+}
+
+/// Значения атрибутов вставки по тегам. Тег без значения берет значение
+/// по умолчанию, поэтому переопределение блока не теряет введенное.
+#[derive(Component, Clone, PartialEq, Debug, Default)]
+pub struct BlockAttributes {
+    pub values: Vec<(String, String)>,
+}
+
+impl BlockAttributes {
+    pub fn value<'a>(&'a self, definition: &'a AttributeDefinition) -> &'a str {
+        self.values
+            .iter()
+            .find(|(tag, _)| *tag == definition.tag)
+            .map_or(&definition.default, |(_, value)| value)
+    }
+
+    pub fn set(&mut self, tag: &str, value: String) {
+        match self.values.iter_mut().find(|(existing, _)| existing == tag) {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((tag.to_string(), value)),
+        }
+    }
+}
+
+/// Ввод значений атрибутов вставки по очереди
+#[derive(Clone, Copy, Debug)]
+pub struct AttributeEntry {
+    pub reference: Entity,
+    pub index: usize,
+}
+
+/// Параметры новых определений атрибутов, ввод значений и итог извлечения
+#[derive(Resource)]
+pub struct AttributeTool {
+    pub tag: String,
+    pub prompt: String,
+    pub default: String,
+    pub visible: bool,
+    pub entry: Option<AttributeEntry>,
+    /// Файл, в который извлекаются значения
+    pub csv_path: PathBuf,
+    pub report: Option<String>,
+}
+
+impl Default for AttributeTool {
+    fn default() -> Self {
+        Self {
+            tag: String::new(),
+            prompt: String::new(),
+            default: String::new(),
+            visible: true,
+            entry: None,
+            csv_path: csv_path(ATTRIBUTES_CSV_FILE),
+            report: None,
+        }
+    }
+}
+
+impl AttributeTool {
+    /// Новое определение атрибута в точке `position`
+    pub fn definition(&self, position: DVec3, height: f64) -> AttributeDefinition {
+        AttributeDefinition {
+            tag: self.tag.clone(),
+            prompt: if self.prompt.is_empty() {
+                self.tag.clone()
+            } else {
+                self.prompt.clone()
+            },
+            default: self.default.clone(),
+            visible: self.visible,
+            position,
+            height,
+        }
+    }
+}
+
+/// Подпись атрибута: тег определения или значение атрибута вставки
+#[derive(Component)]
+pub struct AttributeLabel {
+    pub owner: Entity,
+    pub index: usize,
+}
+
+/// Определение атрибута в чертеже; его подпись добавляет `update_attribute_labels_system`
+pub fn spawn_attribute_definition(
+    commands: &mut Commands,
+    definition: AttributeDefinition,
+) -> Entity {
+    commands
+        .spawn((definition, Name::new("AttributeDefinition")))
+        .id()
+}
+
+/// Определение атрибута под курсором: ближайшее по точке подписи в пределах апертуры
+pub fn attribute_under_cursor<'a>(
+    cursor: DVec3,
+    definitions: impl Iterator<Item = (Entity, &'a AttributeDefinition)>,
+    aperture: f64,
+) -> Option<Entity> {
+    definitions
+        .map(|(entity, definition)| {
+            let distance = (definition.position - cursor).with_y(0.0).length();
+            (entity, distance)
+        })
+        .filter(|(_, distance)| *distance <= aperture)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entity, _)| entity)
+}
+
+/// Поле CSV в кавычках, если в нем есть разделитель, кавычка или перевод строки
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Таблица значений атрибутов: блок, точка вставки и по столбцу на каждый тег
+pub fn attribute_table(
+    blocks: &Blocks,
+    references: &[(&BlockReference, &BlockAttributes)],
+) -> String {
+    let mut tags: Vec<&str> = Vec::new();
+    for (reference, _) in references {
+        for definition in &blocks.definitions[reference.block].attributes {
+            if !tags.contains(&definition.tag.as_str()) {
+                tags.push(&definition.tag);
+            }
+        }
+    }
+    let mut rows = vec![["Блок", "X", "Y", "Z"]
+        .into_iter()
+        .chain(tags.iter().copied())
+        .map(csv_field)
+        .collect::<Vec<_>>()];
+    for (reference, attributes) in references {
+        let definition = &blocks.definitions[reference.block];
+        let mut row = vec![
+            csv_field(&definition.name),
+            // x, y — план, z — высота
+            reference.position.x.to_string(),
+            reference.position.z.to_string(),
+            reference.position.y.to_string(),
+        ];
+        row.extend(tags.iter().map(|tag| {
+            definition
+                .attributes
+                .iter()
+                .find(|attribute| attribute.tag == *tag)
+                .map_or(String::new(), |attribute| {
+                    csv_field(attributes.value(attribute))
+                })
+        }));
+        rows.push(row);
+    }
+    rows.iter().map(|row| row.join(",") + "\n").collect()
+}
+
+/// Система обработки событий атрибутов
+pub fn handle_attribute_events(
+    mut attribute_events: EventReader<AttributeEvent>,
+    mut tool: ResMut<AttributeTool>,
+    blocks: Res<Blocks>,
+    selection: Res<Selection>,
+    mut reference_query: Query<(Entity, &BlockReference, &mut BlockAttributes)>,
+) {
+    for event in attribute_events.read() {
+        match event {
+            AttributeEvent::SetDefinition {
+                tag,
+                prompt,
+                default,
+            } => {
+                tool.tag = tag.clone();
+                tool.prompt = prompt.clone();
+                tool.default = default.clone();
+            }
+            AttributeEvent::ToggleVisible => tool.visible = !tool.visible,
+            AttributeEvent::Edit => {
+                // Правятся атрибуты первой выделенной вставки блока с атрибутами
+                tool.entry = selection
+                    .entities
+                    .iter()
+                    .filter_map(|entity| reference_query.get(*entity).ok())
+                    .find(|(_, reference, _)| {
+                        !blocks.definitions[reference.block].attributes.is_empty()
+                    })
+                    .map(|(reference, ..)| AttributeEntry {
+                        reference,
+                        index: 0,
+                    });
+                if tool.entry.is_none() {
+                    warn!("No block reference with attributes selected");
+                }
+            }
+            AttributeEvent::SetValue(value) => {
+                let Some(mut entry) = tool.entry else {
+                    continue;
+                };
+                let Ok((_, reference, mut attributes)) = reference_query.get_mut(entry.reference)
+                else {
+                    tool.entry = None;
+                    continue;
+                };
+                let definitions = &blocks.definitions[reference.block].attributes;
+                // Пустой ввод оставляет текущее значение
+                if let (Some(definition), Some(value)) = (definitions.get(entry.index), value) {
+                    attributes.set(&definition.tag, value.clone());
+                }
+                entry.index += 1;
+                tool.entry = (entry.index < definitions.len()).then_some(entry);
+            }
+            AttributeEvent::FinishEntry => tool.entry = None,
+            AttributeEvent::Extract => {
+                let mut references: Vec<(&BlockReference, &BlockAttributes)> = selection
+                    .entities
+                    .iter()
+                    .filter_map(|entity| reference_query.get(*entity).ok())
+                    .map(|(_, reference, attributes)| (reference, attributes))
+                    .collect();
+                // Без выделенных вставок извлекаются все вставки текущего блока
+                if references.is_empty() {
+                    references = reference_query
+                        .iter()
+                        .filter(|(_, reference, _)| reference.block == blocks.current)
+                        .map(|(_, reference, attributes)| (reference, attributes))
+                        .collect();
+                }
+                let table = attribute_table(&blocks, &references);
+                let path = tool.csv_path.display().to_string();
+                tool.report = Some(match fs::write(&tool.csv_path, table) {
+                    Ok(()) => {
+                        info!(
+                            "Extracted attributes of {} references to {}",
+                            references.len(),
+                            path
+                        );
+                        format!("Извлечено вставок: {} → {}", references.len(), path)
+                    }
+                    Err(error) => {
+                        warn!("Failed to write {}: {}", path, error);
+                        format!("Ошибка записи {}: {}", path, error)
+                    }
+                });
+            }
+            AttributeEvent::SetCsvPath(path) => {
+                tool.csv_path = path.clone();
+                tool.report = Some(format!("Файл извлечения: {}", path.display()));
+            }
+        }
+    }
+}
+
+/// Поля параметров новых определений «ТЕГ; подсказка; значение», ввода значений
+/// и файла извлечения
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    if input.kind == InputFieldKind::AttributeCsvPath {
+        let text = input.text.trim();
+        if text.is_empty() {
+            return Err("укажите файл CSV".to_string());
+        }
+        let path = csv_path(text);
+        let message = format!("→ {}", path.display());
+        input
+            .events
+            .attribute
+            .write(AttributeEvent::SetCsvPath(path));
+        return Ok(message);
+    }
+    if input.kind == InputFieldKind::AttributeValue {
+        let value = input.text.trim();
+        input.events.attribute.write(AttributeEvent::SetValue(
+            (!value.is_empty()).then(|| value.to_string()),
+        ));
+        return Ok(if value.is_empty() {
+            "оставлено".to_string()
+        } else {
+            format!("= {}", value)
+        });
+    }
+    let mut parts = input.text.split(';').map(str::trim);
+    let tag = parts.next().unwrap_or_default();
+    if tag.is_empty() || tag.contains(char::is_whitespace) {
+        return Err("тег — непустое слово без пробелов".to_string());
+    }
+    let tag = tag.to_uppercase();
+    let prompt = parts.next().unwrap_or_default().to_string();
+    let default = parts.next().unwrap_or_default().to_string();
+    if parts.next().is_some() {
+        return Err("ожидается «ТЕГ; подсказка; значение»".to_string());
+    }
+    let message = format!("атрибут {}", tag);
+    input.events.attribute.write(AttributeEvent::SetDefinition {
+        tag,
+        prompt,
+        default,
+    });
+    Ok(message)
+}
+
+/// Подписи атрибутов: теги определений в чертеже и видимые значения вставок
+pub fn update_attribute_labels_system(
+    mut commands: Commands,
+    global_font: Res<GlobalFont>,
+    blocks: Res<Blocks>,
+    layers: Res<Layers>,
+    render_origin: Res<RenderOrigin>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    definition_query: Query<(Entity, &AttributeDefinition, Option<&LayerId>)>,
+    reference_query: Query<(Entity, &BlockReference, &BlockAttributes, Option<&LayerId>)>,
+    mut label_query: Query<(
+        Entity,
+        &AttributeLabel,
+        &mut Text,
+        &mut TextFont,
+        &mut Node,
+        &mut Visibility,
+        &ComputedNode,
+    )>,
+) {
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+    // Подписи объекта: текст, точка, высота; и виден ли слой объекта
+    let labels = |owner: Entity| -> Option<(Vec<(String, DVec3, f64)>, bool)> {
+        let visible = |layer: Option<&LayerId>| layer.is_none_or(|layer| layers.is_visible(*layer));
+        if let Ok((_, definition, layer)) = definition_query.get(owner) {
+            let label = (
+                definition.tag.clone(),
+                definition.position,
+                definition.height,
+            );
+            return Some((vec![label], visible(layer)));
+        }
+        let (_, reference, attributes, layer) = reference_query.get(owner).ok()?;
+        let labels = blocks
+            .definitions
+            .get(reference.block)?
+            .attributes
+            .iter()
+            .filter(|definition| definition.visible)
+            .map(|definition| {
+                (
+                    attributes.value(definition).to_string(),
+                    reference.place(definition.position),
+                    definition.height * reference.scale,
+                )
+            })
+            .collect();
+        Some((labels, visible(layer)))
+    };
+
+    let mut present = HashSet::new();
+    for (entity, label, mut text, mut font, mut node, mut visibility, computed) in
+        label_query.iter_mut()
+    {
+        let Some((owner_labels, visible)) = labels(label.owner) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let Some((content, position, height)) = owner_labels.get(label.index) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        present.insert((label.owner, label.index));
+        if !visible {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        if **text != *content {
+            **text = content.clone();
+        }
+        place_label(
+            camera,
+            render_origin.to_render(*position),
+            *height,
+            &mut font,
+            &mut node,
+            &mut visibility,
+            computed,
+        );
+    }
+
+    // Новые определения и вставки получают подписи
+    let owners = definition_query
+        .iter()
+        .map(|(entity, ..)| (entity, Color::srgb(0.9, 0.6, 0.3)))
+        .chain(
+            reference_query
+                .iter()
+                .map(|(entity, ..)| (entity, Color::srgb(0.9, 0.9, 0.9))),
+        );
+    for (owner, color) in owners {
+        let count = labels(owner).map_or(0, |(labels, _)| labels.len());
+        for index in 0..count {
+            if present.contains(&(owner, index)) {
+                continue;
+            }
+            commands.spawn((
+                Text::new(""),
+                TextFont {
+                    font: global_font.handle.clone(),
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(color),
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                Visibility::Hidden,
+                AttributeLabel { owner, index },
+                Name::new("AttributeLabel"),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockDefinition;
+
+    #[test]
+    fn attribute_values_are_extracted_to_csv() {
+        let attribute = |tag: &str, default: &str| AttributeDefinition {
+            tag: tag.to_string(),
+            prompt: tag.to_string(),
+            default: default.to_string(),
+            visible: true,
+            position: DVec3::ZERO,
+            height: 1.0,
+        };
+        let mut blocks = Blocks::default();
+        let pump = blocks.define(BlockDefinition {
+            name: "Насос".to_string(),
+            lines: Vec::new(),
+            blocks: Vec::new(),
+            attributes: vec![attribute("ПОЗ", "?"), attribute("МАРКА", "К 80-50")],
+        });
+        let reference = BlockReference {
+            block: pump,
+            position: DVec3::new(10.0, 0.5, 20.0),
+            scale: 1.0,
+            rotation: 0.0,
+        };
+        let mut values = BlockAttributes::default();
+        values.set("ПОЗ", "Н-1, резерв".to_string());
+        let defaults = BlockAttributes::default();
+
+        let table = attribute_table(&blocks, &[(&reference, &values), (&reference, &defaults)]);
+        assert_eq!(
+            table,
+            "Блок,X,Y,Z,ПОЗ,МАРКА\n\
+             Насос,10,20,0.5,\"Н-1, резерв\",К 80-50\n\
+             Насос,10,20,0.5,?,К 80-50\n"
+        );
+    }
+
+    #[test]
+    fn missing_tags_and_special_characters_in_csv() {
+        let attribute = |tag: &str| AttributeDefinition {
+            tag: tag.to_string(),
+            prompt: String::new(),
+            default: String::new(),
+            visible: true,
+            position: DVec3::ZERO,
+            height: 1.0,
+        };
+        let mut blocks = Blocks::default();
+        let valve = blocks.define(BlockDefinition {
+            name: "Задвижка \"Ду 100\"".to_string(),
+            lines: Vec::new(),
+            blocks: Vec::new(),
+            attributes: vec![attribute("ПОЗ"), attribute("ПРИМ")],
+        });
+        let mark = blocks.define(BlockDefinition {
+            name: "Отметка".to_string(),
+            lines: Vec::new(),
+            blocks: Vec::new(),
+            attributes: vec![attribute("ОТМ"), attribute("ПОЗ")],
+        });
+        let at = |block| BlockReference {
+            block,
+            position: DVec3::ZERO,
+            scale: 1.0,
+            rotation: 0.0,
+        };
+        let (valve_ref, mark_ref) = (at(valve), at(mark));
+        let mut valve_values = BlockAttributes::default();
+        valve_values.set("ПОЗ", "З-1".to_string());
+        valve_values.set("ПРИМ", "две\nстроки".to_string());
+        let mut mark_values = BlockAttributes::default();
+        mark_values.set("ОТМ", "+1.500".to_string());
+
+        // Теги собираются по всем блокам; у блока без тега ячейка пустая.
+        // Кавычки удваиваются, перенос строки остается внутри кавычек.
+        let table = attribute_table(
+            &blocks,
+            &[(&valve_ref, &valve_values), (&mark_ref, &mark_values)],
+        );
+        assert_eq!(
+            table,
+            "Блок,X,Y,Z,ПОЗ,ПРИМ,ОТМ\n\
+             \"Задвижка \"\"Ду 100\"\"\",0,0,0,З-1,\"две\nстроки\",\n\
+             Отметка,0,0,0,,,+1.500\n"
+        );
+        assert_eq!(attribute_table(&blocks, &[]), "Блок,X,Y,Z\n");
+    }
+
+    #[test]
+    fn relative_csv_path_starts_at_the_working_directory() {
+        let current = std::env::current_dir().unwrap();
+        assert_eq!(
+            csv_path("отчеты/насосы.csv"),
+            current.join("отчеты/насосы.csv")
+        );
+        let absolute = current.join("attributes.csv");
+        assert_eq!(csv_path(absolute.to_str().unwrap()), absolute);
+    }
+}
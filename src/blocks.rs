@@ -0,0 +1,930 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+
+use crate::{
+    attributes::{
+        spawn_attribute_definition, AttributeDefinition, AttributeEntry, AttributeTool,
+        BlockAttributes,
+    },
+    coordinate_systems::formatting,
+    dimension_style::{DimensionStyleId, DimensionStyles},
+    dimensions::{lift, plan},
+    events::{BlockEvent, TypedPointEvent},
+    expression,
+    input_field::{FieldInput, InputFieldKind},
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers, DEFAULT_LINEWEIGHT},
+    line_drawing::{self, DrawableLine, LineDrawingState, LineEndpoint, LineSettings},
+    linetypes::{self, Linetypes},
+    properties::EntityStyle,
+    render_origin::{DocumentPosition, RenderOrigin},
+    selection::{ActiveCommandsMut, Selection},
+    snapping::{candidates, SnapState},
+    ucs::UcsManager,
+};
+
+/// Глубже вложенные блоки не раскрываются (защита от зацикливания)
+const MAX_NESTING: usize = 16;
+
+/// Размещение блока: точка вставки, масштаб и поворот в плане (радианы, против часовой)
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct BlockReference {
+    /// Индекс в `Blocks`
+    pub block: usize,
+    pub position: DVec3,
+    pub scale: f64,
+    pub rotation: f64,
+}
+
+impl BlockReference {
+    /// Точка определения блока в координатах, где лежит вставка
+    pub fn place(&self, point: DVec3) -> DVec3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let local = plan(point) * self.scale;
+        let rotated = DVec2::new(local.x * cos - local.y * sin, local.x * sin + local.y * cos);
+        self.position + lift(rotated, point.y * self.scale)
+    }
+
+    /// Вставка `inner`, лежащая в определении этого блока, в координатах этой вставки
+    pub fn compose(&self, inner: &BlockReference) -> BlockReference {
+        BlockReference {
+            block: inner.block,
+            position: self.place(inner.position),
+            scale: self.scale * inner.scale,
+            rotation: self.rotation + inner.rotation,
+        }
+    }
+
+    /// Поворот сцены; перенос задается `DocumentPosition`. Масштаб уже учтен
+    /// в меше, иначе вместе с геометрией росли бы толщина линий и штрихи.
+    fn transform(&self, translation: Vec3) -> Transform {
+        Transform {
+            translation,
+            // Поворот плана против часовой (от X к Z) — поворот вокруг Y по часовой
+            rotation: Quat::from_rotation_y(-self.rotation as f32),
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+/// Отрезок определения блока со своим слоем и свойствами
+#[derive(Clone, Debug)]
+pub struct BlockLine {
+    pub start: DVec3,
+    pub end: DVec3,
+    pub layer: LayerId,
+    pub style: EntityStyle,
+}
+
+/// Вложенная вставка другого блока со значениями ее атрибутов
+#[derive(Clone, Debug)]
+pub struct NestedBlock {
+    pub reference: BlockReference,
+    pub layer: LayerId,
+    pub attributes: BlockAttributes,
+}
+
+/// Именованное определение блока. Координаты отсчитываются от базовой точки,
+/// которая при вставке совпадает с точкой вставки.
+#[derive(Clone, Debug)]
+pub struct BlockDefinition {
+    pub name: String,
+    pub lines: Vec<BlockLine>,
+    pub blocks: Vec<NestedBlock>,
+    pub attributes: Vec<AttributeDefinition>,
+}
+
+/// Определения блоков и текущий блок палитры.
+/// Определения не удаляются, поэтому индексы во вставках остаются верными.
+#[derive(Resource, Default)]
+pub struct Blocks {
+    pub definitions: Vec<BlockDefinition>,
+    pub current: usize,
+}
+
+impl Blocks {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.definitions
+            .iter()
+            .position(|definition| definition.name == name)
+    }
+
+    /// Новое определение заменяет одноименное, и все его вставки меняются вместе с ним.
+    /// Определение становится текущим.
+    pub fn define(&mut self, definition: BlockDefinition) -> usize {
+        let index = match self.find(&definition.name) {
+            Some(index) => {
+                self.definitions[index] = definition;
+                index
+            }
+            None => {
+                self.definitions.push(definition);
+                self.definitions.len() - 1
+            }
+        };
+        self.current = index;
+        index
+    }
+
+    /// Блок `outer` совпадает с `inner` или содержит его на любой глубине
+    pub fn uses(&self, outer: usize, inner: usize) -> bool {
+        self.uses_at(outer, inner, 0)
+    }
+
+    fn uses_at(&self, outer: usize, inner: usize, depth: usize) -> bool {
+        outer == inner
+            || depth < MAX_NESTING
+                && self.definitions.get(outer).is_some_and(|definition| {
+                    definition
+                        .blocks
+                        .iter()
+                        .any(|nested| self.uses_at(nested.reference.block, inner, depth + 1))
+                })
+    }
+
+    /// Свободное имя «Блок N»
+    pub fn free_name(&self) -> String {
+        (1..)
+            .map(|number| format!("Блок {}", number))
+            .find(|name| self.find(name).is_none())
+            .unwrap_or_default()
+    }
+
+    /// Видимые отрезки вставки со всеми вложенными блоками в координатах вставки
+    pub fn segments<'a>(
+        &'a self,
+        reference: &BlockReference,
+        layers: &Layers,
+    ) -> Vec<(DVec3, DVec3, &'a BlockLine)> {
+        let mut segments = Vec::new();
+        self.collect_segments(reference, layers, 0, &mut segments);
+        segments
+    }
+
+    fn collect_segments<'a>(
+        &'a self,
+        reference: &BlockReference,
+        layers: &Layers,
+        depth: usize,
+        segments: &mut Vec<(DVec3, DVec3, &'a BlockLine)>,
+    ) {
+        let Some(definition) = self.definitions.get(reference.block) else {
+            return;
+        };
+        for line in &definition.lines {
+            if layers.is_visible(line.layer) {
+                segments.push((reference.place(line.start), reference.place(line.end), line));
+            }
+        }
+        if depth >= MAX_NESTING {
+            return;
+        }
+        for nested in &definition.blocks {
+            if layers.is_visible(nested.layer) {
+                let inner = reference.compose(&nested.reference);
+                self.collect_segments(&inner, layers, depth + 1, segments);
+            }
+        }
+    }
+
+    /// Общий меш вставок определения с данным масштабом в координатах
+    /// определения: отрезки с цветом вершин, толщиной и типом линий по их
+    /// свойствам. Масштабируется только геометрия. `None`, если видимых отрезков нет.
+    pub fn mesh(
+        &self,
+        block: usize,
+        scale: f64,
+        layers: &Layers,
+        linetypes: &Linetypes,
+        line_thickness: f32,
+    ) -> Option<Mesh> {
+        let scaled = BlockReference {
+            block,
+            position: DVec3::ZERO,
+            scale,
+            rotation: 0.0,
+        };
+        let mut mesh: Option<Mesh> = None;
+        for (start, end, line) in self.segments(&scaled, layers) {
+            let resolved = line.style.resolve(layers.get(line.layer));
+            let (center, rotation, length) = line_drawing::line_placement(start, end);
+            let radius = line_thickness * resolved.lineweight / DEFAULT_LINEWEIGHT;
+            let mut part = linetypes::line_mesh(
+                length,
+                radius,
+                linetypes.get(&resolved.linetype),
+                linetypes.scale,
+            )
+            .rotated_by(rotation)
+            .translated_by(center.as_vec3());
+            let color = resolved.color().to_linear().to_f32_array();
+            part.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![color; part.count_vertices()]);
+            match &mut mesh {
+                Some(mesh) => {
+                    if let Err(error) = mesh.merge(&part) {
+                        warn!("Block mesh merge failed: {}", error);
+                    }
+                }
+                None => mesh = Some(part),
+            }
+        }
+        mesh
+    }
+}
+
+/// Команда палитры блоков
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlockCommand {
+    /// Указание базовой точки нового определения
+    Define,
+    /// Указание точек вставки текущего блока
+    Insert,
+    /// Указание точки подписи нового определения атрибута
+    Attribute,
+}
+
+#[derive(Clone, Debug)]
+pub struct BlockPick {
+    pub command: BlockCommand,
+    /// Клик по кнопке, начавший команду, не должен стать точкой
+    armed: bool,
+}
+
+impl BlockPick {
+    pub fn prompt(&self) -> &'static str {
+        match self.command {
+            BlockCommand::Define => "Блок: укажите базовую точку",
+            BlockCommand::Insert => "Вставка: укажите точку вставки, Esc — завершить",
+            BlockCommand::Attribute => "Атрибут: укажите точку подписи",
+        }
+    }
+}
+
+/// Имя нового блока, масштаб и поворот новых вставок
+#[derive(Resource)]
+pub struct BlockTool {
+    pub pick: Option<BlockPick>,
+    /// Пустое имя — свободное «Блок N»
+    pub name: String,
+    pub scale: f64,
+    pub rotation: f64,
+}
+
+impl Default for BlockTool {
+    fn default() -> Self {
+        Self {
+            pick: None,
+            name: String::new(),
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl BlockTool {
+    pub fn is_picking(&self) -> bool {
+        self.pick.is_some()
+    }
+}
+
+/// Вставка под курсором: ближайшая по ее видимым отрезкам в пределах апертуры
+pub fn reference_under_cursor<'a>(
+    cursor: DVec3,
+    references: impl Iterator<Item = (Entity, &'a BlockReference)>,
+    blocks: &Blocks,
+    layers: &Layers,
+    aperture: f64,
+) -> Option<Entity> {
+    references
+        .filter_map(|(entity, reference)| {
+            blocks
+                .segments(reference, layers)
+                .into_iter()
+                .map(|(start, end, _)| {
+                    let closest = candidates::closest_on_segment(cursor, start, end);
+                    (closest - cursor).with_y(0.0).length()
+                })
+                .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|distance| (entity, distance))
+        })
+        .filter(|(_, distance)| *distance <= aperture)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entity, _)| entity)
+}
+
+/// Сущность вставки; меш и материал добавляет `update_block_meshes_system`.
+/// Атрибуты без введенных значений берут значения по умолчанию.
+pub fn spawn_reference(
+    commands: &mut Commands,
+    render_origin: &RenderOrigin,
+    reference: BlockReference,
+    attributes: BlockAttributes,
+) -> Entity {
+    commands
+        .spawn((
+            reference,
+            attributes,
+            DocumentPosition(reference.position),
+            reference.transform(render_origin.to_render(reference.position)),
+            Visibility::default(),
+            Name::new("BlockReference"),
+        ))
+        .id()
+}
+
+/// Система обработки событий блоков (кроме расчленения)
+pub fn handle_block_events(
+    mut block_events: EventReader<BlockEvent>,
+    mut active: ActiveCommandsMut,
+    mut blocks: ResMut<Blocks>,
+    selection: Res<Selection>,
+    attribute_tool: Res<AttributeTool>,
+) {
+    for event in block_events.read() {
+        let command = match event {
+            BlockEvent::StartDefine => BlockCommand::Define,
+            BlockEvent::StartInsert => BlockCommand::Insert,
+            BlockEvent::StartAttribute => BlockCommand::Attribute,
+            BlockEvent::Cancel => {
+                active.block_tool.pick = None;
+                continue;
+            }
+            BlockEvent::Next => {
+                if !blocks.definitions.is_empty() {
+                    blocks.current = (blocks.current + 1) % blocks.definitions.len();
+                }
+                continue;
+            }
+            BlockEvent::SetName(name) => {
+                active.block_tool.name = name.clone();
+                continue;
+            }
+            BlockEvent::SetInsertion { scale, rotation } => {
+                active.block_tool.scale = *scale;
+                active.block_tool.rotation = *rotation;
+                continue;
+            }
+            BlockEvent::Explode => continue,
+        };
+        if command == BlockCommand::Define && selection.entities.is_empty() {
+            warn!("Nothing selected");
+            continue;
+        }
+        if command == BlockCommand::Insert && blocks.definitions.is_empty() {
+            warn!("No blocks defined");
+            continue;
+        }
+        if command == BlockCommand::Attribute && attribute_tool.tag.is_empty() {
+            warn!("Attribute tag is empty");
+            continue;
+        }
+        // Новая команда прерывает рисование и другие указания точек
+        active.cancel_all();
+        active.block_tool.pick = Some(BlockPick {
+            command,
+            armed: false,
+        });
+    }
+}
+
+/// Поля имени нового блока и масштаба с поворотом вставок
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    if input.kind == InputFieldKind::BlockName {
+        let name = input.text.trim();
+        input
+            .events
+            .block
+            .write(BlockEvent::SetName(name.to_string()));
+        return Ok(if name.is_empty() {
+            "имя: Блок N".to_string()
+        } else {
+            format!("имя: {}", name)
+        });
+    }
+    let parts = expression::split_top_level(input.text, ',');
+    let (scale, rotation) = match parts.as_slice() {
+        [scale] => (scale, None),
+        [scale, rotation] => (scale, Some(rotation)),
+        _ => return Err("ожидается «масштаб[, поворот]»".to_string()),
+    };
+    let scale = input.evaluate(scale)?;
+    if scale <= 0.0 {
+        return Err("масштаб должен быть положительным".to_string());
+    }
+    let rotation = match rotation {
+        Some(rotation) => input.direction_angle(rotation)?,
+        None => 0.0,
+    };
+    input
+        .events
+        .block
+        .write(BlockEvent::SetInsertion { scale, rotation });
+    Ok(format!(
+        "масштаб {}, поворот {}",
+        scale,
+        formatting::format_angle(
+            rotation,
+            input.coordinate_settings.angle_unit,
+            input.coordinate_settings.precision,
+        )
+    ))
+}
+
+/// Указание базовой точки блока, точек вставки и точек подписи атрибутов
+/// (клики и ввод с клавиатуры). Выделенные отрезки, вставки и определения
+/// атрибутов уходят в определение блока и заменяются его вставкой.
+pub fn block_pick_system(
+    mut commands: Commands,
+    input: ActionInput,
+    mut snap_state: ResMut<SnapState>,
+    render_origin: Res<RenderOrigin>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
+    mut tool: ResMut<BlockTool>,
+    mut blocks: ResMut<Blocks>,
+    mut selection: ResMut<Selection>,
+    line_query: Query<(Entity, &DrawableLine, &EntityStyle, Option<&LayerId>)>,
+    reference_query: Query<(&BlockReference, &BlockAttributes, Option<&LayerId>)>,
+    endpoint_query: Query<(Entity, &DocumentPosition), With<LineEndpoint>>,
+    definition_query: Query<(Entity, &AttributeDefinition)>,
+    mut attribute_tool: ResMut<AttributeTool>,
+    styles: Res<DimensionStyles>,
+) {
+    let Some(mut pick) = tool.pick.clone() else {
+        return;
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        tool.pick = None;
+        info!("Block command cancelled");
+        return;
+    }
+    if !pick.armed {
+        typed_point_events.clear();
+        pick.armed = true;
+        tool.pick = Some(pick);
+        return;
+    }
+
+    let mut points: Vec<DVec3> = typed_point_events.read().map(|event| event.point).collect();
+    if input.just_pressed(InputAction::PickPoint) {
+        if let Some(point) = snap_state.point() {
+            points.push(point);
+        }
+    }
+    snap_state.remember(points.iter().copied());
+
+    for point in points {
+        match pick.command {
+            BlockCommand::Insert => {
+                let reference = BlockReference {
+                    block: blocks.current,
+                    position: point,
+                    scale: tool.scale,
+                    rotation: tool.rotation,
+                };
+                let entity = spawn_reference(
+                    &mut commands,
+                    &render_origin,
+                    reference,
+                    BlockAttributes::default(),
+                );
+                info!(
+                    "Block inserted: {}",
+                    blocks.definitions[blocks.current].name
+                );
+                // Блок с атрибутами запрашивает их значения, вставка завершается
+                if !blocks.definitions[blocks.current].attributes.is_empty() {
+                    attribute_tool.entry = Some(AttributeEntry {
+                        reference: entity,
+                        index: 0,
+                    });
+                    tool.pick = None;
+                    return;
+                }
+            }
+            BlockCommand::Attribute => {
+                let height = styles.get(DimensionStyleId(styles.current)).text_height();
+                let definition = attribute_tool.definition(point, height);
+                info!("Attribute defined: {}", definition.tag);
+                spawn_attribute_definition(&mut commands, definition);
+                tool.pick = None;
+                return;
+            }
+            BlockCommand::Define => {
+                tool.pick = None;
+                let name = match tool.name.trim() {
+                    "" => blocks.free_name(),
+                    name => name.to_string(),
+                };
+                let layer = |layer: Option<&LayerId>| layer.copied().unwrap_or(LayerId(0));
+                let lines: Vec<(Entity, BlockLine)> = line_query
+                    .iter()
+                    .filter(|(entity, ..)| selection.contains(*entity))
+                    .map(|(entity, line, style, layer_id)| {
+                        let block_line = BlockLine {
+                            start: line.start - point,
+                            end: line.end - point,
+                            layer: layer(layer_id),
+                            style: style.clone(),
+                        };
+                        (entity, block_line)
+                    })
+                    .collect();
+                let nested: Vec<(Entity, NestedBlock)> = selection
+                    .entities
+                    .iter()
+                    .filter_map(|entity| {
+                        let (reference, attributes, layer_id) =
+                            reference_query.get(*entity).ok()?;
+                        let mut reference = *reference;
+                        reference.position -= point;
+                        let nested = NestedBlock {
+                            reference,
+                            layer: layer(layer_id),
+                            attributes: attributes.clone(),
+                        };
+                        Some((*entity, nested))
+                    })
+                    .collect();
+                // Блок не может содержать сам себя
+                if let Some(existing) = blocks.find(&name) {
+                    if nested
+                        .iter()
+                        .any(|(_, nested)| blocks.uses(nested.reference.block, existing))
+                    {
+                        warn!("Block {} can't contain itself", name);
+                        return;
+                    }
+                }
+                let attributes: Vec<(Entity, AttributeDefinition)> = definition_query
+                    .iter()
+                    .filter(|(entity, _)| selection.contains(*entity))
+                    .map(|(entity, definition)| {
+                        let mut definition = definition.clone();
+                        definition.position -= point;
+                        (entity, definition)
+                    })
+                    .collect();
+                if lines.is_empty() && nested.is_empty() && attributes.is_empty() {
+                    warn!("Nothing selected");
+                    return;
+                }
+
+                // Маркеры концов уходят вместе с отрезками, если их не держат другие отрезки
+                let same = |a: DVec3, b: DVec3| a.distance(b) < 1e-9;
+                let kept_ends: Vec<DVec3> = line_query
+                    .iter()
+                    .filter(|(entity, ..)| !selection.contains(*entity))
+                    .flat_map(|(_, line, ..)| [line.start, line.end])
+                    .collect();
+                for (entity, position) in endpoint_query.iter() {
+                    let captured = lines.iter().any(|(_, line)| {
+                        same(line.start + point, position.0) || same(line.end + point, position.0)
+                    });
+                    if captured && !kept_ends.iter().any(|end| same(*end, position.0)) {
+                        commands.entity(entity).despawn();
+                    }
+                }
+                let captured = lines
+                    .iter()
+                    .map(|(entity, _)| entity)
+                    .chain(nested.iter().map(|(entity, _)| entity))
+                    .chain(attributes.iter().map(|(entity, _)| entity));
+                for entity in captured {
+                    commands.entity(*entity).despawn();
+                }
+
+                let definition = BlockDefinition {
+                    name,
+                    lines: lines.into_iter().map(|(_, line)| line).collect(),
+                    blocks: nested.into_iter().map(|(_, nested)| nested).collect(),
+                    attributes: attributes
+                        .into_iter()
+                        .map(|(_, attribute)| attribute)
+                        .collect(),
+                };
+                info!(
+                    "Block defined: {} ({} lines, {} nested, {} attributes)",
+                    definition.name,
+                    definition.lines.len(),
+                    definition.blocks.len(),
+                    definition.attributes.len()
+                );
+                let block = blocks.define(definition);
+                let reference = BlockReference {
+                    block,
+                    position: point,
+                    scale: 1.0,
+                    rotation: 0.0,
+                };
+                spawn_reference(
+                    &mut commands,
+                    &render_origin,
+                    reference,
+                    BlockAttributes::default(),
+                );
+                selection.entities.clear();
+                return;
+            }
+        }
+    }
+    tool.pick = Some(pick);
+}
+
+/// Расчленение: выделенные вставки заменяются отрезками, вложенными вставками
+/// и определениями атрибутов; слои и свойства отрезков сохраняются
+pub fn explode_blocks_system(
+    mut commands: Commands,
+    mut block_events: EventReader<BlockEvent>,
+    blocks: Res<Blocks>,
+    mut selection: ResMut<Selection>,
+    reference_query: Query<&BlockReference>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut line_state: ResMut<LineDrawingState>,
+    line_settings: Res<LineSettings>,
+    render_origin: Res<RenderOrigin>,
+    ucs_manager: Res<UcsManager>,
+    snap_state: Res<SnapState>,
+) {
+    for event in block_events.read() {
+        if !matches!(event, BlockEvent::Explode) {
+            continue;
+        }
+        let frame = ucs_manager.frame(snap_state.last_point);
+        let mut exploded = 0;
+        for entity in std::mem::take(&mut selection.entities) {
+            let Ok(reference) = reference_query.get(entity) else {
+                selection.entities.push(entity);
+                continue;
+            };
+            let Some(definition) = blocks.definitions.get(reference.block) else {
+                continue;
+            };
+            let mut ends: Vec<DVec3> = Vec::new();
+            for line in &definition.lines {
+                let (start, end) = (reference.place(line.start), reference.place(line.end));
+                let (line_entity, _) = line_drawing::spawn_line(
+                    &mut commands,
+                    &mut meshes,
+                    &mut line_state,
+                    &line_settings,
+                    &render_origin,
+                    (start, end),
+                    (frame.coordinate_point(start), frame.coordinate_point(end)),
+                );
+                commands
+                    .entity(line_entity)
+                    .insert((line.layer, line.style.clone()));
+                for point in [start, end] {
+                    if ends.iter().all(|end| end.distance(point) > 1e-9) {
+                        ends.push(point);
+                    }
+                }
+            }
+            for point in ends {
+                line_drawing::spawn_endpoint(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &render_origin,
+                    point,
+                    &line_settings,
+                );
+            }
+            for nested in &definition.blocks {
+                let inner = reference.compose(&nested.reference);
+                let inner_entity = spawn_reference(
+                    &mut commands,
+                    &render_origin,
+                    inner,
+                    nested.attributes.clone(),
+                );
+                commands.entity(inner_entity).insert(nested.layer);
+            }
+            for attribute in &definition.attributes {
+                let mut attribute = attribute.clone();
+                attribute.position = reference.place(attribute.position);
+                attribute.height *= reference.scale;
+                spawn_attribute_definition(&mut commands, attribute);
+            }
+            commands.entity(entity).despawn();
+            exploded += 1;
+        }
+        info!("Exploded block references: {}", exploded);
+    }
+}
+
+/// Общие меши определений и материал вставок
+#[derive(Default)]
+pub struct BlockRenderAssets {
+    /// Ручка меша для каждого определения и масштаба (по битам `f64`);
+    /// вставки блока с одинаковым масштабом делят одну ручку
+    meshes: HashMap<(usize, u64), Handle<Mesh>>,
+    material: Option<Handle<StandardMaterial>>,
+}
+
+/// Перестройка общих мешей определений при их изменении и размещение вставок.
+/// Меш определения заменяется под той же ручкой, поэтому все вставки
+/// сразу показывают новое содержимое.
+pub fn update_block_meshes_system(
+    mut commands: Commands,
+    blocks: Res<Blocks>,
+    layers: Res<Layers>,
+    linetypes: Res<Linetypes>,
+    line_settings: Res<LineSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<BlockRenderAssets>,
+    mut reference_query: Query<(
+        Entity,
+        Ref<BlockReference>,
+        Option<Ref<LayerId>>,
+        &mut DocumentPosition,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    let rebuild = blocks.is_changed() || layers.is_changed() || linetypes.is_changed();
+    let build = |meshes: &mut Assets<Mesh>, block: usize, scale: f64, handle: AssetId<Mesh>| {
+        // Без видимых отрезков у ручки нет меша, и вставки ничего не рисуют
+        match blocks.mesh(
+            block,
+            scale,
+            &layers,
+            &linetypes,
+            line_settings.line_thickness,
+        ) {
+            Some(mesh) => meshes.insert(handle, mesh),
+            None => {
+                meshes.remove(handle);
+            }
+        }
+    };
+    let rescaled = reference_query
+        .iter()
+        .any(|(_, reference, ..)| reference.is_changed());
+    if rebuild || rescaled {
+        // Меши масштабов, которых больше нет у вставок, удаляются
+        let used: HashSet<(usize, u64)> = reference_query
+            .iter()
+            .map(|(_, reference, ..)| (reference.block, reference.scale.to_bits()))
+            .collect();
+        assets.meshes.retain(|key, _| used.contains(key));
+    }
+    if rebuild {
+        for (&(block, scale), handle) in &assets.meshes {
+            build(&mut meshes, block, f64::from_bits(scale), handle.id());
+        }
+    }
+    let material = assets
+        .material
+        .get_or_insert_with(|| {
+            // Цвет задают вершины меша
+            materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                ..default()
+            })
+        })
+        .clone();
+
+    for (entity, reference, layer, mut position, mut transform, mut visibility) in
+        reference_query.iter_mut()
+    {
+        let layer_changed = layer.as_ref().is_some_and(|layer| layer.is_changed());
+        if !rebuild && !reference.is_changed() && !layer_changed {
+            continue;
+        }
+        if reference.block >= blocks.definitions.len() {
+            continue;
+        }
+        let mesh = assets
+            .meshes
+            .entry((reference.block, reference.scale.to_bits()))
+            .or_insert_with(|| {
+                let handle = meshes.reserve_handle();
+                build(&mut meshes, reference.block, reference.scale, handle.id());
+                handle
+            })
+            .clone();
+        commands
+            .entity(entity)
+            .insert((Mesh3d(mesh), MeshMaterial3d(material.clone())));
+        position.0 = reference.position;
+        *transform = reference.transform(transform.translation);
+        visibility.set_if_neq(if layer.is_none_or(|layer| layers.is_visible(*layer)) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::Property;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn line(start: DVec3, end: DVec3) -> BlockLine {
+        BlockLine {
+            start,
+            end,
+            layer: LayerId(0),
+            style: EntityStyle::default(),
+        }
+    }
+
+    #[test]
+    fn nested_blocks_are_placed_and_cycles_found() {
+        let mut blocks = Blocks::default();
+        let bolt = blocks.define(BlockDefinition {
+            name: "Болт".to_string(),
+            lines: vec![line(DVec3::ZERO, DVec3::X)],
+            blocks: Vec::new(),
+            attributes: Vec::new(),
+        });
+        // Узел: болт повернут на 90° и увеличен вдвое относительно базы узла
+        let joint = blocks.define(BlockDefinition {
+            name: "Узел".to_string(),
+            lines: vec![line(DVec3::ZERO, DVec3::Z)],
+            blocks: vec![NestedBlock {
+                reference: BlockReference {
+                    block: bolt,
+                    position: DVec3::X,
+                    scale: 2.0,
+                    rotation: FRAC_PI_2,
+                },
+                layer: LayerId(0),
+                attributes: BlockAttributes::default(),
+            }],
+            attributes: Vec::new(),
+        });
+        let reference = BlockReference {
+            block: joint,
+            position: DVec3::new(10.0, 0.0, 0.0),
+            scale: 1.0,
+            rotation: 0.0,
+        };
+        let layers = Layers::default();
+        let segments = blocks.segments(&reference, &layers);
+        assert_eq!(segments.len(), 2);
+        let (start, end, _) = segments[1];
+        assert!(start.abs_diff_eq(DVec3::new(11.0, 0.0, 0.0), 1e-9));
+        assert!(end.abs_diff_eq(DVec3::new(11.0, 0.0, 2.0), 1e-9));
+
+        // Переопределение меняет содержимое всех вставок
+        blocks.define(BlockDefinition {
+            name: "Болт".to_string(),
+            lines: vec![line(DVec3::ZERO, DVec3::X), line(DVec3::X, DVec3::Z)],
+            blocks: Vec::new(),
+            attributes: Vec::new(),
+        });
+        assert_eq!(blocks.definitions.len(), 2);
+        assert_eq!(blocks.segments(&reference, &layers).len(), 3);
+
+        assert!(blocks.uses(joint, bolt));
+        assert!(!blocks.uses(bolt, joint));
+        assert_eq!(blocks.free_name(), "Блок 1");
+    }
+
+    #[test]
+    fn reference_scale_keeps_lineweight_and_dashes() {
+        let mut blocks = Blocks::default();
+        let mut dashed = line(DVec3::ZERO, DVec3::new(3.0, 0.0, 0.0));
+        dashed.style.linetype = Property::Value("Штриховая".to_string());
+        let block = blocks.define(BlockDefinition {
+            name: "Штрих".to_string(),
+            lines: vec![dashed],
+            blocks: Vec::new(),
+            attributes: Vec::new(),
+        });
+        let (layers, linetypes) = (Layers::default(), Linetypes::default());
+        let extent = |scale: f64| {
+            let mesh = blocks
+                .mesh(block, scale, &layers, &linetypes, 0.05)
+                .unwrap();
+            let positions = mesh
+                .attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(|positions| positions.as_float3())
+                .unwrap()
+                .to_vec();
+            let max = positions
+                .iter()
+                .fold(Vec3::splat(f32::MIN), |max, p| max.max(Vec3::from(*p)));
+            (max, mesh.count_vertices())
+        };
+        let (single, single_vertices) = extent(1.0);
+        let (double, double_vertices) = extent(2.0);
+        // Длина растет вместе с масштабом (последний штрих кончается на 2.75 и 5.75),
+        // толщина остается прежней
+        assert!((single.x - 2.75).abs() < 1e-3 && (double.x - 5.75).abs() < 1e-3);
+        assert!((single.y - double.y).abs() < 1e-6 && double.y < 0.06);
+        // Штрихов вдвое больше: их длина задана в единицах чертежа
+        assert_eq!(double_vertices, 2 * single_vertices);
+    }
+}
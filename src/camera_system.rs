@@ -1,6 +1,10 @@
-use bevy::prelude::*;
+use bevy::{math::DVec3, prelude::*};
 
-use crate::events::{CameraResetEvent, CameraToggleEvent};
+use crate::{
+    events::{CameraResetEvent, CameraToggleEvent},
+    keybindings::{ActionInput, InputAction},
+    render_origin::RenderOrigin,
+};
 
 // Ресурс для управления масштабированием камеры
 #[derive(Resource)]
@@ -62,6 +66,7 @@ pub fn handle_camera_toggle_events(
     mut query_camera: Query<&mut Transform, With<Camera3d>>,
     mut state_camera: ResMut<crate::CameraState>,
     mut camera_zoom: ResMut<crate::camera_system::CameraZoom>,
+    mut render_origin: ResMut<RenderOrigin>,
 ) {
     // Обрабатываем все события переключения камеры
     for _event in toggle_events.read() {
@@ -77,6 +82,9 @@ pub fn handle_camera_toggle_events(
                 info!("Switched to perspective view");
             }
 
+            // Оба вида смотрят на начало координат документа
+            render_origin.set_if_neq(RenderOrigin::default());
+
             // Применяем текущий масштаб
             crate::camera_system::apply_zoom_to_camera(&mut transform, &camera_zoom);
 
@@ -85,9 +93,23 @@ pub fn handle_camera_toggle_events(
     }
 }
 
+/// Горячие клавиши вида сверху и сброса масштаба
+pub fn keyboard_camera_system(
+    input: ActionInput,
+    mut camera_toggle_events: EventWriter<CameraToggleEvent>,
+    mut camera_reset_events: EventWriter<CameraResetEvent>,
+) {
+    if input.just_pressed(InputAction::ToggleTopView) {
+        camera_toggle_events.write(CameraToggleEvent);
+    }
+    if input.just_pressed(InputAction::ResetCamera) {
+        camera_reset_events.write(CameraResetEvent);
+    }
+}
+
 pub fn camera_drag_pan_system(
     mut mouse_motion_events: EventReader<bevy::input::mouse::MouseMotion>,
-    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    input: ActionInput,
     mut camera_query: Query<&mut Transform, With<Camera3d>>,
     camera_zoom: Res<CameraZoom>,
 ) {
@@ -95,8 +117,8 @@ pub fn camera_drag_pan_system(
     if !camera_zoom.is_top_view {
         return;
     }
-    // Проверяем что зажата правая кнопка мыши
-    if !mouse_buttons.pressed(MouseButton::Right) {
+    // Проверяем что зажата кнопка панорамирования (по умолчанию правая)
+    if !input.pressed(InputAction::PanCamera) {
         return;
     }
     if let Ok(mut camera_transform) = camera_query.single_mut() {
@@ -142,10 +164,12 @@ pub fn cursor_coordinates_system(
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
     window_query: Query<&Window, With<bevy::window::PrimaryWindow>>,
     camera_zoom: Res<CameraZoom>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    coordinate_settings: Res<crate::coordinate_systems::CoordinateSettings>,
+    render_origin: Res<RenderOrigin>,
+    input: ActionInput,
 ) {
     // Показываем координаты только в режиме вида сверху при нажатии Tab
-    if !camera_zoom.is_top_view || !keyboard_input.pressed(KeyCode::Tab) {
+    if !camera_zoom.is_top_view || !input.pressed(InputAction::ShowCursorCoordinates) {
         return;
     }
 
@@ -167,10 +191,15 @@ pub fn cursor_coordinates_system(
         ) {
             // В реальном приложении здесь можно отображать в UI
             // Пока выводим в консоль при удерживании Tab
-            if keyboard_input.just_pressed(KeyCode::Tab) {
+            if input.just_pressed(InputAction::ShowCursorCoordinates) {
                 info!(
-                    "Cursor position: X: {:.2}, Z: {:.2}",
-                    world_position.x, world_position.z
+                    "Cursor position: {}",
+                    crate::coordinate_systems::formatting::format_cartesian_3d(
+                        crate::coordinate_systems::conversions::world_to_cartesian_3d(
+                            render_origin.to_document(world_position)
+                        ),
+                        &coordinate_settings
+                    )
                 );
             }
         }
@@ -180,12 +209,13 @@ pub fn cursor_coordinates_system(
 // Система для автоматического центрирования камеры на объектах
 // ?
 pub fn center_camera_on_lines_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     line_query: Query<&crate::line_drawing::DrawableLine>,
     mut camera_query: Query<&mut Transform, With<Camera3d>>,
     camera_zoom: Res<CameraZoom>,
+    mut render_origin: ResMut<RenderOrigin>,
 ) {
-    if !keyboard_input.just_pressed(KeyCode::KeyF) || !camera_zoom.is_top_view {
+    if !input.just_pressed(InputAction::CenterCamera) || !camera_zoom.is_top_view {
         return;
     }
 
@@ -195,10 +225,10 @@ pub fn center_camera_on_lines_system(
     }
 
     // Вычисляем центр всех линий
-    let mut min_x = f32::INFINITY;
-    let mut max_x = f32::NEG_INFINITY;
-    let mut min_z = f32::INFINITY;
-    let mut max_z = f32::NEG_INFINITY;
+    let mut min_x = f64::INFINITY;
+    let mut max_x = f64::NEG_INFINITY;
+    let mut min_z = f64::INFINITY;
+    let mut max_z = f64::NEG_INFINITY;
 
     for line in &lines {
         min_x = min_x.min(line.start.x).min(line.end.x);
@@ -211,8 +241,10 @@ pub fn center_camera_on_lines_system(
     let center_z = (min_z + max_z) / 2.0;
 
     if let Ok(mut camera_transform) = camera_query.single_mut() {
-        camera_transform.translation.x = center_x;
-        camera_transform.translation.z = center_z;
+        // Начало сцены переносится в центр линий, камера встает над ним
+        render_origin.origin = DVec3::new(center_x, 0.0, center_z);
+        camera_transform.translation.x = 0.0;
+        camera_transform.translation.z = 0.0;
         info!(
             "Camera centered on lines at ({:.2}, {:.2})",
             center_x, center_z
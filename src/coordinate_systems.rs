@@ -1,4 +1,10 @@
-use bevy::prelude::*;
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use crate::input_field::{FieldInput, InputFieldKind};
 
 /// Режим системы координат
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
@@ -6,6 +12,49 @@ pub enum CoordinateSystem {
     #[default]
     Cartesian,
     Polar,
+    /// Декартовы (x, y, z), z — высота над плоскостью чертежа
+    Cartesian3D,
+    /// Цилиндрические (r, θ, h) относительно полюса
+    Cylindrical,
+    /// Сферические (ρ, θ, φ) относительно полюса, φ — угол над плоскостью чертежа
+    Spherical,
+}
+
+impl CoordinateSystem {
+    /// Порядок переключения клавишей и кнопкой «Координаты»
+    pub const ALL: [CoordinateSystem; 5] = [
+        CoordinateSystem::Cartesian,
+        CoordinateSystem::Polar,
+        CoordinateSystem::Cartesian3D,
+        CoordinateSystem::Cylindrical,
+        CoordinateSystem::Spherical,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|system| *system == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Отсчитываются ли координаты от полюса (полярные, цилиндрические, сферические)
+    pub fn is_polar(self) -> bool {
+        matches!(
+            self,
+            CoordinateSystem::Polar | CoordinateSystem::Cylindrical | CoordinateSystem::Spherical
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CoordinateSystem::Cartesian => "Декартовы",
+            CoordinateSystem::Polar => "Полярные",
+            CoordinateSystem::Cartesian3D => "Декартовы 3D",
+            CoordinateSystem::Cylindrical => "Цилиндрические",
+            CoordinateSystem::Spherical => "Сферические",
+        }
+    }
 }
 
 /// Единицы измерения углов
@@ -14,6 +63,248 @@ pub enum AngleUnit {
     #[default]
     Degrees,
     Radians,
+    /// Грады (400g на полный оборот)
+    Gradians,
+    /// Градусы, минуты, секунды: `30°15'20"`
+    DegreesMinutesSeconds,
+    /// Румбы: `N 45°30' E`
+    SurveyorBearing,
+}
+
+impl AngleUnit {
+    /// Порядок переключения единиц клавишей и кнопкой «Углы»
+    pub const ALL: [AngleUnit; 5] = [
+        AngleUnit::Degrees,
+        AngleUnit::Radians,
+        AngleUnit::Gradians,
+        AngleUnit::DegreesMinutesSeconds,
+        AngleUnit::SurveyorBearing,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|unit| *unit == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Сколько радиан в одной единице. Для ГМС и румбов числа без
+    /// обозначений считаются градусами.
+    pub fn radians_per_unit(self) -> f64 {
+        match self {
+            AngleUnit::Radians => 1.0,
+            AngleUnit::Gradians => std::f64::consts::PI / 200.0,
+            AngleUnit::Degrees | AngleUnit::DegreesMinutesSeconds | AngleUnit::SurveyorBearing => {
+                std::f64::consts::PI / 180.0
+            }
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "Градусы",
+            AngleUnit::Radians => "Радианы",
+            AngleUnit::Gradians => "Грады",
+            AngleUnit::DegreesMinutesSeconds => "Гр. мин. сек.",
+            AngleUnit::SurveyorBearing => "Румбы",
+        }
+    }
+}
+
+/// Направление нулевого угла
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum AngleBase {
+    /// 0° по оси X ПСК
+    #[default]
+    East,
+    /// 0° по оси Y ПСК
+    North,
+}
+
+impl AngleBase {
+    /// Угол нулевого направления от оси X ПСК (против часовой стрелки)
+    pub fn angle(self) -> f64 {
+        match self {
+            AngleBase::East => 0.0,
+            AngleBase::North => FRAC_PI_2,
+        }
+    }
+}
+
+/// Направление положительного отсчета углов
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum AngleDirection {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Линейные единицы чертежа: чему равна единица модели и как выводятся длины
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum LinearUnit {
+    #[default]
+    Millimeters,
+    Centimeters,
+    Meters,
+    Inches,
+    /// Футы и дюймы с дробями: `3'-6 1/2"` (единица модели — дюйм)
+    Architectural,
+    /// Дюймы с дробями: `6 1/2` (единица модели — дюйм)
+    Fractional,
+}
+
+impl LinearUnit {
+    pub const ALL: [LinearUnit; 6] = [
+        LinearUnit::Millimeters,
+        LinearUnit::Centimeters,
+        LinearUnit::Meters,
+        LinearUnit::Inches,
+        LinearUnit::Architectural,
+        LinearUnit::Fractional,
+    ];
+
+    /// Сколько миллиметров в одной единице модели
+    pub fn millimeters_per_unit(self) -> f64 {
+        match self {
+            LinearUnit::Millimeters => 1.0,
+            LinearUnit::Centimeters => 10.0,
+            LinearUnit::Meters => 1000.0,
+            LinearUnit::Inches | LinearUnit::Architectural | LinearUnit::Fractional => 25.4,
+        }
+    }
+
+    /// Выводятся ли длины дробями (точность — степень двойки знаменателя)
+    pub fn is_fractional(self) -> bool {
+        matches!(self, LinearUnit::Architectural | LinearUnit::Fractional)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LinearUnit::Millimeters => "Миллиметры",
+            LinearUnit::Centimeters => "Сантиметры",
+            LinearUnit::Meters => "Метры",
+            LinearUnit::Inches => "Дюймы",
+            LinearUnit::Architectural => "Футы и дюймы",
+            LinearUnit::Fractional => "Дюймы с дробями",
+        }
+    }
+}
+
+/// Картографическая проекция, в которой задана геопривязка чертежа
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+    /// UTM на WGS 84: зоны 1–60 по 6°, масштаб 0.9996 на осевом меридиане
+    Utm { zone: u8, north: bool },
+    /// Гаусс–Крюгер СК-42 на эллипсоиде Красовского: зоны по 6°,
+    /// номер зоны — первые цифры ординаты (Y = 7 500 000 для зоны 7)
+    GaussKruger { zone: u8 },
+}
+
+impl Projection {
+    /// Долгота осевого меридиана зоны в градусах
+    pub fn central_meridian(self) -> f64 {
+        match self {
+            Projection::Utm { zone, .. } => zone as f64 * 6.0 - 183.0,
+            Projection::GaussKruger { zone } => zone as f64 * 6.0 - 3.0,
+        }
+    }
+
+    /// Следующая проекция для переключения кнопкой: UTM -> ГК с тем же
+    /// осевым меридианом -> без геопривязки
+    pub fn cycle(projection: Option<Projection>) -> Option<Projection> {
+        match projection {
+            None => Some(Projection::Utm {
+                zone: 37,
+                north: true,
+            }),
+            Some(Projection::Utm { zone, .. }) => Some(Projection::GaussKruger {
+                zone: (zone + 29) % 60 + 1,
+            }),
+            Some(Projection::GaussKruger { .. }) => None,
+        }
+    }
+
+    /// Соседняя зона (шаг ±1 по кругу 1–60)
+    pub fn shift_zone(self, step: i32) -> Self {
+        let shift = |zone: u8| ((zone as i32 - 1 + step).rem_euclid(60) + 1) as u8;
+        match self {
+            Projection::Utm { zone, north } => Projection::Utm {
+                zone: shift(zone),
+                north,
+            },
+            Projection::GaussKruger { zone } => Projection::GaussKruger { zone: shift(zone) },
+        }
+    }
+
+    /// Датум, в котором выводятся и вводятся широта и долгота
+    pub fn datum_label(self) -> &'static str {
+        match self {
+            Projection::Utm { .. } => "WGS 84",
+            Projection::GaussKruger { .. } => "СК-42",
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Projection::Utm { zone, north } => {
+                format!("UTM {}{}", zone, if north { "N" } else { "S" })
+            }
+            Projection::GaussKruger { zone } => format!("ГК СК-42, зона {}", zone),
+        }
+    }
+}
+
+/// Что показывать рядом с координатами курсора
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum GeoReadout {
+    #[default]
+    Off,
+    /// Широта и долгота
+    Geographic,
+    /// Восток и север в проекции
+    Projected,
+}
+
+impl GeoReadout {
+    pub fn next(self) -> Self {
+        match self {
+            GeoReadout::Off => GeoReadout::Geographic,
+            GeoReadout::Geographic => GeoReadout::Projected,
+            GeoReadout::Projected => GeoReadout::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GeoReadout::Off => "нет",
+            GeoReadout::Geographic => "B, L",
+            GeoReadout::Projected => "E, N",
+        }
+    }
+}
+
+/// Привязка мировой системы чертежа к проекции: начало чертежа лежит в точке
+/// `origin` (восток, север в метрах), ось X чертежа повернута на `rotation`
+/// против часовой стрелки от направления на восток сетки.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Georeference {
+    pub projection: Option<Projection>,
+    pub origin: DVec2,
+    pub rotation: f64,
+    pub readout: GeoReadout,
+}
+
+impl Georeference {
+    /// Точка чертежа (в единицах чертежа) -> восток, север в метрах
+    pub fn drawing_to_grid(&self, point: CartesianPoint, unit: LinearUnit) -> DVec2 {
+        let meters = DVec2::new(point.x, point.y) * unit.millimeters_per_unit() / 1000.0;
+        self.origin + DVec2::from_angle(self.rotation).rotate(meters)
+    }
+
+    /// Восток, север в метрах -> точка чертежа
+    pub fn grid_to_drawing(&self, grid: DVec2, unit: LinearUnit) -> CartesianPoint {
+        let meters = DVec2::from_angle(-self.rotation).rotate(grid - self.origin);
+        let local = meters * 1000.0 / unit.millimeters_per_unit();
+        CartesianPoint::new(local.x, local.y)
+    }
 }
 
 /// Настройки системы координат
@@ -21,6 +312,12 @@ pub enum AngleUnit {
 pub struct CoordinateSettings {
     pub coordinate_system: CoordinateSystem,
     pub angle_unit: AngleUnit,
+    pub angle_base: AngleBase,
+    pub angle_direction: AngleDirection,
+    pub linear_unit: LinearUnit,
+    /// Знаков после запятой, для дробных единиц — знаменатель 2^precision
+    pub precision: usize,
+    pub georeference: Georeference,
 }
 
 impl Default for CoordinateSettings {
@@ -28,6 +325,51 @@ impl Default for CoordinateSettings {
         Self {
             coordinate_system: CoordinateSystem::Cartesian,
             angle_unit: AngleUnit::Degrees,
+            angle_base: AngleBase::East,
+            angle_direction: AngleDirection::CounterClockwise,
+            linear_unit: LinearUnit::Millimeters,
+            precision: 2,
+            georeference: Georeference::default(),
+        }
+    }
+}
+
+impl CoordinateSettings {
+    /// Наибольшая точность: 8 знаков или 1/256 дюйма
+    pub const MAX_PRECISION: usize = 8;
+
+    /// Образец точности для панели: `0.00` или `1/16`
+    pub fn precision_label(&self) -> String {
+        if self.linear_unit.is_fractional() && self.precision > 0 {
+            format!("1/{}", 1u32 << self.precision)
+        } else if self.linear_unit.is_fractional() {
+            "1".to_string()
+        } else {
+            format!("{:.*}", self.precision, 0.0)
+        }
+    }
+
+    /// Направление в ПСК (радианы против часовой стрелки от оси X) в угол,
+    /// отсчитанный от выбранной базы в выбранную сторону, в диапазоне [0, 2π)
+    pub fn angle_from_direction(&self, direction: f64) -> f64 {
+        let angle = match self.angle_direction {
+            AngleDirection::CounterClockwise => direction - self.angle_base.angle(),
+            AngleDirection::Clockwise => self.angle_base.angle() - direction,
+        };
+        let angle = angle.rem_euclid(TAU);
+        // Не показываем 360° вместо 0° из-за погрешности вычислений
+        if TAU - angle < 1e-9 {
+            0.0
+        } else {
+            angle
+        }
+    }
+
+    /// Угол, отсчитанный от базы в выбранную сторону, в направление в ПСК
+    pub fn direction_from_angle(&self, angle: f64) -> f64 {
+        match self.angle_direction {
+            AngleDirection::CounterClockwise => self.angle_base.angle() + angle,
+            AngleDirection::Clockwise => self.angle_base.angle() - angle,
         }
     }
 }
@@ -35,42 +377,67 @@ impl Default for CoordinateSettings {
 /// Структура для представления точки в полярных координатах
 #[derive(Clone, Copy, Debug)]
 pub struct PolarPoint {
-    pub r: f32, // радиус (расстояние от начала координат)
-    pub theta: f32, // угол в радианах
+    pub r: f64,     // радиус (расстояние от начала координат)
+    pub theta: f64, // угол в радианах
 }
 
 impl PolarPoint {
-    pub fn new(r: f32, theta: f32) -> Self {
+    pub fn new(r: f64, theta: f64) -> Self {
         Self { r, theta }
     }
 
     /// Создать полярную точку с углом в градусах
-    pub fn from_degrees(r: f32, theta_degrees: f32) -> Self {
+    pub fn from_degrees(r: f64, theta_degrees: f64) -> Self {
         Self {
             r,
             theta: theta_degrees.to_radians(),
         }
     }
-
-    /// Получить угол в градусах
-    pub fn theta_degrees(&self) -> f32 {
-        self.theta.to_degrees()
-    }
 }
 
 /// Структура для представления точки в декартовых координатах
 #[derive(Clone, Copy, Debug)]
 pub struct CartesianPoint {
-    pub x: f32,
-    pub y: f32,
+    pub x: f64,
+    pub y: f64,
 }
 
 impl CartesianPoint {
-    pub fn new(x: f32, y: f32) -> Self {
+    pub fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
 }
 
+/// Точка в декартовых координатах 3D: x, y в плоскости чертежа, z — высота
+#[derive(Clone, Copy, Debug)]
+pub struct CartesianPoint3D {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl CartesianPoint3D {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Точка в цилиндрических координатах
+#[derive(Clone, Copy, Debug)]
+pub struct CylindricalPoint {
+    pub r: f64,     // расстояние от оси в плоскости чертежа
+    pub theta: f64, // угол в радианах
+    pub h: f64,     // высота
+}
+
+/// Точка в сферических координатах
+#[derive(Clone, Copy, Debug)]
+pub struct SphericalPoint {
+    pub rho: f64,   // расстояние от полюса
+    pub theta: f64, // угол в плоскости чертежа, в радианах
+    pub phi: f64,   // угол над плоскостью чертежа, в радианах
+}
+
 /// Функции преобразования координат
 pub mod conversions {
     use super::*;
@@ -90,68 +457,275 @@ pub mod conversions {
         PolarPoint { r, theta }
     }
 
-    /// Преобразование Vec3 (мировые координаты Bevy) в декартовы координаты 2D
-    pub fn world_to_cartesian(world_pos: Vec3) -> CartesianPoint {
+    /// Преобразование DVec3 (мировые координаты документа) в декартовы координаты 2D
+    pub fn world_to_cartesian(world_pos: DVec3) -> CartesianPoint {
         CartesianPoint {
             x: world_pos.x,
             y: world_pos.z, // В Bevy Z - это глубина, но в 2D это Y
         }
     }
 
-    /// Преобразование декартовых координат 2D в Vec3 (мировые координаты Bevy)
-    pub fn cartesian_to_world(cartesian: CartesianPoint) -> Vec3 {
-        Vec3::new(cartesian.x, 0.0, cartesian.y)
+    /// Преобразование декартовых координат 2D в DVec3 (мировые координаты документа)
+    pub fn cartesian_to_world(cartesian: CartesianPoint) -> DVec3 {
+        DVec3::new(cartesian.x, 0.0, cartesian.y)
+    }
+
+    /// Преобразование DVec3 в декартовы координаты 3D (высота - мировая Y)
+    pub fn world_to_cartesian_3d(world_pos: DVec3) -> CartesianPoint3D {
+        CartesianPoint3D {
+            x: world_pos.x,
+            y: world_pos.z,
+            z: world_pos.y,
+        }
+    }
+
+    /// Преобразование декартовых координат 3D в DVec3
+    pub fn cartesian_3d_to_world(cartesian: CartesianPoint3D) -> DVec3 {
+        DVec3::new(cartesian.x, cartesian.z, cartesian.y)
+    }
+
+    /// Преобразование из цилиндрических в декартовы координаты 3D
+    pub fn cylindrical_to_cartesian_3d(cylindrical: CylindricalPoint) -> CartesianPoint3D {
+        let flat = polar_to_cartesian(PolarPoint::new(cylindrical.r, cylindrical.theta));
+        CartesianPoint3D::new(flat.x, flat.y, cylindrical.h)
+    }
+
+    /// Преобразование из декартовых 3D в цилиндрические координаты
+    pub fn cartesian_3d_to_cylindrical(cartesian: CartesianPoint3D) -> CylindricalPoint {
+        let polar = cartesian_to_polar(CartesianPoint::new(cartesian.x, cartesian.y));
+        CylindricalPoint {
+            r: polar.r,
+            theta: polar.theta,
+            h: cartesian.z,
+        }
+    }
+
+    /// Преобразование из сферических в декартовы координаты 3D
+    pub fn spherical_to_cartesian_3d(spherical: SphericalPoint) -> CartesianPoint3D {
+        let r = spherical.rho * spherical.phi.cos();
+        let flat = polar_to_cartesian(PolarPoint::new(r, spherical.theta));
+        CartesianPoint3D::new(flat.x, flat.y, spherical.rho * spherical.phi.sin())
+    }
+
+    /// Преобразование из декартовых 3D в сферические координаты
+    pub fn cartesian_3d_to_spherical(cartesian: CartesianPoint3D) -> SphericalPoint {
+        let cylindrical = cartesian_3d_to_cylindrical(cartesian);
+        SphericalPoint {
+            rho: (cylindrical.r * cylindrical.r + cartesian.z * cartesian.z).sqrt(),
+            theta: cylindrical.theta,
+            phi: cartesian.z.atan2(cylindrical.r),
+        }
+    }
+}
+
+/// Геодезические координаты и поперечная проекция Меркатора (UTM, Гаусс–Крюгер)
+pub mod geodesy {
+    use super::*;
+
+    /// Эллипсоид: большая полуось (м) и сжатие
+    #[derive(Clone, Copy, Debug)]
+    pub struct Ellipsoid {
+        pub a: f64,
+        pub f: f64,
+    }
+
+    pub const WGS84: Ellipsoid = Ellipsoid {
+        a: 6_378_137.0,
+        f: 1.0 / 298.257_223_563,
+    };
+
+    pub const KRASOVSKY: Ellipsoid = Ellipsoid {
+        a: 6_378_245.0,
+        f: 1.0 / 298.3,
+    };
+
+    /// Широта и долгота в радианах
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct GeoPoint {
+        pub latitude: f64,
+        pub longitude: f64,
+    }
+
+    impl GeoPoint {
+        pub fn from_degrees(latitude: f64, longitude: f64) -> Self {
+            Self {
+                latitude: latitude.to_radians(),
+                longitude: longitude.to_radians(),
+            }
+        }
+    }
+
+    /// Поперечная проекция Меркатора по рядам Крюгера до n⁴
+    /// (ошибка меньше миллиметра в пределах зоны)
+    struct TransverseMercator {
+        ellipsoid: Ellipsoid,
+        central_meridian: f64,
+        scale: f64,
+        false_easting: f64,
+        false_northing: f64,
     }
 
-    /// Преобразование Vec3 в полярные координаты
-    pub fn world_to_polar(world_pos: Vec3) -> PolarPoint {
-        let cartesian = world_to_cartesian(world_pos);
-        cartesian_to_polar(cartesian)
+    impl TransverseMercator {
+        fn for_projection(projection: Projection) -> Self {
+            let central_meridian = projection.central_meridian().to_radians();
+            match projection {
+                Projection::Utm { north, .. } => Self {
+                    ellipsoid: WGS84,
+                    central_meridian,
+                    scale: 0.9996,
+                    false_easting: 500_000.0,
+                    false_northing: if north { 0.0 } else { 10_000_000.0 },
+                },
+                Projection::GaussKruger { zone } => Self {
+                    ellipsoid: KRASOVSKY,
+                    central_meridian,
+                    scale: 1.0,
+                    false_easting: zone as f64 * 1_000_000.0 + 500_000.0,
+                    false_northing: 0.0,
+                },
+            }
+        }
+
+        fn third_flattening(&self) -> f64 {
+            self.ellipsoid.f / (2.0 - self.ellipsoid.f)
+        }
+
+        fn eccentricity(&self) -> f64 {
+            (self.ellipsoid.f * (2.0 - self.ellipsoid.f)).sqrt()
+        }
+
+        /// Радиус спрямляющей сферы, умноженный на масштаб
+        fn rectifying_radius(&self) -> f64 {
+            let n = self.third_flattening();
+            let n2 = n * n;
+            self.scale * self.ellipsoid.a / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0)
+        }
+
+        fn alpha(&self) -> [f64; 4] {
+            let n = self.third_flattening();
+            let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+            [
+                n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4,
+                13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4,
+                61.0 / 240.0 * n3 - 103.0 / 140.0 * n4,
+                49561.0 / 161280.0 * n4,
+            ]
+        }
+
+        fn beta(&self) -> [f64; 4] {
+            let n = self.third_flattening();
+            let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+            [
+                n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4,
+                1.0 / 48.0 * n2 + 1.0 / 15.0 * n3 - 437.0 / 1440.0 * n4,
+                17.0 / 480.0 * n3 - 37.0 / 840.0 * n4,
+                4397.0 / 161280.0 * n4,
+            ]
+        }
+
+        fn forward(&self, point: GeoPoint) -> DVec2 {
+            let e = self.eccentricity();
+            let sin_latitude = point.latitude.sin();
+            // Тангенс конформной широты
+            let tau = (sin_latitude.atanh() - e * (e * sin_latitude).atanh()).sinh();
+            let longitude = point.longitude - self.central_meridian;
+            let xi = tau.atan2(longitude.cos());
+            let eta = (longitude.sin() / (1.0 + tau * tau).sqrt()).atanh();
+
+            let (mut northing, mut easting) = (xi, eta);
+            for (j, alpha) in self.alpha().iter().enumerate() {
+                let k = 2.0 * (j + 1) as f64;
+                northing += alpha * (k * xi).sin() * (k * eta).cosh();
+                easting += alpha * (k * xi).cos() * (k * eta).sinh();
+            }
+            let radius = self.rectifying_radius();
+            DVec2::new(
+                self.false_easting + radius * easting,
+                self.false_northing + radius * northing,
+            )
+        }
+
+        fn inverse(&self, grid: DVec2) -> GeoPoint {
+            let radius = self.rectifying_radius();
+            let xi = (grid.y - self.false_northing) / radius;
+            let eta = (grid.x - self.false_easting) / radius;
+
+            let (mut xi_prime, mut eta_prime) = (xi, eta);
+            for (j, beta) in self.beta().iter().enumerate() {
+                let k = 2.0 * (j + 1) as f64;
+                xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+                eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+            }
+            let tau_conformal = xi_prime.sin() / (eta_prime.sinh().hypot(xi_prime.cos()));
+            let longitude = self.central_meridian + eta_prime.sinh().atan2(xi_prime.cos());
+
+            // Широта по тангенсу конформной широты: несколько итераций Ньютона
+            let e = self.eccentricity();
+            let e2 = e * e;
+            let mut tau = tau_conformal;
+            for _ in 0..5 {
+                let sqrt_tau = (1.0 + tau * tau).sqrt();
+                let sigma = (e * (e * tau / sqrt_tau).atanh()).sinh();
+                let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * sqrt_tau;
+                let delta = (tau_conformal - tau_i) / (1.0 + tau_i * tau_i).sqrt()
+                    * (1.0 + (1.0 - e2) * tau * tau)
+                    / ((1.0 - e2) * sqrt_tau);
+                tau += delta;
+                if delta.abs() < 1e-14 {
+                    break;
+                }
+            }
+            GeoPoint {
+                latitude: tau.atan(),
+                longitude,
+            }
+        }
     }
 
-    /// Преобразование полярных координат в Vec3
-    pub fn polar_to_world(polar: PolarPoint) -> Vec3 {
-        let cartesian = polar_to_cartesian(polar);
-        cartesian_to_world(cartesian)
+    /// Широта и долгота -> восток, север в метрах
+    pub fn geographic_to_projected(point: GeoPoint, projection: Projection) -> DVec2 {
+        TransverseMercator::for_projection(projection).forward(point)
+    }
+
+    /// Восток, север в метрах -> широта и долгота
+    pub fn projected_to_geographic(grid: DVec2, projection: Projection) -> GeoPoint {
+        TransverseMercator::for_projection(projection).inverse(grid)
     }
 }
 
 /// Компонент для хранения информации о точке в разных системах координат
 #[derive(Component, Clone)]
 pub struct CoordinatePoint {
-    pub world_position: Vec3,
+    pub world_position: DVec3,
     pub cartesian: CartesianPoint,
     pub polar: PolarPoint,
+    pub cartesian_3d: CartesianPoint3D,
+    pub cylindrical: CylindricalPoint,
+    pub spherical: SphericalPoint,
 }
 
 impl CoordinatePoint {
-    pub fn from_world(world_pos: Vec3) -> Self {
-        let cartesian = conversions::world_to_cartesian(world_pos);
-        let polar = conversions::cartesian_to_polar(cartesian);
-        Self {
-            world_position: world_pos,
-            cartesian,
-            polar,
-        }
+    pub fn from_world(world_pos: DVec3) -> Self {
+        Self::from_cartesian_3d(conversions::world_to_cartesian_3d(world_pos))
     }
 
     pub fn from_cartesian(cartesian: CartesianPoint) -> Self {
-        let world_position = conversions::cartesian_to_world(cartesian);
-        let polar = conversions::cartesian_to_polar(cartesian);
-        Self {
-            world_position,
-            cartesian,
-            polar,
-        }
+        Self::from_cartesian_3d(CartesianPoint3D::new(cartesian.x, cartesian.y, 0.0))
     }
 
     pub fn from_polar(polar: PolarPoint) -> Self {
-        let cartesian = conversions::polar_to_cartesian(polar);
-        let world_position = conversions::cartesian_to_world(cartesian);
+        Self::from_cartesian(conversions::polar_to_cartesian(polar))
+    }
+
+    pub fn from_cartesian_3d(cartesian: CartesianPoint3D) -> Self {
+        let cylindrical = conversions::cartesian_3d_to_cylindrical(cartesian);
         Self {
-            world_position,
-            cartesian,
-            polar,
+            world_position: conversions::cartesian_3d_to_world(cartesian),
+            cartesian: CartesianPoint::new(cartesian.x, cartesian.y),
+            polar: PolarPoint::new(cylindrical.r, cylindrical.theta),
+            cartesian_3d: cartesian,
+            cylindrical,
+            spherical: conversions::cartesian_3d_to_spherical(cartesian),
         }
     }
 }
@@ -159,45 +733,522 @@ impl CoordinatePoint {
 /// Утилиты для форматирования координат
 pub mod formatting {
     use super::*;
+    use crate::ucs::CoordinateFrame;
+
+    /// Форматирование длины или координаты в единицах чертежа с заданной точностью.
+    /// Все выводимые длины (панели, подсказки, журнал) проходят через эту функцию.
+    pub fn format_length(value: f64, settings: &CoordinateSettings) -> String {
+        let precision = settings.precision.min(CoordinateSettings::MAX_PRECISION);
+        match settings.linear_unit {
+            LinearUnit::Architectural => format_feet_inches(value, precision),
+            LinearUnit::Fractional => format_fraction(value, precision),
+            _ => {
+                let text = format!("{:.*}", precision, value);
+                // Без «-0.00» для значений, округлившихся до нуля
+                match text.strip_prefix('-') {
+                    Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+                    _ => text,
+                }
+            }
+        }
+    }
+
+    /// Дюймы с дробью `6 1/2`; знаменатель — 2^precision, дробь сокращается
+    pub fn format_fraction(inches: f64, precision: usize) -> String {
+        let denominator = 1u64 << precision;
+        let units = (inches.abs() * denominator as f64).round() as u64;
+        let sign = if inches < 0.0 && units > 0 { "-" } else { "" };
+        let (whole, mut numerator, mut denominator) =
+            (units / denominator, units % denominator, denominator);
+        while numerator > 0 && numerator % 2 == 0 {
+            numerator /= 2;
+            denominator /= 2;
+        }
+        match (whole, numerator) {
+            (_, 0) => format!("{}{}", sign, whole),
+            (0, _) => format!("{}{}/{}", sign, numerator, denominator),
+            _ => format!("{}{} {}/{}", sign, whole, numerator, denominator),
+        }
+    }
+
+    /// Футы и дюймы `3'-6 1/2"` (значение в дюймах)
+    pub fn format_feet_inches(inches: f64, precision: usize) -> String {
+        let denominator = (1u64 << precision) as f64;
+        // Округляем заранее, чтобы 11.999" не превращались в 12"
+        let rounded = (inches.abs() * denominator).round() / denominator;
+        let sign = if inches < 0.0 && rounded > 0.0 {
+            "-"
+        } else {
+            ""
+        };
+        let feet = (rounded / 12.0).floor();
+        format!(
+            "{}{}'-{}\"",
+            sign,
+            feet,
+            format_fraction(rounded - feet * 12.0, precision)
+        )
+    }
 
     /// Форматирование декартовых координат
-    pub fn format_cartesian(point: CartesianPoint) -> String {
-        format!("({:.2}, {:.2})", point.x, point.y)
+    pub fn format_cartesian(point: CartesianPoint, settings: &CoordinateSettings) -> String {
+        format!(
+            "({}, {})",
+            format_length(point.x, settings),
+            format_length(point.y, settings)
+        )
+    }
+
+    /// Форматирование полярных координат (угол — направление в ПСК)
+    pub fn format_polar(point: PolarPoint, settings: &CoordinateSettings) -> String {
+        format!(
+            "(r: {}, θ: {})",
+            format_length(point.r, settings),
+            format_direction(point.theta, settings)
+        )
+    }
+
+    /// Форматирование декартовых координат 3D
+    pub fn format_cartesian_3d(point: CartesianPoint3D, settings: &CoordinateSettings) -> String {
+        format!(
+            "({}, {}, {})",
+            format_length(point.x, settings),
+            format_length(point.y, settings),
+            format_length(point.z, settings)
+        )
+    }
+
+    /// Форматирование цилиндрических координат
+    pub fn format_cylindrical(point: CylindricalPoint, settings: &CoordinateSettings) -> String {
+        format!(
+            "(r: {}, θ: {}, h: {})",
+            format_length(point.r, settings),
+            format_direction(point.theta, settings),
+            format_length(point.h, settings)
+        )
+    }
+
+    /// Форматирование сферических координат (φ — величина угла над плоскостью)
+    pub fn format_spherical(point: SphericalPoint, settings: &CoordinateSettings) -> String {
+        format!(
+            "(ρ: {}, θ: {}, φ: {})",
+            format_length(point.rho, settings),
+            format_direction(point.theta, settings),
+            format_angle(point.phi, settings.angle_unit, settings.precision)
+        )
     }
 
-    /// Форматирование полярных координат
-    pub fn format_polar(point: PolarPoint, angle_unit: AngleUnit) -> String {
+    /// Форматирование точки в текущей системе координат
+    pub fn format_coordinate_point(
+        point: &CoordinatePoint,
+        settings: &CoordinateSettings,
+    ) -> String {
+        match settings.coordinate_system {
+            CoordinateSystem::Cartesian => format_cartesian(point.cartesian, settings),
+            CoordinateSystem::Polar => format_polar(point.polar, settings),
+            CoordinateSystem::Cartesian3D => format_cartesian_3d(point.cartesian_3d, settings),
+            CoordinateSystem::Cylindrical => format_cylindrical(point.cylindrical, settings),
+            CoordinateSystem::Spherical => format_spherical(point.spherical, settings),
+        }
+    }
+
+    /// Расчет и форматирование длины отрезка
+    pub fn format_line_length(start: DVec3, end: DVec3, settings: &CoordinateSettings) -> String {
+        format_length((end - start).length(), settings)
+    }
+
+    /// Форматирование величины угла (в радианах) в выбранных единицах с заданной точностью.
+    /// Румбы описывают только направления, поэтому величина выводится в ГМС;
+    /// в ГМС знаки точности сверх двух становятся долями секунды.
+    pub fn format_angle(angle_rad: f64, angle_unit: AngleUnit, precision: usize) -> String {
+        let precision = precision.min(CoordinateSettings::MAX_PRECISION);
         match angle_unit {
-            AngleUnit::Degrees => {
-                format!("(r: {:.2}, θ: {:.1}°)", point.r, point.theta_degrees())
+            AngleUnit::Degrees => format!("{:.*}°", precision, angle_rad.to_degrees()),
+            AngleUnit::Radians => format!("{:.*} рад", precision, angle_rad),
+            AngleUnit::Gradians => {
+                format!("{:.*}g", precision, angle_rad.to_degrees() * 400.0 / 360.0)
             }
-            AngleUnit::Radians => {
-                format!("(r: {:.2}, θ: {:.3} рад)", point.r, point.theta)
+            AngleUnit::DegreesMinutesSeconds | AngleUnit::SurveyorBearing => {
+                format_dms(angle_rad.to_degrees(), precision.saturating_sub(2))
             }
         }
     }
 
-    /// Расчет и форматирование длины отрезка
-    pub fn format_line_length(start: Vec3, end: Vec3) -> String {
-        let length = (end - start).length();
-        format!("{:.2}", length)
+    /// Форматирование направления в ПСК (радианы против часовой стрелки от оси X)
+    /// с учетом единиц, нулевого направления и направления отсчета
+    pub fn format_direction(direction: f64, settings: &CoordinateSettings) -> String {
+        match settings.angle_unit {
+            AngleUnit::SurveyorBearing => format_bearing(direction),
+            unit => format_angle(
+                settings.angle_from_direction(direction),
+                unit,
+                settings.precision,
+            ),
+        }
     }
 
-    /// Расчет и форматирование угла наклона отрезка
-    pub fn format_line_angle(start: Vec3, end: Vec3, angle_unit: AngleUnit) -> String {
-        let direction = end - start;
-        let angle_rad = direction.z.atan2(direction.x);
-        match angle_unit {
-            AngleUnit::Degrees => format!("{:.1}°", angle_rad.to_degrees()),
-            AngleUnit::Radians => format!("{:.3} рад", angle_rad),
+    /// Градусы в виде `30°15'20"` или `30°15'20.25"` с `decimals` знаками секунд
+    pub fn format_dms(degrees: f64, decimals: usize) -> String {
+        let sign = if degrees < 0.0 { "-" } else { "" };
+        let parts = 10u64.pow(decimals as u32);
+        let total = (degrees.abs() * 3600.0 * parts as f64).round() as u64;
+        let whole_seconds = total / parts;
+        let seconds = match decimals {
+            0 => format!("{}", whole_seconds % 60),
+            _ => format!(
+                "{}.{:0width$}",
+                whole_seconds % 60,
+                total % parts,
+                width = decimals
+            ),
+        };
+        format!(
+            "{}{}°{}'{}\"",
+            sign,
+            whole_seconds / 3600,
+            whole_seconds / 60 % 60,
+            seconds
+        )
+    }
+
+    /// Румб направления: `N 45°30' E`. Север — ось Y ПСК, восток — ось X ПСК.
+    pub fn format_bearing(direction: f64) -> String {
+        // Азимут: от севера по часовой стрелке, в секундах для точного деления
+        let azimuth = (90.0 - direction.to_degrees()).rem_euclid(360.0);
+        let seconds = (azimuth * 3600.0).round() as u64 % (360 * 3600);
+        let quarter = 90 * 3600;
+        let (north_south, angle, east_west) = match seconds / quarter {
+            0 => ('N', seconds, 'E'),
+            1 => ('S', 2 * quarter - seconds, 'E'),
+            2 => ('S', seconds - 2 * quarter, 'W'),
+            _ => ('N', 4 * quarter - seconds, 'W'),
+        };
+        let mut text = format!("{} {}°{}'", north_south, angle / 3600, angle / 60 % 60);
+        if angle % 60 != 0 {
+            text.push_str(&format!("{}\"", angle % 60));
+        }
+        format!("{} {}", text, east_west)
+    }
+
+    /// Широта и долгота: `55°45'07.123" N, 37°37'12.456" E`
+    /// (тысячные доли секунды — около 3 см)
+    pub fn format_geographic(point: geodesy::GeoPoint) -> String {
+        let part = |radians: f64, positive: char, negative: char| {
+            let degrees = radians.to_degrees();
+            let thousandths = (degrees.abs() * 3_600_000.0).round() as u64;
+            format!(
+                "{}°{:02}'{:02}.{:03}\" {}",
+                thousandths / 3_600_000,
+                thousandths / 60_000 % 60,
+                thousandths / 1000 % 60,
+                thousandths % 1000,
+                if degrees < 0.0 { negative } else { positive }
+            )
+        };
+        format!(
+            "{}, {}",
+            part(point.latitude, 'N', 'S'),
+            part(point.longitude, 'E', 'W')
+        )
+    }
+
+    /// Восток и север в проекции, в метрах с точностью до миллиметра
+    pub fn format_projected(grid: DVec2) -> String {
+        format!("E {:.3} м, N {:.3} м", grid.x, grid.y)
+    }
+
+    /// Геодезические координаты мировой точки для строки курсора;
+    /// `None`, если геопривязка не задана или ее вывод выключен
+    pub fn format_georeferenced(world_pos: DVec3, settings: &CoordinateSettings) -> Option<String> {
+        let georeference = &settings.georeference;
+        let projection = georeference.projection?;
+        let cartesian = conversions::world_to_cartesian(world_pos);
+        let grid = georeference.drawing_to_grid(cartesian, settings.linear_unit);
+        match georeference.readout {
+            GeoReadout::Off => None,
+            GeoReadout::Geographic => Some(format!(
+                "{} ({})",
+                format_geographic(geodesy::projected_to_geographic(grid, projection)),
+                projection.datum_label()
+            )),
+            GeoReadout::Projected => Some(format!(
+                "{} ({})",
+                format_projected(grid),
+                projection.label()
+            )),
         }
     }
+
+    /// Расчет и форматирование направления отрезка в ПСК
+    pub fn format_line_angle(
+        start: DVec3,
+        end: DVec3,
+        frame: &CoordinateFrame,
+        settings: &CoordinateSettings,
+    ) -> String {
+        format_direction(frame.line_angle(start, end), settings)
+    }
+}
+
+/// Разбор координат, вводимых с клавиатуры
+pub mod parsing {
+    use super::*;
+    use crate::{
+        expression::{self, Context},
+        ucs::CoordinateFrame,
+    };
+
+    /// Разбор точки в любой из систем координат:
+    /// - `x, y` и `x, y, z` — декартовы в ПСК (z — высота);
+    /// - `r < θ` и `r < θ, h` — полярные и цилиндрические относительно полюса;
+    /// - `ρ < θ < φ` — сферические относительно полюса (φ — угол над плоскостью).
+    ///
+    /// Углы θ разбираются по правилам [`parse_direction`], φ — по [`parse_angle`].
+    /// Префикс `@` задает смещение от базовой точки в осях ПСК.
+    /// Каждая составляющая может быть выражением: `W/2, 10*sqrt(2)`, `5 < 30d`.
+    /// Точки в геодезических координатах — см. [`parse_georeferenced_point`].
+    pub fn parse_point(
+        input: &str,
+        context: &Context,
+        base: Option<DVec3>,
+        frame: &CoordinateFrame,
+        settings: &CoordinateSettings,
+    ) -> Result<DVec3, String> {
+        let input = input.trim();
+        if let Some(point) = parse_georeferenced_point(input, context, settings)? {
+            return Ok(point);
+        }
+        let (relative, body) = match input.strip_prefix('@') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let evaluate = |part: &str| expression::evaluate(part, context).map_err(|e| e.to_string());
+        let wrong_format =
+            || "ожидается «x, y», «x, y, z», «r < θ», «r < θ, h» или «ρ < θ < φ»".to_string();
+
+        let polar_parts = expression::split_top_level(body, '<');
+        let local = match polar_parts.as_slice() {
+            [rho, theta, phi] => conversions::spherical_to_cartesian_3d(SphericalPoint {
+                rho: evaluate(rho)?,
+                theta: parse_direction(theta, context, settings)?,
+                phi: parse_angle(phi, context)?,
+            }),
+            [r, rest] => {
+                let (theta, h) = match expression::split_top_level(rest, ',').as_slice() {
+                    [theta] => (*theta, 0.0),
+                    [theta, h] => (*theta, evaluate(h)?),
+                    _ => return Err(wrong_format()),
+                };
+                conversions::cylindrical_to_cartesian_3d(CylindricalPoint {
+                    r: evaluate(r)?,
+                    theta: parse_direction(theta, context, settings)?,
+                    h,
+                })
+            }
+            [cartesian] => match expression::split_top_level(cartesian, ',').as_slice() {
+                [x, y] => CartesianPoint3D::new(evaluate(x)?, evaluate(y)?, 0.0),
+                [x, y, z] => CartesianPoint3D::new(evaluate(x)?, evaluate(y)?, evaluate(z)?),
+                _ => return Err(wrong_format()),
+            },
+            _ => return Err(wrong_format()),
+        };
+
+        let offset = frame.vector_3d_to_world(local);
+        match (relative, base) {
+            (false, _) if polar_parts.len() > 1 => Ok(frame.pole + offset),
+            (false, _) => Ok(frame.origin + offset),
+            (true, Some(base)) => Ok(base + offset),
+            (true, None) => Err("нет базовой точки для относительного ввода".to_string()),
+        }
+    }
+
+    /// Разбор точки по геопривязке чертежа:
+    /// - `geo B, L[, h]` — широта и долгота в датуме проекции (градусы или ГМС,
+    ///   с необязательными `N`/`S` и `E`/`W`: `geo 55°45'07" N, 37°37'12" E`);
+    /// - `grid E, N[, h]` — восток и север в проекции, в метрах.
+    ///
+    /// Высота h — в единицах чертежа. `Ok(None)`, если префикса нет.
+    pub fn parse_georeferenced_point(
+        input: &str,
+        context: &Context,
+        settings: &CoordinateSettings,
+    ) -> Result<Option<DVec3>, String> {
+        let (geographic, body) = match input.split_once(char::is_whitespace) {
+            Some((prefix, body)) if ["geo", "гео"].contains(&prefix.to_lowercase().as_str()) => {
+                (true, body)
+            }
+            Some((prefix, body)) if ["grid", "сетка"].contains(&prefix.to_lowercase().as_str()) => {
+                (false, body)
+            }
+            _ => return Ok(None),
+        };
+        let georeference = &settings.georeference;
+        let Some(projection) = georeference.projection else {
+            return Err("геопривязка не задана: выберите проекцию".to_string());
+        };
+        let evaluate = |part: &str| expression::evaluate(part, context).map_err(|e| e.to_string());
+
+        let parts = expression::split_top_level(body, ',');
+        let (first, second, height) = match parts.as_slice() {
+            [first, second] => (*first, *second, 0.0),
+            [first, second, height] => (*first, *second, evaluate(height)?),
+            _ if geographic => return Err("ожидается «geo B, L[, h]»".to_string()),
+            _ => return Err("ожидается «grid E, N[, h]»".to_string()),
+        };
+        let grid = if geographic {
+            let latitude = parse_geographic_angle(first, ('N', 'S'))?;
+            let longitude = parse_geographic_angle(second, ('E', 'W'))?;
+            if latitude.abs() > 90.0 {
+                return Err("широта должна быть от -90° до 90°".to_string());
+            }
+            geodesy::geographic_to_projected(
+                geodesy::GeoPoint::from_degrees(latitude, longitude),
+                projection,
+            )
+        } else {
+            DVec2::new(evaluate(first)?, evaluate(second)?)
+        };
+        let drawing = georeference.grid_to_drawing(grid, settings.linear_unit);
+        Ok(Some(
+            conversions::cartesian_to_world(drawing).with_y(height),
+        ))
+    }
+
+    /// Широта или долгота в градусах: `55.75`, `55°45'07.5"`, `37.62 E`, `S 33.9`.
+    /// Буква полушария `negative` меняет знак.
+    fn parse_geographic_angle(
+        input: &str,
+        (positive, negative): (char, char),
+    ) -> Result<f64, String> {
+        let input = input.trim();
+        let hemisphere =
+            |c: char| c.to_ascii_uppercase() == positive || c.to_ascii_uppercase() == negative;
+        let (sign, body) = match (input.chars().next(), input.chars().next_back()) {
+            (Some(c), _) if hemisphere(c) => (c.to_ascii_uppercase(), &input[c.len_utf8()..]),
+            (_, Some(c)) if hemisphere(c) => {
+                (c.to_ascii_uppercase(), &input[..input.len() - c.len_utf8()])
+            }
+            _ => (positive, input),
+        };
+        let body = body.trim();
+        let degrees = match parse_dms(body) {
+            Some(degrees) => degrees,
+            None => body
+                .trim_end_matches(['°', 'd'])
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("не удалось разобрать «{}» как градусы", input))?,
+        };
+        Ok(if sign == negative { -degrees } else { degrees })
+    }
+
+    /// Разбор величины угла в радианы: ГМС (`30°15'20"`) или выражение
+    /// в текущих единицах (`45`, `100g`, `pi/4r`)
+    pub fn parse_angle(input: &str, context: &Context) -> Result<f64, String> {
+        match parse_dms(input) {
+            Some(degrees) => Ok(degrees.to_radians()),
+            None => {
+                let value = expression::evaluate(input, context).map_err(|e| e.to_string())?;
+                Ok(value * context.angle_unit.radians_per_unit())
+            }
+        }
+    }
+
+    /// Разбор направления в радианы против часовой стрелки от оси X ПСК.
+    /// Принимает румб (`N 45°30' E`) или величину угла по правилам [`parse_angle`],
+    /// которая отсчитывается от выбранного нулевого направления в выбранную сторону.
+    pub fn parse_direction(
+        input: &str,
+        context: &Context,
+        settings: &CoordinateSettings,
+    ) -> Result<f64, String> {
+        let input = input.trim();
+        if let Some(direction) = parse_bearing(input)? {
+            return Ok(direction);
+        }
+        Ok(settings.direction_from_angle(parse_angle(input, context)?))
+    }
+
+    /// Разбор записи `30°15'20"` в градусы. Градусы обозначаются `°` или `d`;
+    /// любую из частей можно опустить (`15'`, `30°20"`), но порядок обязателен.
+    /// Возвращает `None`, если строка не похожа на ГМС (нет ни `'`, ни `"`).
+    pub fn parse_dms(input: &str) -> Option<f64> {
+        let input = input.trim();
+        if !input.contains(['\'', '"']) {
+            return None;
+        }
+        let (sign, mut rest) = match input.strip_prefix('-') {
+            Some(rest) => (-1.0, rest),
+            None => (1.0, input.strip_prefix('+').unwrap_or(input)),
+        };
+
+        let mut degrees = 0.0;
+        for (marks, divisor) in [
+            (&['°', 'd'][..], 1.0),
+            (&['\''][..], 60.0),
+            (&['"'][..], 3600.0),
+        ] {
+            if let Some((end, mark)) = rest.char_indices().find(|(_, c)| marks.contains(c)) {
+                let value: f64 = rest[..end].trim().parse().ok()?;
+                if value < 0.0 {
+                    return None;
+                }
+                degrees += value / divisor;
+                rest = &rest[end + mark.len_utf8()..];
+            }
+        }
+        rest.trim().is_empty().then_some(sign * degrees)
+    }
+
+    /// Разбор румба `N 45°30' E` (`S30W`, `n 12.5 e`) в направление в ПСК.
+    /// Угол внутри — градусы или ГМС. `Ok(None)`, если строка не является румбом.
+    pub fn parse_bearing(input: &str) -> Result<Option<f64>, String> {
+        let input = input.trim();
+        let mut chars = input.chars();
+        let (Some(first), Some(last)) = (chars.next(), chars.next_back()) else {
+            return Ok(None);
+        };
+        let north = match first.to_ascii_uppercase() {
+            'N' => true,
+            'S' => false,
+            _ => return Ok(None),
+        };
+        let east = match last.to_ascii_uppercase() {
+            'E' => true,
+            'W' => false,
+            _ => return Ok(None),
+        };
+
+        let body = chars.as_str().trim();
+        let degrees = match parse_dms(body) {
+            Some(degrees) => degrees,
+            None => match body.trim_end_matches(['°', 'd']).trim().parse::<f64>() {
+                Ok(degrees) => degrees,
+                // Например, переменная `SIZE` — пусть ее разберет вычислитель выражений
+                Err(_) => return Ok(None),
+            },
+        };
+        if !(0.0..=90.0).contains(&degrees) {
+            return Err("угол румба должен быть от 0° до 90°".to_string());
+        }
+
+        let azimuth = match (north, east) {
+            (true, true) => degrees,
+            (false, true) => 180.0 - degrees,
+            (false, false) => 180.0 + degrees,
+            (true, false) => 360.0 - degrees,
+        };
+        Ok(Some((90.0 - azimuth).to_radians()))
+    }
 }
 
 /// Система для обработки изменений системы координат (использует события из events.rs)
 pub fn handle_coordinate_system_events(
     mut coordinate_events: EventReader<crate::events::CoordinateSystemChangeEvent>,
     mut angle_events: EventReader<crate::events::AngleUnitChangeEvent>,
+    mut orientation_events: EventReader<crate::events::AngleOrientationEvent>,
+    mut units_events: EventReader<crate::events::UnitsEvent>,
+    mut georeference_events: EventReader<crate::events::GeoreferenceEvent>,
     mut settings: ResMut<CoordinateSettings>,
 ) {
     for event in coordinate_events.read() {
@@ -209,30 +1260,523 @@ pub fn handle_coordinate_system_events(
         settings.angle_unit = event.new_unit;
         info!("Angle unit changed to: {:?}", event.new_unit);
     }
+
+    for event in orientation_events.read() {
+        match *event {
+            crate::events::AngleOrientationEvent::SetBase(base) => settings.angle_base = base,
+            crate::events::AngleOrientationEvent::SetDirection(direction) => {
+                settings.angle_direction = direction
+            }
+        }
+        info!(
+            "Angle orientation changed to: {:?}, {:?}",
+            settings.angle_base, settings.angle_direction
+        );
+    }
+
+    for event in units_events.read() {
+        match *event {
+            crate::events::UnitsEvent::SetLinearUnit(unit) => settings.linear_unit = unit,
+            crate::events::UnitsEvent::IncreasePrecision => {
+                settings.precision = (settings.precision + 1).min(CoordinateSettings::MAX_PRECISION)
+            }
+            crate::events::UnitsEvent::DecreasePrecision => {
+                settings.precision = settings.precision.saturating_sub(1)
+            }
+        }
+        info!(
+            "Linear units changed to: {:?}, precision {}",
+            settings.linear_unit,
+            settings.precision_label()
+        );
+    }
+
+    for event in georeference_events.read() {
+        let georeference = &mut settings.georeference;
+        match *event {
+            crate::events::GeoreferenceEvent::CycleProjection => {
+                georeference.projection = Projection::cycle(georeference.projection);
+            }
+            crate::events::GeoreferenceEvent::ShiftZone(step) => {
+                georeference.projection = georeference
+                    .projection
+                    .map(|projection| projection.shift_zone(step));
+            }
+            crate::events::GeoreferenceEvent::ToggleHemisphere => {
+                if let Some(Projection::Utm { north, .. }) = &mut georeference.projection {
+                    *north = !*north;
+                }
+            }
+            crate::events::GeoreferenceEvent::CycleReadout => {
+                georeference.readout = georeference.readout.next();
+            }
+            crate::events::GeoreferenceEvent::SetOrigin(origin) => georeference.origin = origin,
+            crate::events::GeoreferenceEvent::SetRotation(rotation) => {
+                georeference.rotation = rotation
+            }
+        }
+        info!("Georeference changed to: {:?}", settings.georeference);
+    }
+}
+
+/// Поля геопривязки: начало чертежа «E, N» и поворот чертежа
+pub fn submit_georeference_field(input: &mut FieldInput) -> Result<String, String> {
+    if input.kind == InputFieldKind::GeoRotation {
+        let rotation = input.direction_angle(input.text)?;
+        input
+            .events
+            .georeference
+            .write(crate::events::GeoreferenceEvent::SetRotation(rotation));
+        return Ok(format!(
+            "= {}",
+            formatting::format_angle(
+                rotation,
+                input.coordinate_settings.angle_unit,
+                input.coordinate_settings.precision
+            )
+        ));
+    }
+    match crate::expression::split_top_level(input.text, ',').as_slice() {
+        [easting, northing] => {
+            let origin = DVec2::new(input.evaluate(easting)?, input.evaluate(northing)?);
+            input
+                .events
+                .georeference
+                .write(crate::events::GeoreferenceEvent::SetOrigin(origin));
+            Ok(format!("= {}", formatting::format_projected(origin)))
+        }
+        _ => Err("ожидается «E, N»".to_string()),
+    }
 }
 
 /// Клавиатурные команды для переключения систем координат
 pub fn keyboard_coordinate_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: crate::keybindings::ActionInput,
     mut coordinate_events: EventWriter<crate::events::CoordinateSystemChangeEvent>,
     mut angle_events: EventWriter<crate::events::AngleUnitChangeEvent>,
     settings: Res<CoordinateSettings>,
 ) {
-    // Клавиша X - переключение системы координат
-    if keyboard_input.just_pressed(KeyCode::KeyX) {
-        let new_system = match settings.coordinate_system {
-            CoordinateSystem::Cartesian => CoordinateSystem::Polar,
-            CoordinateSystem::Polar => CoordinateSystem::Cartesian,
-        };
+    // Клавиша X - переключение системы координат по кругу
+    if input.just_pressed(crate::keybindings::InputAction::ToggleCoordinateSystem) {
+        let new_system = settings.coordinate_system.next();
         coordinate_events.send(crate::events::CoordinateSystemChangeEvent { new_system });
     }
 
-    // Клавиша U - переключение единиц измерения углов
-    if keyboard_input.just_pressed(KeyCode::KeyU) {
-        let new_unit = match settings.angle_unit {
-            AngleUnit::Degrees => AngleUnit::Radians,
-            AngleUnit::Radians => AngleUnit::Degrees,
-        };
+    // Клавиша U - переключение единиц измерения углов по кругу
+    if input.just_pressed(crate::keybindings::InputAction::ToggleAngleUnit) {
+        let new_unit = settings.angle_unit.next();
         angle_events.send(crate::events::AngleUnitChangeEvent { new_unit });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::formatting::*;
+    use super::parsing::*;
+    use super::*;
+    use crate::expression::{Context, Variables};
+
+    fn settings(unit: AngleUnit, base: AngleBase, direction: AngleDirection) -> CoordinateSettings {
+        CoordinateSettings {
+            angle_unit: unit,
+            angle_base: base,
+            angle_direction: direction,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn dms_round_trip() {
+        let degrees = 30.0 + 15.0 / 60.0 + 20.25 / 3600.0;
+        assert_eq!(format_dms(degrees, 0), "30°15'20\"");
+        assert_eq!(format_dms(degrees, 2), "30°15'20.25\"");
+        assert_eq!(format_dms(-0.5, 0), "-0°30'0\"");
+        assert_eq!(format_dms(59.0 / 60.0 + 59.999 / 3600.0, 1), "1°0'0.0\"");
+        assert!((parse_dms("30°15'20\"").unwrap() - 30.255_555).abs() < 1e-5);
+        assert!((parse_dms("30d15'").unwrap() - 30.25).abs() < 1e-9);
+        assert_eq!(parse_dms("-15'"), Some(-0.25));
+        assert_eq!(parse_dms("45"), None);
+        assert_eq!(parse_dms("20\"15'"), None);
+    }
+
+    #[test]
+    fn bearings_round_trip() {
+        assert_eq!(format_bearing(45f64.to_radians()), "N 45°0' E");
+        assert_eq!(format_bearing(-120f64.to_radians()), "S 30°0' W");
+        assert_eq!(format_bearing(100.5f64.to_radians()), "N 10°30' W");
+
+        let direction = parse_bearing("N 45°30' E").unwrap().unwrap();
+        assert!((direction.to_degrees() - 44.5).abs() < 1e-4);
+        let direction = parse_bearing("s30w").unwrap().unwrap();
+        assert!((direction.to_degrees() + 120.0).abs() < 1e-4);
+        assert_eq!(parse_bearing("SIZE"), Ok(None));
+        assert!(parse_bearing("N 95 E").is_err());
+    }
+
+    #[test]
+    fn lengths_follow_units_and_precision() {
+        let mut settings = CoordinateSettings::default();
+        assert_eq!(format_length(12.345, &settings), "12.35");
+        assert_eq!(format_length(-0.001, &settings), "0.00");
+        settings.precision = 0;
+        assert_eq!(format_length(12.5, &settings), "12");
+
+        settings.linear_unit = LinearUnit::Architectural;
+        settings.precision = 4;
+        assert_eq!(format_length(42.5, &settings), "3'-6 1/2\"");
+        assert_eq!(format_length(11.99, &settings), "1'-0\"");
+        assert_eq!(format_length(-7.25, &settings), "-0'-7 1/4\"");
+
+        settings.linear_unit = LinearUnit::Fractional;
+        assert_eq!(format_length(6.5, &settings), "6 1/2");
+        assert_eq!(format_length(0.0625, &settings), "1/16");
+        assert_eq!(settings.precision_label(), "1/16");
+    }
+
+    #[test]
+    fn typed_points_in_3d_systems() {
+        let vars = Variables::default();
+        let context = Context::new(&vars, AngleUnit::Degrees);
+        let settings = CoordinateSettings::default();
+        let frame = crate::ucs::CoordinateFrame::default();
+        let parse = |text: &str, base| parse_point(text, &context, base, &frame, &settings);
+
+        assert!(parse("1, 2, 3", None)
+            .unwrap()
+            .abs_diff_eq(DVec3::new(1.0, 3.0, 2.0), 1e-9));
+        assert!(parse("2 < 90, 5", None)
+            .unwrap()
+            .abs_diff_eq(DVec3::new(0.0, 5.0, 2.0), 1e-9));
+        assert!(parse("2 < 0 < 90", None)
+            .unwrap()
+            .abs_diff_eq(DVec3::new(0.0, 2.0, 0.0), 1e-9));
+        assert!(parse("@0, 0, 1", Some(DVec3::ONE))
+            .unwrap()
+            .abs_diff_eq(DVec3::new(1.0, 2.0, 1.0), 1e-9));
+        assert!(parse("1, 2, 3, 4", None).is_err());
+
+        let point = frame.coordinate_point(DVec3::new(3.0, 4.0, 0.0));
+        assert!((point.cylindrical.r - 3.0).abs() < 1e-5);
+        assert!((point.cylindrical.h - 4.0).abs() < 1e-5);
+        assert!((point.spherical.rho - 5.0).abs() < 1e-5);
+        assert!((point.spherical.phi - 4f64.atan2(3.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn directions_follow_base_and_orientation() {
+        let vars = Variables::default();
+        let context = Context::new(&vars, AngleUnit::Degrees);
+        let north_cw = settings(
+            AngleUnit::Degrees,
+            AngleBase::North,
+            AngleDirection::Clockwise,
+        );
+
+        // Азимут 90° (по часовой от севера) — это ось X
+        let direction = parse_direction("90", &context, &north_cw).unwrap();
+        assert!(direction.abs() < 1e-6);
+        assert_eq!(format_direction(0.0, &north_cw), "90.00°");
+        assert_eq!(format_direction(-10f64.to_radians(), &north_cw), "100.00°");
+
+        let gradians = settings(
+            AngleUnit::Gradians,
+            AngleBase::East,
+            AngleDirection::CounterClockwise,
+        );
+        let context = Context::new(&vars, AngleUnit::Gradians);
+        let direction = parse_direction("100", &context, &gradians).unwrap();
+        assert!((direction - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(
+            format_direction(-std::f64::consts::FRAC_PI_2, &gradians),
+            "300.00g"
+        );
+    }
+
+    #[test]
+    fn angles_follow_precision() {
+        let quarter = std::f64::consts::FRAC_PI_2;
+        assert_eq!(format_angle(quarter, AngleUnit::Degrees, 0), "90°");
+        assert_eq!(format_angle(quarter, AngleUnit::Degrees, 3), "90.000°");
+        assert_eq!(format_angle(quarter, AngleUnit::Radians, 4), "1.5708 рад");
+        assert_eq!(format_angle(quarter, AngleUnit::Gradians, 1), "100.0g");
+        let angle = 12.5f64.to_radians();
+        assert_eq!(
+            format_angle(angle, AngleUnit::DegreesMinutesSeconds, 2),
+            "12°30'0\""
+        );
+        assert_eq!(
+            format_angle(angle, AngleUnit::DegreesMinutesSeconds, 3),
+            "12°30'0.0\""
+        );
+
+        let mut settings = settings(
+            AngleUnit::Degrees,
+            AngleBase::East,
+            AngleDirection::CounterClockwise,
+        );
+        settings.precision = 1;
+        assert_eq!(format_direction(quarter / 3.0, &settings), "30.0°");
+    }
+
+    /// Детерминированный генератор для проверки свойств на множестве точек
+    struct Samples(u64);
+
+    impl Samples {
+        fn next(&mut self) -> f64 {
+            // xorshift64*: равномерное значение в [0, 1)
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn range(&mut self, min: f64, max: f64) -> f64 {
+            min + (max - min) * self.next()
+        }
+
+        /// Точка генплана: координаты порядка 500000 с долями миллиметра
+        fn site_point(&mut self) -> DVec3 {
+            DVec3::new(
+                self.range(-600_000.0, 600_000.0),
+                self.range(-100.0, 100.0),
+                self.range(-600_000.0, 600_000.0),
+            )
+        }
+    }
+
+    const SAMPLES: usize = 2000;
+    /// Допуск 1 мкм: на порядки точнее миллиметра на расстоянии 600 км
+    const TOLERANCE: f64 = 1e-6;
+
+    #[test]
+    fn conversions_round_trip_for_far_points() {
+        let mut samples = Samples(0x5eed);
+        for _ in 0..SAMPLES {
+            let world = samples.site_point();
+            let cartesian = conversions::world_to_cartesian_3d(world);
+            assert!(conversions::cartesian_3d_to_world(cartesian).abs_diff_eq(world, TOLERANCE));
+
+            let flat = CartesianPoint::new(cartesian.x, cartesian.y);
+            let back = conversions::polar_to_cartesian(conversions::cartesian_to_polar(flat));
+            assert!((back.x - flat.x).abs() < TOLERANCE && (back.y - flat.y).abs() < TOLERANCE);
+
+            let back = conversions::cylindrical_to_cartesian_3d(
+                conversions::cartesian_3d_to_cylindrical(cartesian),
+            );
+            assert!(conversions::cartesian_3d_to_world(back).abs_diff_eq(world, TOLERANCE));
+
+            let back = conversions::spherical_to_cartesian_3d(
+                conversions::cartesian_3d_to_spherical(cartesian),
+            );
+            assert!(conversions::cartesian_3d_to_world(back).abs_diff_eq(world, TOLERANCE));
+        }
+    }
+
+    #[test]
+    fn repeated_round_trips_do_not_drift() {
+        let mut samples = Samples(0xd81f7);
+        for _ in 0..SAMPLES / 10 {
+            let start = conversions::world_to_cartesian(samples.site_point());
+            let mut point = start;
+            for _ in 0..100 {
+                point = conversions::polar_to_cartesian(conversions::cartesian_to_polar(point));
+            }
+            assert!((point.x - start.x).abs() < TOLERANCE);
+            assert!((point.y - start.y).abs() < TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn ucs_frames_round_trip_for_far_points() {
+        let mut samples = Samples(0xf4a3e);
+        for _ in 0..SAMPLES {
+            let frame = crate::ucs::CoordinateFrame {
+                origin: samples.site_point(),
+                angle: samples.range(-TAU, TAU),
+                mirrored: samples.next() < 0.5,
+                pole: samples.site_point(),
+            };
+            let world = samples.site_point();
+
+            // Плоские координаты возвращают точку на высоте начала ПСК или полюса
+            let back = frame.cartesian_point(frame.local_cartesian(world));
+            assert!(back.abs_diff_eq(world.with_y(frame.origin.y), TOLERANCE));
+            let back = frame.polar_point(frame.local_polar(world));
+            assert!(back.abs_diff_eq(world.with_y(frame.pole.y), TOLERANCE));
+
+            let local = frame.coordinate_point(world).cartesian_3d;
+            let back = frame.origin + frame.vector_3d_to_world(local);
+            assert!(back.abs_diff_eq(world, TOLERANCE));
+        }
+    }
+
+    #[test]
+    fn typed_site_coordinates_keep_millimeters() {
+        let vars = Variables::default();
+        let context = Context::new(&vars, AngleUnit::Degrees);
+        let settings = CoordinateSettings {
+            precision: 3,
+            ..default()
+        };
+        let frame = crate::ucs::CoordinateFrame::default();
+
+        let point =
+            parse_point("500000.123, 6100000.456", &context, None, &frame, &settings).unwrap();
+        assert_eq!(
+            format_cartesian(frame.local_cartesian(point), &settings),
+            "(500000.123, 6100000.456)"
+        );
+        let point = parse_point("@0.001 < 45", &context, Some(point), &frame, &settings).unwrap();
+        let polar = frame.coordinate_point(point);
+        assert_eq!(
+            format_cartesian(polar.cartesian, &settings),
+            "(500000.124, 6100000.457)"
+        );
+    }
+
+    #[test]
+    fn transverse_mercator_matches_reference_values() {
+        use geodesy::*;
+        // На осевом меридиане северная координата — длина дуги меридиана WGS 84
+        let utm31 = Projection::Utm {
+            zone: 31,
+            north: true,
+        };
+        let grid = geographic_to_projected(GeoPoint::from_degrees(45.0, 3.0), utm31);
+        assert!(grid.abs_diff_eq(DVec2::new(500_000.0, 0.9996 * 4_984_944.378), 1e-3));
+
+        // CN Tower: UTM 17N, 630084 E, 4833438 N
+        let tower = GeoPoint::from_degrees(
+            43.0 + 38.0 / 60.0 + 33.24 / 3600.0,
+            -(79.0 + 23.0 / 60.0 + 13.7 / 3600.0),
+        );
+        let utm17 = Projection::Utm {
+            zone: 17,
+            north: true,
+        };
+        let grid = geographic_to_projected(tower, utm17);
+        assert!(
+            grid.abs_diff_eq(DVec2::new(630_084.0, 4_833_438.0), 1.0),
+            "{grid}"
+        );
+
+        // Номер зоны Гаусса–Крюгера — первые цифры ординаты
+        let gk7 = Projection::GaussKruger { zone: 7 };
+        let grid = geographic_to_projected(GeoPoint::from_degrees(55.75, 39.0), gk7);
+        assert!((grid.x - 7_500_000.0).abs() < 1e-6);
+        assert_eq!(
+            Projection::cycle(Some(Projection::Utm {
+                zone: 37,
+                north: true
+            })),
+            Some(gk7)
+        );
+    }
+
+    #[test]
+    fn projections_round_trip_across_zone() {
+        use geodesy::*;
+        let mut samples = Samples(0x6e0_2025);
+        for projection in [
+            Projection::Utm {
+                zone: 37,
+                north: true,
+            },
+            Projection::Utm {
+                zone: 19,
+                north: false,
+            },
+            Projection::GaussKruger { zone: 7 },
+        ] {
+            let central = projection.central_meridian();
+            let southern = matches!(projection, Projection::Utm { north: false, .. });
+            for _ in 0..SAMPLES {
+                let latitude = samples.range(0.0, 80.0) * if southern { -1.0 } else { 1.0 };
+                let longitude = central + samples.range(-3.5, 3.5);
+                let point = GeoPoint::from_degrees(latitude, longitude);
+                let grid = geographic_to_projected(point, projection);
+                let back = projected_to_geographic(grid, projection);
+                // 1e-11 рад — меньше 0.1 мм на поверхности Земли
+                assert!(
+                    (back.latitude - point.latitude).abs() < 1e-11,
+                    "{point:?} -> {back:?}"
+                );
+                assert!(
+                    (back.longitude - point.longitude).abs() < 1e-11,
+                    "{point:?} -> {back:?}"
+                );
+                let again = geographic_to_projected(back, projection);
+                assert!(again.abs_diff_eq(grid, TOLERANCE));
+            }
+        }
+    }
+
+    #[test]
+    fn georeferenced_points_are_parsed_and_formatted() {
+        let vars = Variables::default();
+        let context = Context::new(&vars, AngleUnit::Degrees);
+        let settings = CoordinateSettings {
+            linear_unit: LinearUnit::Millimeters,
+            georeference: Georeference {
+                projection: Some(Projection::Utm {
+                    zone: 17,
+                    north: true,
+                }),
+                origin: DVec2::new(630_000.0, 4_833_000.0),
+                rotation: 30f64.to_radians(),
+                readout: GeoReadout::Geographic,
+            },
+            ..default()
+        };
+        let frame = crate::ucs::CoordinateFrame::default();
+
+        // Сетка -> чертеж в миллиметрах с поворотом и обратно
+        let point = parse_point(
+            "grid 630084, 4833438, 1500",
+            &context,
+            None,
+            &frame,
+            &settings,
+        )
+        .unwrap();
+        assert!((point.y - 1500.0).abs() < 1e-9);
+        let grid = settings
+            .georeference
+            .drawing_to_grid(conversions::world_to_cartesian(point), settings.linear_unit);
+        assert!(grid.abs_diff_eq(DVec2::new(630_084.0, 4_833_438.0), 1e-9));
+        // 1 м сетки — 1000 единиц чертежа
+        assert!(
+            (grid.distance(settings.georeference.origin) * 1000.0 - point.with_y(0.0).length())
+                .abs()
+                < 1e-6
+        );
+
+        let geo = parse_point(
+            "geo 43°38'33.24\" N, 79°23'13.7\" W",
+            &context,
+            None,
+            &frame,
+            &settings,
+        )
+        .unwrap();
+        // Табличные координаты округлены до метра
+        assert!(geo.with_y(0.0).distance(point.with_y(0.0)) < 1000.0);
+        assert_eq!(
+            format_georeferenced(geo, &settings).unwrap(),
+            "43°38'33.240\" N, 79°23'13.700\" W (WGS 84)"
+        );
+        let same = parse_point(
+            "geo 43.6425667, -79.3871389",
+            &context,
+            None,
+            &frame,
+            &settings,
+        )
+        .unwrap();
+        assert!(same.distance(geo) < 10.0);
+
+        assert!(parse_point("geo 95, 10", &context, None, &frame, &settings).is_err());
+        let unreferenced = CoordinateSettings::default();
+        assert!(parse_point("geo 43, -79", &context, None, &frame, &unreferenced).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use bevy::prelude::*;
+
+use crate::{
+    coordinate_systems::{parsing, CoordinateSettings},
+    events::DimensionStyleEvent,
+    expression,
+    input_field::{FieldInput, InputFieldKind},
+};
+
+/// Обозначение конца размерной линии
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArrowKind {
+    /// Закрашиваемая стрелка (контур треугольника)
+    Closed,
+    /// Открытая стрелка из двух штрихов
+    Open,
+    /// Засечка под 45°, как на строительных чертежах
+    Tick,
+    Dot,
+}
+
+impl ArrowKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ArrowKind::Closed => "закрытая стрелка",
+            ArrowKind::Open => "открытая стрелка",
+            ArrowKind::Tick => "засечка",
+            ArrowKind::Dot => "точка",
+        }
+    }
+
+    pub fn cycle(self) -> Self {
+        match self {
+            ArrowKind::Closed => ArrowKind::Open,
+            ArrowKind::Open => ArrowKind::Tick,
+            ArrowKind::Tick => ArrowKind::Dot,
+            ArrowKind::Dot => ArrowKind::Closed,
+        }
+    }
+}
+
+/// Положение текста относительно размерной линии
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextPlacement {
+    Above,
+    /// Центр текста на размерной линии
+    Centered,
+}
+
+impl TextPlacement {
+    pub fn label(self) -> &'static str {
+        match self {
+            TextPlacement::Above => "над линией",
+            TextPlacement::Centered => "на линии",
+        }
+    }
+
+    pub fn cycle(self) -> Self {
+        match self {
+            TextPlacement::Above => TextPlacement::Centered,
+            TextPlacement::Centered => TextPlacement::Above,
+        }
+    }
+}
+
+/// Предельные отклонения, приписываемые к значению размера.
+/// Задаются в единицах измеряемой величины (длины или радианы).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tolerance {
+    None,
+    /// Симметричное отклонение `±value`
+    Symmetric(f64),
+    /// Верхнее и нижнее отклонения со знаком, `+0.2 -0.1`
+    Deviation {
+        upper: f64,
+        lower: f64,
+    },
+}
+
+/// Именованный размерный стиль. Размеры задаются в единицах чертежа
+/// при масштабе 1 и умножаются на общий масштаб стиля.
+#[derive(Clone, Debug)]
+pub struct DimensionStyle {
+    pub name: String,
+    pub arrow: ArrowKind,
+    pub arrow_size: f64,
+    /// Высота прописных букв текста
+    pub text_height: f64,
+    pub text_placement: TextPlacement,
+    /// Зазор между измеряемой точкой и выносной линией
+    pub extension_offset: f64,
+    /// Выход выносной линии за размерную
+    pub extension_beyond: f64,
+    /// Знаков после запятой у линейных размеров
+    pub precision: usize,
+    pub prefix: String,
+    pub suffix: String,
+    /// Отклонения линейных, радиусных размеров и длин дуг
+    pub tolerance: Tolerance,
+    /// Отклонения угловых размеров
+    pub angular_tolerance: Tolerance,
+    pub scale: f64,
+}
+
+impl Default for DimensionStyle {
+    fn default() -> Self {
+        Self {
+            name: "Стандартный".to_string(),
+            arrow: ArrowKind::Closed,
+            arrow_size: 0.25,
+            text_height: 0.25,
+            text_placement: TextPlacement::Above,
+            extension_offset: 0.1,
+            extension_beyond: 0.2,
+            precision: 2,
+            prefix: String::new(),
+            suffix: String::new(),
+            tolerance: Tolerance::None,
+            angular_tolerance: Tolerance::None,
+            scale: 1.0,
+        }
+    }
+}
+
+impl DimensionStyle {
+    /// Чертежи деталей в миллиметрах по ЕСКД: стрелки 2.5, шрифт 3.5,
+    /// выносные линии от контура с выходом 2 мм
+    pub fn mm_parts() -> Self {
+        Self {
+            name: "ЕСКД, детали (мм)".to_string(),
+            arrow: ArrowKind::Closed,
+            arrow_size: 2.5,
+            text_height: 3.5,
+            extension_offset: 0.0,
+            extension_beyond: 2.0,
+            precision: 1,
+            ..default()
+        }
+    }
+
+    /// Генплан в метрах, М 1:500: засечки, шрифт 2.5 мм на листе
+    pub fn site_plan() -> Self {
+        Self {
+            name: "Генплан (м, 1:500)".to_string(),
+            arrow: ArrowKind::Tick,
+            arrow_size: 2.0,
+            text_height: 2.5,
+            extension_offset: 1.0,
+            extension_beyond: 2.0,
+            precision: 2,
+            scale: 0.5,
+            ..default()
+        }
+    }
+
+    pub fn arrow_size(&self) -> f64 {
+        self.arrow_size * self.scale
+    }
+
+    pub fn text_height(&self) -> f64 {
+        self.text_height * self.scale
+    }
+
+    pub fn extension_offset(&self) -> f64 {
+        self.extension_offset * self.scale
+    }
+
+    pub fn extension_beyond(&self) -> f64 {
+        self.extension_beyond * self.scale
+    }
+
+    /// Смещение центра текста от размерной линии
+    pub fn text_gap(&self) -> f64 {
+        match self.text_placement {
+            TextPlacement::Above => 0.6 * self.text_height(),
+            TextPlacement::Centered => 0.0,
+        }
+    }
+
+    /// Текст размера: префикс, знак (R, Ø), значение, суффикс и отклонения
+    pub fn text(&self, symbol: &str, value: f64, format: impl Fn(f64) -> String) -> String {
+        self.text_with_tolerance(symbol, value, self.tolerance, format)
+    }
+
+    /// Текст углового размера с угловыми отклонениями
+    pub fn angular_text(&self, value: f64, format: impl Fn(f64) -> String) -> String {
+        self.text_with_tolerance("", value, self.angular_tolerance, format)
+    }
+
+    fn text_with_tolerance(
+        &self,
+        symbol: &str,
+        value: f64,
+        tolerance: Tolerance,
+        format: impl Fn(f64) -> String,
+    ) -> String {
+        let signed = |value: f64| {
+            if value < 0.0 {
+                format(value)
+            } else {
+                format!("+{}", format(value))
+            }
+        };
+        let tolerance = match tolerance {
+            Tolerance::None => String::new(),
+            Tolerance::Symmetric(value) => format!(" ±{}", format(value.abs())),
+            Tolerance::Deviation { upper, lower } => {
+                format!(" {} {}", signed(upper), signed(lower))
+            }
+        };
+        format!(
+            "{}{}{}{}{}",
+            self.prefix,
+            symbol,
+            format(value),
+            self.suffix,
+            tolerance
+        )
+    }
+}
+
+/// Стиль, которым оформлен размер (индекс в `DimensionStyles`)
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct DimensionStyleId(pub usize);
+
+/// Список размерных стилей и стиль для новых размеров.
+/// Стили не удаляются, поэтому индексы в `DimensionStyleId` остаются верными.
+#[derive(Resource)]
+pub struct DimensionStyles {
+    pub styles: Vec<DimensionStyle>,
+    pub current: usize,
+}
+
+impl Default for DimensionStyles {
+    fn default() -> Self {
+        Self {
+            styles: vec![
+                DimensionStyle::default(),
+                DimensionStyle::mm_parts(),
+                DimensionStyle::site_plan(),
+            ],
+            current: 0,
+        }
+    }
+}
+
+impl DimensionStyles {
+    pub fn current(&self) -> &DimensionStyle {
+        &self.styles[self.current]
+    }
+
+    pub fn current_mut(&mut self) -> &mut DimensionStyle {
+        let index = self.current;
+        &mut self.styles[index]
+    }
+
+    /// Стиль размера; неизвестный индекс дает первый стиль
+    pub fn get(&self, id: DimensionStyleId) -> &DimensionStyle {
+        self.styles.get(id.0).unwrap_or(&self.styles[0])
+    }
+}
+
+/// Выбор текущего стиля и изменение его параметров.
+/// Размеры перестраиваются по стилю при каждой отрисовке, поэтому правка
+/// стиля сразу видна на всех размерах, которые на него ссылаются.
+pub fn handle_dimension_style_events(
+    mut style_events: EventReader<DimensionStyleEvent>,
+    mut styles: ResMut<DimensionStyles>,
+) {
+    for event in style_events.read() {
+        if let DimensionStyleEvent::Next = event {
+            styles.current = (styles.current + 1) % styles.styles.len();
+            continue;
+        }
+        let style = styles.current_mut();
+        match event {
+            DimensionStyleEvent::Next => {}
+            DimensionStyleEvent::CycleArrow => style.arrow = style.arrow.cycle(),
+            DimensionStyleEvent::CycleTextPlacement => {
+                style.text_placement = style.text_placement.cycle();
+            }
+            DimensionStyleEvent::IncreasePrecision => {
+                style.precision = (style.precision + 1).min(CoordinateSettings::MAX_PRECISION);
+            }
+            DimensionStyleEvent::DecreasePrecision => {
+                style.precision = style.precision.saturating_sub(1);
+            }
+            DimensionStyleEvent::MultiplyScale(factor) => style.scale *= factor,
+            DimensionStyleEvent::SetSizes(sizes) => {
+                [
+                    style.arrow_size,
+                    style.text_height,
+                    style.extension_offset,
+                    style.extension_beyond,
+                ] = *sizes;
+            }
+            DimensionStyleEvent::SetText { prefix, suffix } => {
+                style.prefix = prefix.clone();
+                style.suffix = suffix.clone();
+            }
+            DimensionStyleEvent::SetTolerance(tolerance) => style.tolerance = *tolerance,
+            DimensionStyleEvent::SetAngularTolerance(tolerance) => {
+                style.angular_tolerance = *tolerance;
+            }
+        }
+    }
+}
+
+/// Поля размеров, текста и допусков текущего размерного стиля
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    match input.kind {
+        InputFieldKind::DimensionSizes => submit_sizes(input),
+        InputFieldKind::DimensionText => submit_text(input),
+        InputFieldKind::DimensionAngularTolerance => submit_angular_tolerance(input),
+        _ => submit_tolerance(input),
+    }
+}
+
+/// «стрелка, текст, отступ, выход»
+fn submit_sizes(input: &mut FieldInput) -> Result<String, String> {
+    let parts = expression::split_top_level(input.text, ',');
+    let [arrow, height, offset, beyond] = parts.as_slice() else {
+        return Err("ожидается «стрелка, текст, отступ, выход»".to_string());
+    };
+    let mut sizes = [0.0; 4];
+    for (size, part) in sizes.iter_mut().zip([arrow, height, offset, beyond]) {
+        *size = input.evaluate(part)?;
+    }
+    if sizes[0] <= 0.0 || sizes[1] <= 0.0 || sizes[2] < 0.0 || sizes[3] < 0.0 {
+        return Err("размеры стиля не могут быть отрицательными".to_string());
+    }
+    input
+        .events
+        .dimension_style
+        .write(DimensionStyleEvent::SetSizes(sizes));
+    Ok("стиль изменен".to_string())
+}
+
+/// «<>» обозначает измеренное значение; пустое поле убирает префикс и суффикс
+fn submit_text(input: &mut FieldInput) -> Result<String, String> {
+    let (prefix, suffix) = match input.text.split_once("<>") {
+        Some((prefix, suffix)) => (prefix.to_string(), suffix.to_string()),
+        None if input.text.trim().is_empty() => (String::new(), String::new()),
+        None => return Err("ожидается «префикс<>суффикс»".to_string()),
+    };
+    let message = format!("{}<>{}", prefix, suffix);
+    input
+        .events
+        .dimension_style
+        .write(DimensionStyleEvent::SetText { prefix, suffix });
+    Ok(message)
+}
+
+/// Допуск «±t», «верхнее, нижнее» или пусто; значения разбирает `parse`
+fn parse_tolerance(
+    text: &str,
+    parse: impl Fn(&str) -> Result<f64, String>,
+) -> Result<Tolerance, String> {
+    let text = text.trim();
+    Ok(match expression::split_top_level(text, ',').as_slice() {
+        _ if text.is_empty() => Tolerance::None,
+        [value] => Tolerance::Symmetric(parse(value.trim().trim_start_matches('±'))?.abs()),
+        [upper, lower] => Tolerance::Deviation {
+            upper: parse(upper.trim())?,
+            lower: parse(lower.trim())?,
+        },
+        _ => return Err("ожидается «±t» или «верхнее, нижнее»".to_string()),
+    })
+}
+
+/// Описание допуска для сообщения под полем
+fn tolerance_message(tolerance: Tolerance, format: impl Fn(f64) -> String) -> String {
+    match tolerance {
+        Tolerance::None => "без допуска".to_string(),
+        Tolerance::Symmetric(value) => format!("±{}", format(value)),
+        Tolerance::Deviation { upper, lower } => format!("{}, {}", format(upper), format(lower)),
+    }
+}
+
+/// Допуск линейных размеров в единицах длины
+fn submit_tolerance(input: &mut FieldInput) -> Result<String, String> {
+    let tolerance = parse_tolerance(input.text, |text| input.evaluate(text))?;
+    input
+        .events
+        .dimension_style
+        .write(DimensionStyleEvent::SetTolerance(tolerance));
+    Ok(tolerance_message(tolerance, |value| input.length(value)))
+}
+
+/// Допуск угловых размеров в единицах углов, в том числе в ГМС
+fn submit_angular_tolerance(input: &mut FieldInput) -> Result<String, String> {
+    let tolerance = parse_tolerance(input.text, |text| {
+        parsing::parse_angle(text, &input.context)
+    })?;
+    input
+        .events
+        .dimension_style
+        .write(DimensionStyleEvent::SetAngularTolerance(tolerance));
+    Ok(tolerance_message(tolerance, |value| input.angle(value)))
+}
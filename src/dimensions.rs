@@ -0,0 +1,1382 @@
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+use std::f64::consts::{PI, TAU};
+
+use crate::{
+    coordinate_systems::{formatting, CoordinateSettings},
+    dimension_style::{ArrowKind, DimensionStyle, DimensionStyleId, DimensionStyles},
+    events::{DimensionEvent, TypedPointEvent},
+    font_resource::GlobalFont,
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers},
+    line_drawing::DrawableLine,
+    render_origin::RenderOrigin,
+    selection::{line_under_cursor, ActiveCommandsMut},
+    snapping::{SnapPoint, SnapSettings, SnapState, SnapType},
+    ucs::UcsManager,
+};
+
+/// Ориентация линейного размера
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LinearKind {
+    /// Вдоль оси X текущей ПСК
+    Horizontal,
+    /// Вдоль оси Y текущей ПСК
+    Vertical,
+    /// Параллельно отрезку между измеряемыми точками
+    Aligned,
+}
+
+impl LinearKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LinearKind::Horizontal => "Горизонтальный",
+            LinearKind::Vertical => "Вертикальный",
+            LinearKind::Aligned => "Параллельный",
+        }
+    }
+}
+
+/// Вид радиального размера
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RadialKind {
+    Radius,
+    Diameter,
+    /// Радиус с изломом: размерная линия начинается не в центре,
+    /// а в указанной точке — для больших радиусов, центр которых вне листа
+    JoggedRadius,
+}
+
+impl RadialKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            RadialKind::Radius => "Радиус",
+            RadialKind::Diameter => "Диаметр",
+            RadialKind::JoggedRadius => "Радиус с изломом",
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            RadialKind::Radius | RadialKind::JoggedRadius => "R",
+            RadialKind::Diameter => "Ø",
+        }
+    }
+}
+
+/// Команда простановки размера
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DimensionCommand {
+    Linear(LinearKind),
+    /// Угол между двумя отрезками
+    AngularLines,
+    /// Угол по вершине и двум точкам
+    AngularVertex,
+    /// Радиус или диаметр по центру и точке на окружности
+    Radial(RadialKind),
+    /// Длина дуги по центру и концам дуги
+    ArcLength,
+}
+
+impl DimensionCommand {
+    pub fn label(self) -> &'static str {
+        match self {
+            DimensionCommand::Linear(kind) => kind.label(),
+            DimensionCommand::AngularLines => "Угловой по отрезкам",
+            DimensionCommand::AngularVertex => "Угловой по 3 точкам",
+            DimensionCommand::Radial(kind) => kind.label(),
+            DimensionCommand::ArcLength => "Длина дуги",
+        }
+    }
+}
+
+/// Размер на чертеже. Геометрия хранится в координатах документа,
+/// значение вычисляется заново при каждой отрисовке.
+#[derive(Component, Clone, Debug)]
+pub enum Dimension {
+    Linear {
+        kind: LinearKind,
+        first: DVec3,
+        second: DVec3,
+        /// Точка, через которую проходит размерная линия
+        location: DVec3,
+        /// Направление измерения в плане (единичный вектор x, y чертежа).
+        /// У параллельного размера следует за измеряемыми точками.
+        direction: DVec2,
+    },
+    /// Угол между прямыми, проходящими через два отрезка. Из четырех углов
+    /// между прямыми образмеривается тот, в котором лежит `location`;
+    /// расстояние от вершины до `location` — радиус размерной дуги.
+    Angular {
+        first: [DVec3; 2],
+        second: [DVec3; 2],
+        location: DVec3,
+    },
+    /// Радиус или диаметр окружности с центром `center`, проходящей через `point`.
+    /// Размерная линия направлена к `location`: внутри окружности или снаружи.
+    Radial {
+        kind: RadialKind,
+        center: DVec3,
+        point: DVec3,
+        location: DVec3,
+    },
+    /// Длина дуги с центром `center` между `start` и `end`. Из двух дуг
+    /// образмеривается та, в сторону которой указан `location`.
+    ArcLength {
+        center: DVec3,
+        start: DVec3,
+        end: DVec3,
+        location: DVec3,
+    },
+}
+
+/// Подпись размера: текст интерфейса, который следует за точкой на чертеже
+#[derive(Component)]
+pub struct DimensionLabel {
+    pub dimension: Entity,
+}
+
+/// Связь точки размера с объектом чертежа
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    /// Точка отрезка с параметром `t`: 0 — начало, 1 — конец, 0.5 — середина
+    OnLine { line: Entity, t: f64 },
+    /// Пересечение прямых, проходящих через два отрезка
+    Intersection { first: Entity, second: Entity },
+}
+
+impl Anchor {
+    /// Связь для точки объектной привязки: отрезок (или два отрезка
+    /// для пересечения), на котором лежит точка
+    pub fn find<'a>(
+        snap: SnapPoint,
+        lines: impl Iterator<Item = (Entity, &'a DrawableLine)>,
+    ) -> Option<Anchor> {
+        let point = plan(snap.position);
+        let on_lines: Vec<(Entity, f64)> = lines
+            .filter_map(|(entity, line)| {
+                let (start, end) = (plan(line.start), plan(line.end));
+                let along = end - start;
+                if along.length_squared() < 1e-24 {
+                    return None;
+                }
+                let t = ((point - start).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+                ((start + along * t).distance(point) < 1e-6).then_some((entity, t))
+            })
+            .collect();
+        let on_line = |accept: fn(f64) -> Option<f64>| {
+            on_lines
+                .iter()
+                .find_map(|&(line, t)| accept(t).map(|t| Anchor::OnLine { line, t }))
+        };
+        match snap.snap_type {
+            SnapType::Intersection => match on_lines.as_slice() {
+                [(first, _), (second, _), ..] => Some(Anchor::Intersection {
+                    first: *first,
+                    second: *second,
+                }),
+                _ => None,
+            },
+            SnapType::Endpoint => on_line(|t| ((t - t.round()).abs() < 1e-6).then_some(t.round())),
+            SnapType::Midpoint => on_line(|t| ((t - 0.5).abs() < 1e-6).then_some(0.5)),
+            SnapType::Nearest | SnapType::Perpendicular => on_line(Some),
+        }
+    }
+
+    /// Положение точки по текущей геометрии отрезков. `None`, если отрезок
+    /// удален; `Some(None)`, если отрезки пересечения стали параллельны.
+    pub fn resolve(&self, line: impl Fn(Entity) -> Option<[DVec3; 2]>) -> Option<Option<DVec3>> {
+        match *self {
+            Anchor::OnLine { line: entity, t } => {
+                let [start, end] = line(entity)?;
+                Some(Some(start.lerp(end, t)))
+            }
+            Anchor::Intersection { first, second } => {
+                let (first, second) = (line(first)?, line(second)?);
+                Some(
+                    line_intersection(first.map(plan), second.map(plan))
+                        .map(|point| lift(point, first[0].y)),
+                )
+            }
+        }
+    }
+}
+
+/// Связи точек размера с объектами. У размера по введенным с клавиатуры
+/// точкам все записи пустые, и он не меняется вместе с чертежом.
+#[derive(Component, Clone, Debug, Default)]
+pub struct DimensionAssociation {
+    /// По записи на точку из `Dimension::anchored_points_mut`
+    pub anchors: Vec<Option<Anchor>>,
+    /// Объект хотя бы одной из связей удален
+    pub lost: bool,
+}
+
+/// Отрезки, стрелки и текст размера в координатах документа
+#[derive(Clone, Debug, Default)]
+pub struct DimensionLayout {
+    pub lines: Vec<(DVec3, DVec3)>,
+    /// Острие стрелки и направление, в котором она указывает (в плане)
+    pub arrows: Vec<(DVec3, DVec2)>,
+    pub text: String,
+    pub text_position: DVec3,
+}
+
+/// Точка документа -> точка плана (x, y чертежа)
+pub fn plan(point: DVec3) -> DVec2 {
+    DVec2::new(point.x, point.z)
+}
+
+/// Точка плана -> точка документа на заданной высоте
+pub fn lift(point: DVec2, height: f64) -> DVec3 {
+    DVec3::new(point.x, height, point.y)
+}
+
+/// Точка пересечения прямых, проходящих через отрезки (в плане)
+fn line_intersection(first: [DVec2; 2], second: [DVec2; 2]) -> Option<DVec2> {
+    let (d1, d2) = (first[1] - first[0], second[1] - second[0]);
+    let denominator = d1.perp_dot(d2);
+    if denominator.abs() <= 1e-12 * d1.length() * d2.length() {
+        return None;
+    }
+    let t = (second[0] - first[0]).perp_dot(d2) / denominator;
+    Some(first[0] + d1 * t)
+}
+
+/// Угол из четырех между прямыми с направлениями `first` и `second`,
+/// в котором лежит направление `target`: (начальный угол, величина против часовой стрелки)
+fn angular_sector(first: DVec2, second: DVec2, target: DVec2) -> (f64, f64) {
+    let angle = |direction: DVec2| direction.y.atan2(direction.x).rem_euclid(TAU);
+    let (a, b) = (angle(first), angle(second));
+    let mut bounds = [a, b, (a + PI).rem_euclid(TAU), (b + PI).rem_euclid(TAU)];
+    bounds.sort_by(f64::total_cmp);
+    let target = angle(target);
+    for i in 0..4 {
+        let start = bounds[i];
+        let end = if i == 3 {
+            bounds[0] + TAU
+        } else {
+            bounds[i + 1]
+        };
+        let target = if target < start { target + TAU } else { target };
+        if target <= end {
+            return (start, end - start);
+        }
+    }
+    (bounds[0], bounds[1] - bounds[0])
+}
+
+/// Размерная дуга с центром `vertex` от угла `start` на `sweep` (со знаком)
+/// со стрелками на концах. Для каждой стороны угла дорисовывается выносная
+/// линия, если дуга не доходит до отрезка этой стороны или выходит за него.
+pub fn angular_arc(
+    vertex: DVec3,
+    start: f64,
+    sweep: f64,
+    radius: f64,
+    segments: &[[DVec2; 2]],
+    style: &DimensionStyle,
+) -> DimensionLayout {
+    let (height, center) = (vertex.y, plan(vertex));
+    let point = |angle: f64| lift(center + DVec2::from_angle(angle) * radius, height);
+    let mut layout = DimensionLayout::default();
+
+    let steps = ((sweep.abs() / (PI / 32.0)).ceil() as usize).max(1);
+    for i in 0..steps {
+        let (from, to) = (i as f64 / steps as f64, (i + 1) as f64 / steps as f64);
+        layout
+            .lines
+            .push((point(start + sweep * from), point(start + sweep * to)));
+    }
+
+    // Стрелки касаются дуги и указывают от ее середины к концам;
+    // на короткой дуге они ставятся снаружи
+    let inside = (sweep * radius).abs() >= 3.0 * style.arrow_size();
+    let turn = sweep.signum() * if inside { 1.0 } else { -1.0 };
+    for (angle, sign) in [(start, -turn), (start + sweep, turn)] {
+        layout
+            .arrows
+            .push((point(angle), DVec2::from_angle(angle).perp() * sign));
+    }
+
+    for angle in [start, start + sweep] {
+        let direction = DVec2::from_angle(angle);
+        let Some(segment) = segments.iter().find(|segment| {
+            (segment[1] - segment[0]).perp_dot(direction).abs()
+                < 1e-9 * (segment[1] - segment[0]).length().max(1e-300)
+        }) else {
+            continue;
+        };
+        let along = segment.map(|end| (end - center).dot(direction));
+        let (near, far) = (along[0].min(along[1]), along[0].max(along[1]));
+        let (offset, beyond) = (style.extension_offset(), style.extension_beyond());
+        let extension = if radius > far.max(0.0) + offset {
+            Some((far.max(0.0) + offset, radius + beyond))
+        } else if radius < near - offset {
+            Some((near - offset, (radius - beyond).max(0.0)))
+        } else {
+            None
+        };
+        if let Some((from, to)) = extension {
+            layout.lines.push((
+                lift(center + direction * from, height),
+                lift(center + direction * to, height),
+            ));
+        }
+    }
+
+    layout.text_position = point(start + sweep / 2.0)
+        + lift(
+            DVec2::from_angle(start + sweep / 2.0) * style.text_gap(),
+            0.0,
+        );
+    layout
+}
+
+/// Размерная линия радиуса или диаметра. Внутри окружности она идет
+/// через центр, снаружи — от окружности к `location` со стрелкой к центру.
+/// У радиуса с изломом линия начинается в `location` и доходит до окружности
+/// зигзагом по направлению на `point`.
+fn radial_leader(
+    kind: RadialKind,
+    center: DVec3,
+    point: DVec3,
+    location: DVec3,
+    style: &DimensionStyle,
+) -> DimensionLayout {
+    let height = location.y;
+    let (center, location) = (plan(center), plan(location));
+    let radius = (plan(point) - center).length();
+    let mut layout = DimensionLayout::default();
+    if radius <= 1e-12 {
+        return layout;
+    }
+
+    if kind == RadialKind::JoggedRadius {
+        let direction = (plan(point) - center) / radius;
+        let tip = center + direction * radius;
+        // Излом под 45° посередине между началом линии и окружностью
+        let along = (tip - location).dot(direction);
+        let bend = location + direction * along / 2.0;
+        let offset = (bend - center).dot(direction.perp());
+        let joint = bend - direction.perp() * offset + direction * offset.abs();
+        for (from, to) in [(location, bend), (bend, joint), (joint, tip)] {
+            layout.lines.push((lift(from, height), lift(to, height)));
+        }
+        layout.arrows.push((lift(tip, height), direction));
+        layout.text_position = lift(
+            (joint + tip) / 2.0 + direction.perp() * style.text_gap(),
+            height,
+        );
+        return layout;
+    }
+
+    let direction = (location - center).try_normalize().unwrap_or(DVec2::X);
+    let tip = center + direction * radius;
+    let inside = (location - center).length() <= radius;
+    let far = match kind {
+        RadialKind::Diameter => center - direction * radius,
+        _ => center,
+    };
+    if inside {
+        layout.lines.push((lift(far, height), lift(tip, height)));
+        layout.arrows.push((lift(tip, height), direction));
+        layout.text_position = lift(location + direction.perp() * style.text_gap(), height);
+    } else {
+        layout
+            .lines
+            .push((lift(tip, height), lift(location, height)));
+        layout.arrows.push((lift(tip, height), -direction));
+        layout.text_position = lift(location + direction * style.text_gap(), height);
+    }
+    if kind == RadialKind::Diameter {
+        layout.lines.push((lift(far, height), lift(tip, height)));
+        layout.arrows.push((lift(far, height), -direction));
+    }
+    layout
+}
+
+impl Dimension {
+    /// Направление измерения линейного размера
+    fn direction(&self) -> DVec2 {
+        match self {
+            Dimension::Linear {
+                kind: LinearKind::Aligned,
+                first,
+                second,
+                direction,
+                ..
+            } => (plan(*second) - plan(*first))
+                .try_normalize()
+                .unwrap_or(*direction),
+            Dimension::Linear { direction, .. } => *direction,
+            _ => DVec2::X,
+        }
+    }
+
+    /// Вершина, начальный угол, величина и радиус углового размера;
+    /// `None` для параллельных отрезков
+    fn angular_geometry(&self) -> Option<(DVec2, f64, f64, f64)> {
+        let Dimension::Angular {
+            first,
+            second,
+            location,
+        } = self
+        else {
+            return None;
+        };
+        let (first, second) = (first.map(plan), second.map(plan));
+        let vertex = line_intersection(first, second)?;
+        let location = plan(*location) - vertex;
+        let (start, sweep) = angular_sector(first[1] - first[0], second[1] - second[0], location);
+        Some((vertex, start, sweep, location.length()))
+    }
+
+    /// Начальный угол, величина и радиус дуги размера длины дуги;
+    /// `None` при нулевом радиусе
+    fn arc_geometry(&self) -> Option<(f64, f64, f64)> {
+        let Dimension::ArcLength {
+            center,
+            start,
+            end,
+            location,
+        } = self
+        else {
+            return None;
+        };
+        let center = plan(*center);
+        let angle = |point: DVec3| {
+            let direction = plan(point) - center;
+            direction.y.atan2(direction.x)
+        };
+        let radius = (plan(*start) - center).length();
+        if radius <= 1e-12 {
+            return None;
+        }
+        let from = angle(*start);
+        let sweep = (angle(*end) - from).rem_euclid(TAU);
+        // Дуга против часовой стрелки от начала к концу или дополняющая ее
+        let target = (angle(*location) - from).rem_euclid(TAU);
+        let sweep = if target <= sweep { sweep } else { sweep - TAU };
+        Some((from, sweep, radius))
+    }
+
+    /// Измеряемые точки, которые могут быть связаны с объектами
+    fn anchored_points_mut(&mut self) -> Vec<&mut DVec3> {
+        match self {
+            Dimension::Linear { first, second, .. } => vec![first, second],
+            Dimension::Angular { first, second, .. } => {
+                first.iter_mut().chain(second.iter_mut()).collect()
+            }
+            Dimension::Radial { center, point, .. } => vec![center, point],
+            Dimension::ArcLength {
+                center, start, end, ..
+            } => vec![center, start, end],
+        }
+    }
+
+    /// Измеренная величина в единицах чертежа
+    pub fn measurement(&self) -> f64 {
+        match self {
+            Dimension::Linear { first, second, .. } => {
+                (plan(*second) - plan(*first)).dot(self.direction()).abs()
+            }
+            Dimension::Angular { .. } => self
+                .angular_geometry()
+                .map_or(0.0, |(_, _, sweep, _)| sweep),
+            Dimension::Radial {
+                kind,
+                center,
+                point,
+                ..
+            } => {
+                let radius = (plan(*point) - plan(*center)).length();
+                match kind {
+                    RadialKind::Diameter => 2.0 * radius,
+                    RadialKind::Radius | RadialKind::JoggedRadius => radius,
+                }
+            }
+            Dimension::ArcLength { .. } => self
+                .arc_geometry()
+                .map_or(0.0, |(_, sweep, radius)| (sweep * radius).abs()),
+        }
+    }
+
+    /// Построение размера по стилю: выносные линии, размерная линия
+    /// со стрелками и текст
+    pub fn layout(&self, settings: &CoordinateSettings, style: &DimensionStyle) -> DimensionLayout {
+        let settings = CoordinateSettings {
+            precision: style.precision,
+            ..settings.clone()
+        };
+        let length = |value| formatting::format_length(value, &settings);
+        match self {
+            Dimension::Linear {
+                first,
+                second,
+                location,
+                ..
+            } => {
+                let height = location.y;
+                let normal = self.direction().perp();
+                let location = plan(*location);
+                let mut layout = DimensionLayout {
+                    text: style.text("", self.measurement(), length),
+                    ..default()
+                };
+
+                // Основания размерной линии на перпендикулярах из измеряемых точек
+                let feet = [*first, *second].map(|point| {
+                    let point = plan(point);
+                    (point, point + normal * (location - point).dot(normal))
+                });
+                for (point, foot) in feet {
+                    let side = (foot - point).dot(normal);
+                    if side.abs() > style.extension_offset() {
+                        let outward = normal * side.signum();
+                        layout.lines.push((
+                            lift(point + outward * style.extension_offset(), height),
+                            lift(foot + outward * style.extension_beyond(), height),
+                        ));
+                    }
+                }
+
+                let (start, end) = (feet[0].1, feet[1].1);
+                layout.lines.push((lift(start, height), lift(end, height)));
+                let span = end - start;
+                if span.length() > 1e-9 {
+                    let along = span.normalize();
+                    // Если стрелки не помещаются внутри, они ставятся снаружи
+                    let inside = span.length() >= 3.0 * style.arrow_size();
+                    let outward = if inside { along } else { -along };
+                    layout.arrows.push((lift(start, height), -outward));
+                    layout.arrows.push((lift(end, height), outward));
+                    if !inside {
+                        layout.lines.push((
+                            lift(start - along * 2.0 * style.arrow_size(), height),
+                            lift(end + along * 2.0 * style.arrow_size(), height),
+                        ));
+                    }
+                }
+
+                // Текст над размерной линией, со стороны от измеряемых точек
+                let middle = (start + end) / 2.0;
+                let side = (location - plan(*first)).dot(normal);
+                let away = if side < 0.0 { -normal } else { normal };
+                layout.text_position = lift(middle + away * style.text_gap(), height);
+                layout
+            }
+            Dimension::Angular {
+                first,
+                second,
+                location,
+            } => {
+                let Some((vertex, start, sweep, radius)) = self.angular_geometry() else {
+                    return DimensionLayout::default();
+                };
+                let mut layout = angular_arc(
+                    lift(vertex, location.y),
+                    start,
+                    sweep,
+                    radius,
+                    &[first.map(plan), second.map(plan)],
+                    style,
+                );
+                layout.text = style.angular_text(sweep, |value| {
+                    formatting::format_angle(value, settings.angle_unit, settings.precision)
+                });
+                layout
+            }
+            Dimension::Radial {
+                kind,
+                center,
+                point,
+                location,
+            } => {
+                let mut layout = radial_leader(*kind, *center, *point, *location, style);
+                layout.text = style.text(kind.prefix(), self.measurement(), length);
+                layout
+            }
+            Dimension::ArcLength {
+                center,
+                start,
+                end,
+                location,
+            } => {
+                let Some((from, sweep, radius)) = self.arc_geometry() else {
+                    return DimensionLayout::default();
+                };
+                let center = plan(*center);
+                // Выносные линии продолжают радиусы к концам дуги
+                let radii = [*start, *end].map(|point| [center, plan(point)]);
+                let mut layout = angular_arc(
+                    lift(center, location.y),
+                    from,
+                    sweep,
+                    (plan(*location) - center).length().max(radius * 1e-3),
+                    &radii,
+                    style,
+                );
+                layout.text = style.text("◠", self.measurement(), length);
+                layout
+            }
+        }
+    }
+}
+
+/// Указание точек нового размера
+#[derive(Clone, Debug)]
+pub struct DimensionPick {
+    pub command: DimensionCommand,
+    pub points: Vec<DVec3>,
+    /// Объекты, к которым привязаны указанные точки (по одному на точку)
+    pub anchors: Vec<Option<Anchor>>,
+    /// Выбранные отрезки углового размера
+    pub lines: Vec<(Entity, [DVec3; 2])>,
+    /// Клик по кнопке, начавший указание, не должен стать точкой
+    armed: bool,
+}
+
+impl DimensionPick {
+    pub fn prompt(&self) -> String {
+        let what = match (self.command, self.lines.len(), self.points.len()) {
+            (DimensionCommand::Linear(_), _, 0) => "первую точку",
+            (DimensionCommand::Linear(_), _, 1) => "вторую точку",
+            (DimensionCommand::Linear(_), _, _) => "положение размерной линии",
+            (DimensionCommand::AngularLines, 0, _) => "первый отрезок",
+            (DimensionCommand::AngularLines, 1, _) => "второй отрезок",
+            (DimensionCommand::AngularVertex, _, 0) => "вершину угла",
+            (DimensionCommand::AngularVertex, _, 1) => "точку на первой стороне",
+            (DimensionCommand::AngularVertex, _, 2) => "точку на второй стороне",
+            (DimensionCommand::Radial(_) | DimensionCommand::ArcLength, _, 0) => "центр",
+            (DimensionCommand::Radial(_), _, 1) => "точку на окружности",
+            (DimensionCommand::ArcLength, _, 1) => "начало дуги",
+            (DimensionCommand::ArcLength, _, 2) => "конец дуги",
+            (DimensionCommand::Radial(_) | DimensionCommand::ArcLength, _, _) => {
+                "положение размера"
+            }
+            _ => "положение дуги (выбирает один из четырех углов)",
+        };
+        format!("Размер «{}»: укажите {}", self.command.label(), what)
+    }
+
+    /// Собраны ли все данные, кроме положения размерной линии или дуги
+    fn ready(&self) -> bool {
+        match self.command {
+            DimensionCommand::Linear(_) => self.points.len() >= 2,
+            DimensionCommand::AngularLines => self.lines.len() >= 2,
+            DimensionCommand::AngularVertex | DimensionCommand::ArcLength => self.points.len() >= 3,
+            DimensionCommand::Radial(_) => self.points.len() >= 2,
+        }
+    }
+
+    /// Размер с размерной линией или дугой в точке `location`
+    fn dimension(&self, location: DVec3, ucs_manager: &UcsManager) -> Option<Dimension> {
+        let dimension = match self.command {
+            DimensionCommand::Linear(kind) => {
+                let (first, second) = (*self.points.first()?, *self.points.get(1)?);
+                let frame = ucs_manager.frame(None);
+                let direction = match kind {
+                    LinearKind::Horizontal => plan(frame.vector_to_world(DVec2::X)),
+                    LinearKind::Vertical => plan(frame.vector_to_world(DVec2::Y)),
+                    LinearKind::Aligned => plan(second) - plan(first),
+                }
+                .try_normalize()?;
+                Dimension::Linear {
+                    kind,
+                    first,
+                    second,
+                    location,
+                    direction,
+                }
+            }
+            DimensionCommand::AngularLines => Dimension::Angular {
+                first: self.lines.first()?.1,
+                second: self.lines.get(1)?.1,
+                location,
+            },
+            DimensionCommand::AngularVertex => {
+                let [vertex, first, second] = [0, 1, 2].map(|i| self.points.get(i).copied());
+                let vertex = vertex?;
+                Dimension::Angular {
+                    first: [vertex, first?],
+                    second: [vertex, second?],
+                    location,
+                }
+            }
+            DimensionCommand::Radial(kind) => Dimension::Radial {
+                kind,
+                center: *self.points.first()?,
+                point: *self.points.get(1)?,
+                location,
+            },
+            DimensionCommand::ArcLength => Dimension::ArcLength {
+                center: *self.points.first()?,
+                start: *self.points.get(1)?,
+                end: *self.points.get(2)?,
+                location,
+            },
+        };
+        // Параллельные стороны, совпадающие точки и нулевой радиус не дают размера
+        let degenerate = match dimension {
+            Dimension::Linear { .. } => false,
+            Dimension::Angular { .. } => dimension.angular_geometry().is_none(),
+            Dimension::Radial { .. } | Dimension::ArcLength { .. } => {
+                dimension.measurement() <= 1e-12
+            }
+        };
+        if degenerate {
+            return None;
+        }
+        Some(dimension)
+    }
+}
+
+impl DimensionPick {
+    /// Связи точек размера с объектами в порядке `Dimension::anchored_points_mut`
+    fn association(&self) -> DimensionAssociation {
+        let anchor = |i: usize| self.anchors.get(i).copied().flatten();
+        let anchors = match self.command {
+            DimensionCommand::AngularLines => self
+                .lines
+                .iter()
+                .flat_map(|&(line, _)| [0.0, 1.0].map(|t| Some(Anchor::OnLine { line, t })))
+                .collect(),
+            // Вершина — общая точка обеих сторон угла
+            DimensionCommand::AngularVertex => vec![anchor(0), anchor(1), anchor(0), anchor(2)],
+            _ => (0..self.points.len()).map(anchor).collect(),
+        };
+        DimensionAssociation {
+            anchors,
+            lost: false,
+        }
+    }
+}
+
+/// Состояние команды простановки размеров
+#[derive(Resource)]
+pub struct DimensionTool {
+    pub placing: Option<DimensionPick>,
+    /// Показывать размеры, потерявшие объект, без значения и красным
+    pub flag_lost: bool,
+}
+
+impl Default for DimensionTool {
+    fn default() -> Self {
+        Self {
+            placing: None,
+            flag_lost: true,
+        }
+    }
+}
+
+impl DimensionTool {
+    pub fn is_picking(&self) -> bool {
+        self.placing.is_some()
+    }
+}
+
+/// Система обработки событий размеров
+pub fn handle_dimension_events(
+    mut dimension_events: EventReader<DimensionEvent>,
+    mut active: ActiveCommandsMut,
+) {
+    for event in dimension_events.read() {
+        match *event {
+            DimensionEvent::Start(command) => {
+                // Новая команда прерывает рисование и другие указания точек
+                active.cancel_all();
+                active.dimension_tool.placing = Some(DimensionPick {
+                    command,
+                    points: Vec::new(),
+                    anchors: Vec::new(),
+                    lines: Vec::new(),
+                    armed: false,
+                });
+            }
+            DimensionEvent::Cancel => active.dimension_tool.placing = None,
+            DimensionEvent::ToggleFlagLost => {
+                active.dimension_tool.flag_lost = !active.dimension_tool.flag_lost
+            }
+        }
+    }
+}
+
+/// Указание точек и отрезков размера (клики и ввод с клавиатуры)
+pub fn dimension_pick_system(
+    mut commands: Commands,
+    input: ActionInput,
+    mut snap_state: ResMut<SnapState>,
+    snap_settings: Res<SnapSettings>,
+    ucs_manager: Res<UcsManager>,
+    global_font: Res<GlobalFont>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    line_query: Query<(Entity, &DrawableLine, &LayerId)>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
+    mut tool: ResMut<DimensionTool>,
+) {
+    let Some(mut pick) = tool.placing.clone() else {
+        return;
+    };
+    // Отрезки скрытых слоев не указываются
+    let visible_lines = || {
+        line_query
+            .iter()
+            .filter(|(_, _, layer)| layers.is_visible(**layer))
+            .map(|(entity, line, _)| (entity, line))
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        tool.placing = None;
+        info!("Dimension placement cancelled");
+        return;
+    }
+    if !pick.armed {
+        typed_point_events.clear();
+        pick.armed = true;
+        tool.placing = Some(pick);
+        return;
+    }
+
+    // Введенные с клавиатуры точки ни к чему не привязаны,
+    // точка клика привязана к объекту, если на нем сработала объектная привязка
+    let mut points: Vec<(DVec3, Option<Anchor>)> = typed_point_events
+        .read()
+        .map(|event| (event.point, None))
+        .collect();
+    if input.just_pressed(InputAction::PickPoint) {
+        if let Some(point) = snap_state.point() {
+            let anchor = snap_state
+                .snap
+                .and_then(|snap| Anchor::find(snap, visible_lines()));
+            points.push((point, anchor));
+        }
+    }
+    snap_state.remember(points.iter().map(|(point, _)| *point));
+    if points.is_empty() {
+        return;
+    }
+
+    for (point, anchor) in points {
+        if !pick.ready() {
+            if pick.command == DimensionCommand::AngularLines {
+                let line = line_under_cursor(point, visible_lines(), snap_settings.aperture as f64)
+                    .and_then(|entity| line_query.get(entity).ok())
+                    .map(|(entity, line, _)| (entity, [line.start, line.end]));
+                match line {
+                    Some(line) => pick.lines.push(line),
+                    None => warn!("No line under the picked point"),
+                }
+            } else {
+                pick.points.push(point);
+                pick.anchors.push(anchor);
+            }
+            continue;
+        }
+        match pick.dimension(point, &ucs_manager) {
+            Some(dimension) => {
+                info!("Dimension created: {:?}", dimension);
+                let style = DimensionStyleId(styles.current);
+                let association = pick.association();
+                spawn_dimension(&mut commands, &global_font, dimension, style, association);
+                tool.placing = None;
+            }
+            None => {
+                warn!("Dimension points coincide or sides are parallel, pick them again");
+                pick.points.clear();
+                pick.anchors.clear();
+                pick.lines.clear();
+                tool.placing = Some(pick);
+            }
+        }
+        return;
+    }
+    tool.placing = Some(pick);
+}
+
+/// Создание размера и его подписи
+pub fn spawn_dimension(
+    commands: &mut Commands,
+    global_font: &GlobalFont,
+    dimension: Dimension,
+    style: DimensionStyleId,
+    association: DimensionAssociation,
+) -> Entity {
+    let entity = commands
+        .spawn((dimension, style, association, Name::new("Dimension")))
+        .id();
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: global_font.handle.clone(),
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.5)),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Visibility::Hidden,
+        DimensionLabel { dimension: entity },
+        Name::new("DimensionLabel"),
+    ));
+    entity
+}
+
+/// Ассоциативные размеры следуют за отрезками, к которым привязаны их точки.
+/// Связь с удаленным отрезком разрывается, а размер помечается.
+pub fn update_dimension_associations_system(
+    line_query: Query<&DrawableLine>,
+    mut dimension_query: Query<(&mut Dimension, &mut DimensionAssociation)>,
+) {
+    let line = |entity| {
+        line_query
+            .get(entity)
+            .ok()
+            .map(|line| [line.start, line.end])
+    };
+    for (mut dimension, mut association) in dimension_query.iter_mut() {
+        let mut moved = Vec::new();
+        let mut broken = Vec::new();
+        for (index, anchor) in association.anchors.iter().enumerate() {
+            match anchor.map(|anchor| anchor.resolve(line)) {
+                Some(Some(Some(point))) => moved.push((index, point)),
+                Some(None) => broken.push(index),
+                _ => {}
+            }
+        }
+        if !broken.is_empty() {
+            for index in broken {
+                association.anchors[index] = None;
+            }
+            association.lost = true;
+        }
+
+        // Изменение записывается, только если точки действительно сдвинулись
+        let points = dimension.bypass_change_detection().anchored_points_mut();
+        if moved.iter().any(|&(index, point)| *points[index] != point) {
+            let mut points = dimension.anchored_points_mut();
+            for (index, point) in moved {
+                *points[index] = point;
+            }
+        }
+    }
+}
+
+/// Отрисовка выносных и размерных линий со стрелками
+pub fn draw_dimensions_system(
+    dimension_query: Query<(
+        &Dimension,
+        &DimensionStyleId,
+        &DimensionAssociation,
+        Option<&LayerId>,
+    )>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    tool: Res<DimensionTool>,
+    ucs_manager: Res<UcsManager>,
+    snap_state: Res<SnapState>,
+    coordinate_settings: Res<CoordinateSettings>,
+    render_origin: Res<RenderOrigin>,
+    mut gizmos: Gizmos,
+) {
+    for (dimension, style, association, layer) in dimension_query.iter() {
+        if layer.is_some_and(|layer| !layers.is_visible(*layer)) {
+            continue;
+        }
+        let style = styles.get(*style);
+        let layout = dimension.layout(&coordinate_settings, style);
+        let color = dimension_color(association, &tool);
+        draw_layout(&mut gizmos, &render_origin, &layout, style, color);
+    }
+
+    // Предпросмотр: размерная линия следует за курсором
+    let Some(pick) = &tool.placing else {
+        return;
+    };
+    let Some(cursor) = snap_state.point() else {
+        return;
+    };
+    let preview_color = Color::srgba(0.9, 0.9, 0.5, 0.5);
+    if pick.ready() {
+        if let Some(dimension) = pick.dimension(cursor, &ucs_manager) {
+            let style = styles.current();
+            let layout = dimension.layout(&coordinate_settings, style);
+            draw_layout(&mut gizmos, &render_origin, &layout, style, preview_color);
+        }
+        return;
+    }
+    for (_, line) in &pick.lines {
+        gizmos.line(
+            render_origin.to_render(line[0]),
+            render_origin.to_render(line[1]),
+            preview_color,
+        );
+    }
+    if let Some(last) = pick.points.last() {
+        gizmos.line(
+            render_origin.to_render(*last),
+            render_origin.to_render(cursor),
+            preview_color,
+        );
+    }
+}
+
+/// Цвет размера; размер без объекта выделяется, если это включено
+fn dimension_color(association: &DimensionAssociation, tool: &DimensionTool) -> Color {
+    if association.lost && tool.flag_lost {
+        Color::srgb(0.9, 0.3, 0.3)
+    } else {
+        Color::srgb(0.9, 0.9, 0.5)
+    }
+}
+
+/// Отрисовка линий и стрелок размера
+pub fn draw_layout(
+    gizmos: &mut Gizmos,
+    render_origin: &RenderOrigin,
+    layout: &DimensionLayout,
+    style: &DimensionStyle,
+    color: Color,
+) {
+    let size = style.arrow_size();
+    for (start, end) in &layout.lines {
+        gizmos.line(
+            render_origin.to_render(*start),
+            render_origin.to_render(*end),
+            color,
+        );
+    }
+    for (tip, direction) in &layout.arrows {
+        let (height, tip) = (tip.y, plan(*tip));
+        let point = |point: DVec2| render_origin.to_render(lift(point, height));
+        match style.arrow {
+            ArrowKind::Closed | ArrowKind::Open => {
+                // Острие и два угла основания; у открытой стрелки основания нет
+                let back = tip - *direction * size;
+                let side = direction.perp() * size / 3.0;
+                let corners = [tip, back + side, back - side].map(point);
+                if style.arrow == ArrowKind::Closed {
+                    gizmos.linestrip([corners[0], corners[1], corners[2], corners[0]], color);
+                } else {
+                    gizmos.linestrip([corners[1], corners[0], corners[2]], color);
+                }
+            }
+            ArrowKind::Tick => {
+                // Засечка под 45° к размерной линии
+                let stroke = (*direction + direction.perp()) * std::f64::consts::FRAC_1_SQRT_2;
+                gizmos.line(
+                    point(tip - stroke * size / 2.0),
+                    point(tip + stroke * size / 2.0),
+                    color,
+                );
+            }
+            ArrowKind::Dot => {
+                let radius = size / 4.0;
+                let outline = (0..=12)
+                    .map(|i| point(tip + DVec2::from_angle(i as f64 * TAU / 12.0) * radius));
+                gizmos.linestrip(outline, color);
+            }
+        }
+    }
+}
+
+/// Текст размеров: значение и положение на экране над размерной линией
+pub fn update_dimension_labels_system(
+    mut commands: Commands,
+    dimension_query: Query<(
+        &Dimension,
+        &DimensionStyleId,
+        &DimensionAssociation,
+        Option<&LayerId>,
+    )>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    tool: Res<DimensionTool>,
+    coordinate_settings: Res<CoordinateSettings>,
+    render_origin: Res<RenderOrigin>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut label_query: Query<(
+        Entity,
+        &DimensionLabel,
+        &mut Text,
+        &mut TextFont,
+        &mut TextColor,
+        &mut Node,
+        &mut Visibility,
+        &ComputedNode,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+    for (entity, label, mut text, mut font, mut color, mut node, mut visibility, computed) in
+        label_query.iter_mut()
+    {
+        let Ok((dimension, style, association, layer)) = dimension_query.get(label.dimension)
+        else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if layer.is_some_and(|layer| !layers.is_visible(*layer)) {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let style = styles.get(*style);
+        let mut layout = dimension.layout(&coordinate_settings, style);
+        // Вместо устаревшего значения — отметка об удаленном объекте
+        if association.lost && tool.flag_lost {
+            layout.text = "нет объекта".to_string();
+        }
+        if **text != layout.text {
+            **text = layout.text;
+        }
+        color.set_if_neq(TextColor(dimension_color(association, &tool)));
+
+        place_label(
+            (camera, camera_transform),
+            render_origin.to_render(layout.text_position),
+            style.text_height(),
+            &mut font,
+            &mut node,
+            &mut visibility,
+            computed,
+        );
+    }
+}
+
+/// Размещение подписи на экране: центр текста — в точке `position`,
+/// высота прописных букв — `height` единиц чертежа
+pub fn place_label(
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+    position: Vec3,
+    height: f64,
+    font: &mut Mut<TextFont>,
+    node: &mut Mut<Node>,
+    visibility: &mut Mut<Visibility>,
+    computed: &ComputedNode,
+) {
+    let top = position + camera_transform.up() * height as f32;
+    match (
+        camera.world_to_viewport(camera_transform, position),
+        camera.world_to_viewport(camera_transform, top),
+    ) {
+        (Ok(viewport), Ok(top)) => {
+            // Высота прописных букв — около 0.7 кегля; кегль округляется,
+            // чтобы не растеризовать шрифт на каждом кадре
+            let font_size = (viewport.distance(top) / 0.7).round().clamp(1.0, 200.0);
+            if font.font_size != font_size {
+                font.font_size = font_size;
+            }
+            let size = computed.size() * computed.inverse_scale_factor();
+            node.left = Val::Px(viewport.x - size.x / 2.0);
+            node.top = Val::Px(viewport.y - size.y / 2.0);
+            visibility.set_if_neq(Visibility::Visible);
+        }
+        _ => {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dimension_style::Tolerance, render_origin::FAR_POINT};
+
+    #[test]
+    fn linear_dimensions_measure_along_direction() {
+        let settings = CoordinateSettings::default();
+        let style = DimensionStyle::default();
+        let first = FAR_POINT;
+        let second = first + DVec3::new(3.0, 0.0, 4.0);
+        let location = first + DVec3::new(0.0, 0.0, 6.0);
+        let dimension = |kind, direction| Dimension::Linear {
+            kind,
+            first,
+            second,
+            location,
+            direction,
+        };
+
+        let horizontal = dimension(LinearKind::Horizontal, DVec2::X);
+        assert!((horizontal.measurement() - 3.0).abs() < 1e-9);
+        let layout = horizontal.layout(&settings, &style);
+        assert_eq!(layout.text, "3.00");
+        // Две выносные линии и размерная линия на высоте указанного положения
+        assert_eq!(layout.lines.len(), 3);
+        assert_eq!(layout.arrows.len(), 2);
+        let (start, end) = layout.lines[2];
+        assert!(start.abs_diff_eq(location, 1e-9));
+        assert!(end.abs_diff_eq(location + DVec3::X * 3.0, 1e-9));
+
+        let vertical = dimension(LinearKind::Vertical, DVec2::Y);
+        assert!((vertical.measurement() - 4.0).abs() < 1e-9);
+        let aligned = dimension(LinearKind::Aligned, DVec2::new(3.0, 4.0).normalize());
+        assert_eq!(aligned.layout(&settings, &style).text, "5.00");
+    }
+
+    #[test]
+    fn angular_dimension_sector_follows_location() {
+        let settings = CoordinateSettings::default();
+        let style = DimensionStyle::default();
+        let vertex = FAR_POINT;
+        let ray = |degrees: f64, length: f64| {
+            vertex + lift(DVec2::from_angle(degrees.to_radians()) * length, 0.0)
+        };
+        let first = [ray(180.0, 2.0), ray(0.0, 2.0)];
+        let second = [ray(240.0, 1.0), ray(60.0, 3.0)];
+
+        for (direction, expected) in [(30.0, 60.0), (120.0, 120.0), (210.0, 60.0), (300.0, 120.0)] {
+            let dimension = Dimension::Angular {
+                first,
+                second,
+                location: ray(direction, 1.5),
+            };
+            assert!((dimension.measurement().to_degrees() - expected).abs() < 1e-6);
+            let layout = dimension.layout(&settings, &style);
+            assert_eq!(layout.text, format!("{:.2}°", expected));
+            assert_eq!(layout.arrows.len(), 2);
+            // Дуга проходит через указанное положение
+            let (_, _, _, radius) = dimension.angular_geometry().unwrap();
+            assert!((radius - 1.5).abs() < 1e-9);
+        }
+
+        // Точность стиля и только угловые отклонения: линейный допуск углу не приписывается
+        let dimension = Dimension::Angular {
+            first,
+            second,
+            location: ray(30.0, 1.5),
+        };
+        let mut parts = DimensionStyle::mm_parts();
+        parts.tolerance = Tolerance::Symmetric(0.1);
+        assert_eq!(dimension.layout(&settings, &parts).text, "60.0°");
+        parts.angular_tolerance = Tolerance::Deviation {
+            upper: 0.5f64.to_radians(),
+            lower: -0.25f64.to_radians(),
+        };
+        parts.precision = 2;
+        assert_eq!(
+            dimension.layout(&settings, &parts).text,
+            "60.00° +0.50° -0.25°"
+        );
+
+        // Параллельные отрезки угла не образуют
+        let parallel = Dimension::Angular {
+            first,
+            second: first.map(|point| point + DVec3::Z),
+            location: ray(90.0, 1.0),
+        };
+        assert!(parallel.angular_geometry().is_none());
+    }
+
+    #[test]
+    fn radial_and_arc_length_values() {
+        let settings = CoordinateSettings::default();
+        let style = DimensionStyle::default();
+        let center = FAR_POINT;
+        let point = center + DVec3::new(3.0, 0.0, 4.0);
+        let radial = |kind, location| Dimension::Radial {
+            kind,
+            center,
+            point,
+            location,
+        };
+
+        let inside = radial(RadialKind::Radius, center + DVec3::X);
+        assert_eq!(inside.layout(&settings, &style).text, "R5.00");
+        let diameter = radial(RadialKind::Diameter, center + DVec3::X * 8.0);
+        let layout = diameter.layout(&settings, &style);
+        assert_eq!(layout.text, "Ø10.00");
+        // Снаружи: линия от окружности к тексту и диаметр через центр
+        assert_eq!(layout.arrows.len(), 2);
+        assert!(layout.arrows[0]
+            .0
+            .abs_diff_eq(center + DVec3::X * 5.0, 1e-9));
+        let jogged = radial(RadialKind::JoggedRadius, center + DVec3::X);
+        assert_eq!(jogged.layout(&settings, &style).lines.len(), 3);
+
+        let arc = |location| Dimension::ArcLength {
+            center,
+            start: center + DVec3::X * 2.0,
+            end: center + DVec3::Z * 2.0,
+            location,
+        };
+        let quarter = arc(center + DVec3::new(3.0, 0.0, 3.0));
+        assert!((quarter.measurement() - PI).abs() < 1e-9);
+        assert_eq!(quarter.layout(&settings, &style).text, "◠3.14");
+        let rest = arc(center - DVec3::new(3.0, 0.0, 3.0));
+        assert!((rest.measurement() - 3.0 * PI).abs() < 1e-9);
+    }
+
+    #[test]
+    fn style_controls_text_and_sizes() {
+        let settings = CoordinateSettings::default();
+        let first = FAR_POINT;
+        let dimension = Dimension::Linear {
+            kind: LinearKind::Aligned,
+            first,
+            second: first + DVec3::X * 40.0,
+            location: first + DVec3::Z * 10.0,
+            direction: DVec2::X,
+        };
+
+        let mut style = DimensionStyle::mm_parts();
+        style.prefix = "2 отв. ".to_string();
+        style.tolerance = Tolerance::Deviation {
+            upper: 0.2,
+            lower: -0.1,
+        };
+        assert_eq!(
+            dimension.layout(&settings, &style).text,
+            "2 отв. 40.0 +0.2 -0.1"
+        );
+        style.tolerance = Tolerance::Symmetric(0.05);
+        style.precision = 2;
+        style.prefix.clear();
+        assert_eq!(dimension.layout(&settings, &style).text, "40.00 ±0.05");
+
+        // Выносная линия выходит за размерную на выход стиля, умноженный на масштаб
+        let extension = |style: &DimensionStyle| {
+            let (_, end) = dimension.layout(&settings, style).lines[0];
+            end.z - first.z - 10.0
+        };
+        assert!((extension(&style) - 2.0).abs() < 1e-9);
+        style.scale = 0.5;
+        assert!((extension(&style) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn anchors_follow_edited_lines() {
+        let (first, second, deleted) = (
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+            Entity::from_raw(3),
+        );
+        let origin = FAR_POINT;
+        // Первый отрезок растянут и сдвинут, второй повернут вокруг начала
+        let line = |entity| match entity {
+            e if e == first => Some([origin + DVec3::X, origin + DVec3::X * 5.0]),
+            e if e == second => Some([origin, origin + DVec3::new(1.0, 0.0, 1.0)]),
+            _ => None,
+        };
+
+        let middle = Anchor::OnLine {
+            line: first,
+            t: 0.5,
+        };
+        assert_eq!(middle.resolve(line), Some(Some(origin + DVec3::X * 3.0)));
+        let crossing = Anchor::Intersection { first, second };
+        let point = crossing.resolve(line).flatten().unwrap();
+        assert!(point.abs_diff_eq(origin, 1e-6));
+        // Удаленный отрезок разрывает связь
+        let lost = Anchor::OnLine {
+            line: deleted,
+            t: 0.0,
+        };
+        assert_eq!(lost.resolve(line), None);
+    }
+}
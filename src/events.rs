@@ -1,5 +1,19 @@
-use crate::coordinate_systems::{AngleUnit, CoordinateSystem};
-use bevy::prelude::*;
+use crate::coordinate_systems::{
+    AngleBase, AngleDirection, AngleUnit, CoordinateSystem, LinearUnit,
+};
+use crate::dimension_style::Tolerance;
+use crate::dimensions::DimensionCommand;
+use crate::keybindings::InputAction;
+use crate::leaders::LeaderCommand;
+use crate::linetypes::Linetype;
+use crate::snapping::SnapType;
+use crate::transform::FitKind;
+use bevy::{
+    ecs::system::SystemParam,
+    math::{DMat4, DVec2, DVec3},
+    prelude::*,
+};
+use std::path::PathBuf;
 
 // === СОБЫТИЯ ДЛЯ КАМЕРЫ ===
 #[derive(Event)]
@@ -24,6 +38,37 @@ pub struct AngleUnitChangeEvent {
     pub new_unit: AngleUnit,
 }
 
+/// Изменение отсчета углов: нулевое направление и сторона отсчета
+#[derive(Event, Clone, Copy)]
+pub enum AngleOrientationEvent {
+    SetBase(AngleBase),
+    SetDirection(AngleDirection),
+}
+
+/// Изменение геопривязки чертежа
+#[derive(Event, Clone, Copy)]
+pub enum GeoreferenceEvent {
+    /// Без привязки -> UTM -> Гаусс–Крюгер -> без привязки
+    CycleProjection,
+    /// Соседняя зона (±1)
+    ShiftZone(i32),
+    /// Северное или южное полушарие UTM
+    ToggleHemisphere,
+    CycleReadout,
+    /// Восток, север начала чертежа в метрах
+    SetOrigin(DVec2),
+    /// Поворот оси X чертежа от востока сетки, в радианах против часовой стрелки
+    SetRotation(f64),
+}
+
+/// Изменение линейных единиц и точности вывода
+#[derive(Event, Clone, Copy)]
+pub enum UnitsEvent {
+    SetLinearUnit(LinearUnit),
+    IncreasePrecision,
+    DecreasePrecision,
+}
+
 // === СОБЫТИЯ ДЛЯ ЛИНИЙ ===
 #[derive(Event)]
 pub struct CreateLineEvent;
@@ -34,13 +79,19 @@ pub struct DeleteAllLinesEvent;
 #[derive(Event)]
 pub struct LineCreatedEvent {
     pub line_id: u32,
-    pub start: Vec3,
-    pub end: Vec3,
+    pub start: DVec3,
+    pub end: DVec3,
+}
+
+/// Точка, введенная с клавиатуры (вместо клика)
+#[derive(Event)]
+pub struct TypedPointEvent {
+    pub point: DVec3,
 }
 
 #[derive(Event)]
 pub struct PointSelectedEvent {
-    pub point: Vec3,
+    pub point: DVec3,
     pub is_start: bool,
 }
 
@@ -50,6 +101,239 @@ pub struct GridStepChangeEvent {
     pub new_step: f32,
 }
 
+// === СОБЫТИЯ ДЛЯ ПРИВЯЗОК ===
+/// Переключение привязки: конкретного типа или всех привязок (None)
+#[derive(Event)]
+pub struct ObjectSnapToggleEvent {
+    pub snap_type: Option<SnapType>,
+}
+
+/// Управление привязкой к сетке
+#[derive(Event, Clone, Copy)]
+pub enum GridSnapEvent {
+    Toggle,
+    ToggleLink,
+    CycleStep,
+    CycleAngleStep,
+    SetStep(f32),
+}
+
+// === СОБЫТИЯ ДЛЯ ОТСЛЕЖИВАНИЯ ===
+/// Управление режимом ОРТО, полярным и объектным отслеживанием
+#[derive(Event, Clone)]
+pub enum TrackingEvent {
+    ToggleOrtho,
+    TogglePolar,
+    ToggleObjectTracking,
+    CycleAngles,
+    /// Задать пользовательский список углов (в градусах) и включить его
+    SetCustomAngles(Vec<f32>),
+}
+
+// === СОБЫТИЯ ДЛЯ НАЗНАЧЕНИЯ КЛАВИШ ===
+/// Управление редактором горячих клавиш
+#[derive(Event, Clone, Copy)]
+pub enum KeyBindingEvent {
+    StartRebind(InputAction),
+    ResetDefaults,
+    ToggleList,
+}
+
+// === СОБЫТИЯ ДЛЯ ПСК ===
+/// Управление пользовательскими системами координат и полюсом
+#[derive(Event, Clone)]
+pub enum UcsEvent {
+    SetWorld,
+    CycleNext,
+    DeleteActive,
+    Rename(String),
+    /// Новая ПСК по 1, 2 или 3 точкам
+    Define(usize),
+    PoleAtOrigin,
+    PoleAtLastPoint,
+    PickPole,
+}
+
+// === СОБЫТИЯ ДЛЯ ВЫДЕЛЕНИЯ И ПРЕОБРАЗОВАНИЙ ===
+/// Управление набором выделенных объектов
+#[derive(Event, Clone, Copy)]
+pub enum SelectionEvent {
+    SelectAll,
+    Clear,
+}
+
+/// Преобразование выделенных объектов
+#[derive(Event, Clone, Copy)]
+pub enum TransformEvent {
+    /// Применить матрицу (координаты чертежа: x, y — план, z — высота)
+    Apply(DMat4),
+    /// Начать указание пар точек для выравнивания
+    StartAlign(FitKind),
+    /// Подобрать преобразование по указанным парам и применить его
+    FinishAlign,
+    CancelAlign,
+}
+
+// === СОБЫТИЯ ДЛЯ РАЗМЕРОВ ===
+/// Простановка размеров
+#[derive(Event, Clone, Copy)]
+pub enum DimensionEvent {
+    /// Начать указание точек или отрезков размера
+    Start(DimensionCommand),
+    Cancel,
+    /// Помечать размеры, объект которых удален
+    ToggleFlagLost,
+}
+
+/// Выбор размерного стиля для новых размеров и правка текущего стиля
+#[derive(Event, Clone)]
+pub enum DimensionStyleEvent {
+    Next,
+    CycleArrow,
+    CycleTextPlacement,
+    IncreasePrecision,
+    DecreasePrecision,
+    MultiplyScale(f64),
+    /// Стрелка, высота текста, отступ и выход выносной линии
+    SetSizes([f64; 4]),
+    SetText {
+        prefix: String,
+        suffix: String,
+    },
+    SetTolerance(Tolerance),
+    /// Угловой допуск, радианы
+    SetAngularTolerance(Tolerance),
+}
+
+// === СОБЫТИЯ ДЛЯ ВЫНОСОК ===
+/// Создание и правка выносок
+#[derive(Event, Clone)]
+pub enum LeaderEvent {
+    Start(LeaderCommand),
+    /// Создать выноску по указанным точкам или применить команду к выбранным
+    Finish,
+    Cancel,
+    /// Текст для новых выносок
+    SetText(String),
+}
+
+// === СОБЫТИЯ ДЛЯ СЛОЕВ ===
+/// Управление слоями. Свойства меняются у слоя, открытого в панели.
+#[derive(Event, Clone)]
+pub enum LayerEvent {
+    /// Создать слой и сделать его текущим
+    New,
+    /// Открыть в панели следующий слой
+    EditNext,
+    MakeCurrent,
+    CycleColor,
+    CycleLinetype,
+    Thicker,
+    Thinner,
+    CycleTransparency,
+    ToggleOn,
+    ToggleFrozen,
+    ToggleLocked,
+    Rename(String),
+    /// Перенести выделенные объекты на текущий слой
+    MoveSelection,
+}
+
+// === СОБЫТИЯ ДЛЯ ТИПОВ ЛИНИЙ ===
+/// Общий масштаб узоров и новые определения типов линий
+#[derive(Event, Clone)]
+pub enum LinetypeEvent {
+    SetScale(f64),
+    MultiplyScale(f64),
+    /// Добавить типы линий или заменить одноименные
+    Define(Vec<Linetype>),
+    /// Сохранить все определения в файл .lin
+    Export,
+}
+
+// === СОБЫТИЯ ДЛЯ СВОЙСТВ ОБЪЕКТОВ ===
+/// Правка свойств выделенных объектов и копирование свойств
+#[derive(Event, Clone, Copy)]
+pub enum PropertyEvent {
+    ColorByLayer,
+    CycleColor,
+    LinetypeByLayer,
+    CycleLinetype,
+    LineweightByLayer,
+    Thicker,
+    Thinner,
+    TransparencyByLayer,
+    CycleTransparency,
+    /// Выбрать образец и передать его свойства другим объектам
+    StartMatch,
+    CancelMatch,
+}
+
+// === СОБЫТИЯ ДЛЯ БЛОКОВ ===
+/// Определение, вставка и расчленение блоков
+#[derive(Event, Clone)]
+pub enum BlockEvent {
+    /// Указать базовую точку и собрать выделенное в блок с именем из поля
+    StartDefine,
+    /// Указывать точки вставки текущего блока
+    StartInsert,
+    /// Указать точку подписи нового определения атрибута
+    StartAttribute,
+    Cancel,
+    /// Сделать текущим следующий блок палитры
+    Next,
+    /// Имя нового блока; совпадение с существующим переопределяет его
+    SetName(String),
+    /// Масштаб и поворот новых вставок
+    SetInsertion {
+        scale: f64,
+        rotation: f64,
+    },
+    /// Заменить выделенные вставки их содержимым
+    Explode,
+}
+
+// === СОБЫТИЯ ДЛЯ АТРИБУТОВ ===
+/// Определения атрибутов, ввод значений и извлечение в таблицу
+#[derive(Event, Clone)]
+pub enum AttributeEvent {
+    /// Тег, подсказка и значение по умолчанию новых определений
+    SetDefinition {
+        tag: String,
+        prompt: String,
+        default: String,
+    },
+    /// Видимость новых определений
+    ToggleVisible,
+    /// Значение очередного атрибута; `None` оставляет текущее
+    SetValue(Option<String>),
+    /// Закончить ввод, оставив остальные значения как есть
+    FinishEntry,
+    /// Править атрибуты выделенной вставки
+    Edit,
+    /// Записать значения атрибутов в CSV
+    Extract,
+    /// Файл, в который извлекаются значения
+    SetCsvPath(PathBuf),
+}
+
+// === СОБЫТИЯ ДЛЯ ГРУПП ===
+/// Создание, разгруппирование и переименование групп объектов
+#[derive(Event, Clone)]
+pub enum GroupEvent {
+    /// Объединить выделенные объекты в новую группу
+    Create,
+    /// Распустить группы выделенных объектов, без выделения — открытую
+    Ungroup,
+    /// Открыть в панели следующую группу
+    Next,
+    /// Добавить открытую группу к выделению
+    Select,
+    /// Выделять объекты групп по одному
+    ToggleIndividual,
+    Rename(String),
+}
+
 // === ДЕЙСТВИЯ UI (унифицированы с событиями) ===
 #[derive(Component, Clone)]
 pub enum UIAction {
@@ -59,13 +343,18 @@ pub enum UIAction {
 
     // Переключение систем координат
     ToggleCoordinateSystem,
-    SetCartesian,
-    SetPolar,
+    SetCoordinateSystem(CoordinateSystem),
 
     // Переключение единиц углов
     ToggleAngleUnit,
-    SetDegrees,
-    SetRadians,
+    SetAngleUnit(AngleUnit),
+    AngleOrientation(AngleOrientationEvent),
+
+    // Линейные единицы и точность
+    Units(UnitsEvent),
+
+    // Геопривязка
+    Georeference(GeoreferenceEvent),
 
     // Сетка
     SetGridStep(f32),
@@ -73,61 +362,174 @@ pub enum UIAction {
     // Камера
     ToggleCamera,
     ResetCamera,
+
+    // Объектные привязки
+    ToggleObjectSnaps,
+    ToggleSnapType(SnapType),
+
+    // Привязка к сетке
+    GridSnap(GridSnapEvent),
+
+    // ОРТО и полярное отслеживание
+    Tracking(TrackingEvent),
+
+    // Горячие клавиши
+    KeyBinding(KeyBindingEvent),
+
+    // Пользовательские системы координат
+    Ucs(UcsEvent),
+
+    // Выделение и преобразования
+    Selection(SelectionEvent),
+    Transform(TransformEvent),
+
+    // Размеры
+    Dimension(DimensionEvent),
+    DimensionStyle(DimensionStyleEvent),
+
+    // Выноски
+    Leader(LeaderEvent),
+
+    // Слои, типы линий и свойства объектов
+    Layer(LayerEvent),
+    Linetype(LinetypeEvent),
+    Properties(PropertyEvent),
+
+    // Блоки
+    Block(BlockEvent),
+    Attribute(AttributeEvent),
+
+    // Группы
+    Group(GroupEvent),
+}
+
+/// Все писатели событий, которые порождают кнопки интерфейса.
+/// Собраны в один параметр, чтобы системы UI не упирались в ограничение Bevy
+/// на число параметров.
+#[derive(SystemParam)]
+pub struct UiEventWriters<'w> {
+    pub coordinate: EventWriter<'w, CoordinateSystemChangeEvent>,
+    pub angle: EventWriter<'w, AngleUnitChangeEvent>,
+    pub orientation: EventWriter<'w, AngleOrientationEvent>,
+    pub units: EventWriter<'w, UnitsEvent>,
+    pub georeference: EventWriter<'w, GeoreferenceEvent>,
+    pub create_line: EventWriter<'w, CreateLineEvent>,
+    pub delete_lines: EventWriter<'w, DeleteAllLinesEvent>,
+    pub grid: EventWriter<'w, GridStepChangeEvent>,
+    pub camera_toggle: EventWriter<'w, CameraToggleEvent>,
+    pub camera_reset: EventWriter<'w, CameraResetEvent>,
+    pub snap: EventWriter<'w, ObjectSnapToggleEvent>,
+    pub grid_snap: EventWriter<'w, GridSnapEvent>,
+    pub tracking: EventWriter<'w, TrackingEvent>,
+    pub keybinding: EventWriter<'w, KeyBindingEvent>,
+    pub ucs: EventWriter<'w, UcsEvent>,
+    pub selection: EventWriter<'w, SelectionEvent>,
+    pub transform: EventWriter<'w, TransformEvent>,
+    pub dimension: EventWriter<'w, DimensionEvent>,
+    pub dimension_style: EventWriter<'w, DimensionStyleEvent>,
+    pub leader: EventWriter<'w, LeaderEvent>,
+    pub layer: EventWriter<'w, LayerEvent>,
+    pub linetype: EventWriter<'w, LinetypeEvent>,
+    pub properties: EventWriter<'w, PropertyEvent>,
+    pub block: EventWriter<'w, BlockEvent>,
+    pub attribute: EventWriter<'w, AttributeEvent>,
+    pub group: EventWriter<'w, GroupEvent>,
 }
 
 impl UIAction {
     /// Конвертирует UI действие в соответствующие события
-    pub fn emit_events(
-        &self,
-        coordinate_events: &mut EventWriter<CoordinateSystemChangeEvent>,
-        angle_events: &mut EventWriter<AngleUnitChangeEvent>,
-        line_events: &mut EventWriter<CreateLineEvent>,
-        delete_events: &mut EventWriter<DeleteAllLinesEvent>,
-        grid_events: &mut EventWriter<GridStepChangeEvent>,
-        camera_toggle_events: &mut EventWriter<CameraToggleEvent>,
-        camera_reset_events: &mut EventWriter<CameraResetEvent>,
-    ) {
+    pub fn emit_events(&self, events: &mut UiEventWriters) {
         match self {
             UIAction::CreateLine => {
-                line_events.write(CreateLineEvent);
+                events.create_line.write(CreateLineEvent);
             }
             UIAction::DeleteAll => {
-                delete_events.write(DeleteAllLinesEvent);
+                events.delete_lines.write(DeleteAllLinesEvent);
             }
             UIAction::ToggleCoordinateSystem => {
                 // Логика переключения будет в системе обработки
             }
-            UIAction::SetCartesian => {
-                coordinate_events.write(CoordinateSystemChangeEvent {
-                    new_system: CoordinateSystem::Cartesian,
-                });
-            }
-            UIAction::SetPolar => {
-                coordinate_events.write(CoordinateSystemChangeEvent {
-                    new_system: CoordinateSystem::Polar,
+            UIAction::SetCoordinateSystem(system) => {
+                events.coordinate.write(CoordinateSystemChangeEvent {
+                    new_system: *system,
                 });
             }
             UIAction::ToggleAngleUnit => {
                 // Логика переключения будет в системе обработки
             }
-            UIAction::SetDegrees => {
-                angle_events.write(AngleUnitChangeEvent {
-                    new_unit: AngleUnit::Degrees,
-                });
+            UIAction::SetAngleUnit(unit) => {
+                events.angle.write(AngleUnitChangeEvent { new_unit: *unit });
             }
-            UIAction::SetRadians => {
-                angle_events.write(AngleUnitChangeEvent {
-                    new_unit: AngleUnit::Radians,
-                });
+            UIAction::AngleOrientation(event) => {
+                events.orientation.write(*event);
+            }
+            UIAction::Units(event) => {
+                events.units.write(*event);
+            }
+            UIAction::Georeference(event) => {
+                events.georeference.write(*event);
             }
             UIAction::SetGridStep(step) => {
-                grid_events.write(GridStepChangeEvent { new_step: *step });
+                events.grid.write(GridStepChangeEvent { new_step: *step });
             }
             UIAction::ToggleCamera => {
-                camera_toggle_events.write(CameraToggleEvent);
+                events.camera_toggle.write(CameraToggleEvent);
             }
             UIAction::ResetCamera => {
-                camera_reset_events.write(CameraResetEvent);
+                events.camera_reset.write(CameraResetEvent);
+            }
+            UIAction::ToggleObjectSnaps => {
+                events.snap.write(ObjectSnapToggleEvent { snap_type: None });
+            }
+            UIAction::ToggleSnapType(snap_type) => {
+                events.snap.write(ObjectSnapToggleEvent {
+                    snap_type: Some(*snap_type),
+                });
+            }
+            UIAction::GridSnap(event) => {
+                events.grid_snap.write(*event);
+            }
+            UIAction::Tracking(event) => {
+                events.tracking.write(event.clone());
+            }
+            UIAction::KeyBinding(event) => {
+                events.keybinding.write(*event);
+            }
+            UIAction::Ucs(event) => {
+                events.ucs.write(event.clone());
+            }
+            UIAction::Selection(event) => {
+                events.selection.write(*event);
+            }
+            UIAction::Transform(event) => {
+                events.transform.write(*event);
+            }
+            UIAction::Dimension(event) => {
+                events.dimension.write(*event);
+            }
+            UIAction::DimensionStyle(event) => {
+                events.dimension_style.write(event.clone());
+            }
+            UIAction::Leader(event) => {
+                events.leader.write(event.clone());
+            }
+            UIAction::Layer(event) => {
+                events.layer.write(event.clone());
+            }
+            UIAction::Linetype(event) => {
+                events.linetype.write(event.clone());
+            }
+            UIAction::Properties(event) => {
+                events.properties.write(*event);
+            }
+            UIAction::Block(event) => {
+                events.block.write(event.clone());
+            }
+            UIAction::Attribute(event) => {
+                events.attribute.write(event.clone());
+            }
+            UIAction::Group(event) => {
+                events.group.write(event.clone());
             }
         }
     }
@@ -0,0 +1,697 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::coordinate_systems::{AngleUnit, LinearUnit};
+
+/// Ошибка разбора или вычисления выражения
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExpressionError {
+    Empty,
+    UnexpectedChar(char, usize),
+    UnexpectedToken(String, usize),
+    UnexpectedEnd,
+    UnknownVariable(String),
+    UnknownFunction(String),
+    UnknownSuffix(String),
+    WrongArgumentCount {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+    DivisionByZero,
+    InvalidResult,
+    InvalidVariableName(String),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::Empty => write!(f, "пустое выражение"),
+            ExpressionError::UnexpectedChar(c, pos) => {
+                write!(f, "недопустимый символ '{}' (позиция {})", c, pos + 1)
+            }
+            ExpressionError::UnexpectedToken(token, pos) => {
+                write!(f, "неожиданное '{}' (позиция {})", token, pos + 1)
+            }
+            ExpressionError::UnexpectedEnd => write!(f, "неожиданный конец выражения"),
+            ExpressionError::UnknownVariable(name) => {
+                write!(f, "неизвестная переменная '{}'", name)
+            }
+            ExpressionError::UnknownFunction(name) => write!(f, "неизвестная функция '{}'", name),
+            ExpressionError::UnknownSuffix(name) => write!(f, "неизвестный суффикс '{}'", name),
+            ExpressionError::WrongArgumentCount {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "функция '{}' ожидает аргументов: {}, получено: {}",
+                name, expected, got
+            ),
+            ExpressionError::DivisionByZero => write!(f, "деление на ноль"),
+            ExpressionError::InvalidResult => write!(f, "результат не является числом"),
+            ExpressionError::InvalidVariableName(name) => {
+                write!(f, "недопустимое имя переменной '{}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Пользовательские переменные (`W = 120`, затем `W/2`)
+#[derive(Resource, Default, Clone, Debug)]
+pub struct Variables {
+    values: HashMap<String, f64>,
+}
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_string(), value);
+    }
+}
+
+/// Контекст вычисления: переменные, единицы углов для тригонометрии и суффиксов
+/// и единицы чертежа, в которые переводятся длины с суффиксами (`25mm`, `3'6"`)
+#[derive(Clone, Copy)]
+pub struct Context<'a> {
+    pub variables: Option<&'a Variables>,
+    pub angle_unit: AngleUnit,
+    pub linear_unit: LinearUnit,
+}
+
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Self {
+            variables: None,
+            angle_unit: AngleUnit::Radians,
+            linear_unit: LinearUnit::Millimeters,
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    pub fn new(variables: &'a Variables, angle_unit: AngleUnit) -> Self {
+        Self {
+            variables: Some(variables),
+            angle_unit,
+            linear_unit: LinearUnit::Millimeters,
+        }
+    }
+
+    pub fn with_linear_unit(self, linear_unit: LinearUnit) -> Self {
+        Self {
+            linear_unit,
+            ..self
+        }
+    }
+
+    /// Перевод угла из радиан в единицы контекста
+    fn angle_from_radians(&self, radians: f64) -> f64 {
+        radians / self.angle_unit.radians_per_unit()
+    }
+
+    /// Перевод угла из единиц контекста в радианы
+    fn angle_to_radians(&self, value: f64) -> f64 {
+        value * self.angle_unit.radians_per_unit()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Number(value) => value.to_string(),
+            Token::Ident(name) => name.clone(),
+            Token::Op(op) => op.to_string(),
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::Comma => ",".to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ExpressionError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // Экспоненциальная запись: 1e3, 2.5E-2
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<f64>()
+                    .map_err(|_| ExpressionError::UnexpectedToken(text.clone(), start))?;
+                tokens.push((Token::Number(value), start));
+            }
+            c if c.is_alphabetic() || c == '_' || matches!(c, '°' | '\'' | '"') => {
+                let start = i;
+                if matches!(c, '°' | '\'' | '"') {
+                    i += 1;
+                } else {
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '=' => {
+                tokens.push((Token::Op(c), i));
+                i += 1;
+            }
+            '(' => {
+                tokens.push((Token::LParen, i));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::RParen, i));
+                i += 1;
+            }
+            ',' => {
+                tokens.push((Token::Comma, i));
+                i += 1;
+            }
+            _ => return Err(ExpressionError::UnexpectedChar(c, i)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Рекурсивный нисходящий разбор с немедленным вычислением
+struct Parser<'a, 'c> {
+    tokens: &'a [(Token, usize)],
+    position: usize,
+    context: &'a Context<'c>,
+}
+
+impl Parser<'_, '_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens
+            .get(self.position + offset)
+            .map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.tokens.get(self.position) {
+            Some((token, pos)) => ExpressionError::UnexpectedToken(token.text(), *pos),
+            None => ExpressionError::UnexpectedEnd,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ExpressionError> {
+        if self.peek() == Some(&expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.position += 1;
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                _ if rhs == 0.0 => return Err(ExpressionError::DivisionByZero),
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    // unary := ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<f64, ExpressionError> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.position += 1;
+                Ok(-self.unary()?)
+            }
+            Some(Token::Op('+')) => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    // power := postfix ('^' unary)?  (правоассоциативно)
+    fn power(&mut self) -> Result<f64, ExpressionError> {
+        let base = self.postfix()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.position += 1;
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    // postfix := primary suffix?  (45d, 0.5r, (pi/6)r, 30°, 50g, 25mm, 3'6")
+    fn postfix(&mut self) -> Result<f64, ExpressionError> {
+        let value = self.primary()?;
+        if let Some(Token::Ident(name)) = self.peek().cloned() {
+            // Идентификатор сразу после значения может быть только суффиксом
+            if self.peek_at(1) != Some(&Token::LParen) {
+                self.position += 1;
+                let value = self.apply_suffix(value, &name)?;
+                if matches!(name.as_str(), "'" | "ft") {
+                    return Ok(value + self.trailing_inches()?);
+                }
+                return Ok(value);
+            }
+        }
+        Ok(value)
+    }
+
+    // Дюймы сразу после футов: 3'6", 3'6, 3'-6" (но 3'-6 — это вычитание)
+    fn trailing_inches(&mut self) -> Result<f64, ExpressionError> {
+        let offset = usize::from(self.peek() == Some(&Token::Op('-')));
+        let Some(Token::Number(inches)) = self.peek_at(offset).cloned() else {
+            return Ok(0.0);
+        };
+        let marked = matches!(
+            self.peek_at(offset + 1),
+            Some(Token::Ident(name)) if name == "\"" || name == "in"
+        );
+        if offset == 1 && !marked {
+            return Ok(0.0);
+        }
+        self.position += offset + 1 + usize::from(marked);
+        self.apply_suffix(inches, "in")
+    }
+
+    fn apply_suffix(&self, value: f64, suffix: &str) -> Result<f64, ExpressionError> {
+        let millimeters = match suffix {
+            "mm" => Some(1.0),
+            "cm" => Some(10.0),
+            "m" => Some(1000.0),
+            "in" | "\"" => Some(25.4),
+            "ft" | "'" => Some(304.8),
+            _ => None,
+        };
+        if let Some(millimeters) = millimeters {
+            return Ok(value * millimeters / self.context.linear_unit.millimeters_per_unit());
+        }
+
+        let radians = match suffix {
+            "d" | "deg" | "°" => value.to_radians(),
+            "r" | "rad" => value,
+            "g" | "grad" => value * std::f64::consts::PI / 200.0,
+            _ => return Err(ExpressionError::UnknownSuffix(suffix.to_string())),
+        };
+        Ok(self.context.angle_from_radians(radians))
+    }
+
+    // primary := number | ident | ident '(' args ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<f64, ExpressionError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::LParen) => {
+                let value = self.expression()?;
+                self.expect(Token::RParen)?;
+                Ok(value)
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.position += 1;
+                    let mut args = Vec::new();
+                    if self.peek() != Some(&Token::RParen) {
+                        args.push(self.expression()?);
+                        while self.peek() == Some(&Token::Comma) {
+                            self.position += 1;
+                            args.push(self.expression()?);
+                        }
+                    }
+                    self.expect(Token::RParen)?;
+                    self.call(&name, &args)
+                } else {
+                    self.variable(&name)
+                }
+            }
+            Some(_) => {
+                self.position -= 1;
+                Err(self.unexpected())
+            }
+            None => Err(ExpressionError::UnexpectedEnd),
+        }
+    }
+
+    fn variable(&self, name: &str) -> Result<f64, ExpressionError> {
+        if let Some(value) = self.context.variables.and_then(|vars| vars.get(name)) {
+            return Ok(value);
+        }
+        match name {
+            "pi" | "π" => Ok(std::f64::consts::PI),
+            "tau" => Ok(std::f64::consts::TAU),
+            "e" => Ok(std::f64::consts::E),
+            _ => Err(ExpressionError::UnknownVariable(name.to_string())),
+        }
+    }
+
+    fn call(&self, name: &str, args: &[f64]) -> Result<f64, ExpressionError> {
+        let context = self.context;
+        let arity = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                Err(ExpressionError::WrongArgumentCount {
+                    name: name.to_string(),
+                    expected,
+                    got: args.len(),
+                })
+            }
+        };
+
+        match name {
+            "sqrt" | "abs" | "ln" | "log" | "exp" | "floor" | "ceil" | "round" | "sin" | "cos"
+            | "tan" | "asin" | "acos" | "atan" => {
+                arity(1)?;
+                let x = args[0];
+                Ok(match name {
+                    "sqrt" => x.sqrt(),
+                    "abs" => x.abs(),
+                    "ln" => x.ln(),
+                    "log" => x.log10(),
+                    "exp" => x.exp(),
+                    "floor" => x.floor(),
+                    "ceil" => x.ceil(),
+                    "round" => x.round(),
+                    "sin" => context.angle_to_radians(x).sin(),
+                    "cos" => context.angle_to_radians(x).cos(),
+                    "tan" => context.angle_to_radians(x).tan(),
+                    "asin" => context.angle_from_radians(x.asin()),
+                    "acos" => context.angle_from_radians(x.acos()),
+                    _ => context.angle_from_radians(x.atan()),
+                })
+            }
+            "atan2" | "hypot" | "pow" => {
+                arity(2)?;
+                let (a, b) = (args[0], args[1]);
+                Ok(match name {
+                    "atan2" => context.angle_from_radians(a.atan2(b)),
+                    "hypot" => a.hypot(b),
+                    _ => a.powf(b),
+                })
+            }
+            "min" | "max" if !args.is_empty() => {
+                let fold = if name == "min" { f64::min } else { f64::max };
+                Ok(args.iter().copied().fold(args[0], fold))
+            }
+            "min" | "max" => Err(ExpressionError::WrongArgumentCount {
+                name: name.to_string(),
+                expected: 1,
+                got: 0,
+            }),
+            _ => Err(ExpressionError::UnknownFunction(name.to_string())),
+        }
+    }
+}
+
+fn finite(value: f64) -> Result<f64, ExpressionError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(ExpressionError::InvalidResult)
+    }
+}
+
+/// Вычисление выражения, например `12.5*2 + sqrt(3)`, `pi/6` или `45d`
+pub fn evaluate(input: &str, context: &Context) -> Result<f64, ExpressionError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(ExpressionError::Empty);
+    }
+
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        context,
+    };
+    let value = parser.expression()?;
+    if parser.position < tokens.len() {
+        return Err(parser.unexpected());
+    }
+    finite(value)
+}
+
+/// Результат выполнения строки калькулятора
+#[derive(Clone, PartialEq, Debug)]
+pub enum Statement {
+    Value(f64),
+    Assignment(String, f64),
+}
+
+/// Выполнение строки: присваивание `W = 120` или обычное выражение
+pub fn execute(
+    input: &str,
+    variables: &mut Variables,
+    angle_unit: AngleUnit,
+    linear_unit: LinearUnit,
+) -> Result<Statement, ExpressionError> {
+    if let Some((name, expression)) = split_assignment(input) {
+        let name = name.trim();
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid {
+            return Err(ExpressionError::InvalidVariableName(name.to_string()));
+        }
+
+        let context = Context::new(variables, angle_unit).with_linear_unit(linear_unit);
+        let value = evaluate(expression, &context)?;
+        variables.set(name, value);
+        return Ok(Statement::Assignment(name.to_string(), value));
+    }
+
+    let context = Context::new(variables, angle_unit).with_linear_unit(linear_unit);
+    evaluate(input, &context).map(Statement::Value)
+}
+
+fn split_assignment(input: &str) -> Option<(&str, &str)> {
+    let index = input.find('=')?;
+    Some((&input[..index], &input[index + 1..]))
+}
+
+/// Разделение строки по символу вне скобок (например, `min(1, 2), 3` по запятой)
+pub fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&input[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Result<f64, ExpressionError> {
+        evaluate(input, &Context::default())
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn arithmetic_precedence() {
+        assert_close(eval("12.5*2 + sqrt(3)").unwrap(), 25.0 + 3f64.sqrt());
+        assert_close(eval("2 + 3 * 4").unwrap(), 14.0);
+        assert_close(eval("(2 + 3) * 4").unwrap(), 20.0);
+        assert_close(eval("-2^2").unwrap(), -4.0);
+        assert_close(eval("2^3^2").unwrap(), 512.0);
+        assert_close(eval("7 % 4").unwrap(), 3.0);
+        assert_close(eval("1.5e2").unwrap(), 150.0);
+    }
+
+    #[test]
+    fn constants_and_functions() {
+        assert_close(eval("pi/6").unwrap(), std::f64::consts::PI / 6.0);
+        assert_close(eval("max(1, 5, 3)").unwrap(), 5.0);
+        assert_close(eval("hypot(3, 4)").unwrap(), 5.0);
+        assert_close(eval("round(2.6) + floor(2.6)").unwrap(), 5.0);
+    }
+
+    #[test]
+    fn angle_suffixes_follow_context_unit() {
+        assert_close(eval("45d").unwrap(), std::f64::consts::FRAC_PI_4);
+        assert_close(eval("30°").unwrap(), std::f64::consts::FRAC_PI_6);
+
+        let vars = Variables::default();
+        let degrees = Context::new(&vars, AngleUnit::Degrees);
+        assert_close(evaluate("45d", &degrees).unwrap(), 45.0);
+        assert_close(evaluate("(pi/2)r", &degrees).unwrap(), 90.0);
+        assert_close(evaluate("sin(30)", &degrees).unwrap(), 0.5);
+        assert_close(evaluate("atan2(1, 1)", &degrees).unwrap(), 45.0);
+        assert_close(evaluate("100g", &degrees).unwrap(), 90.0);
+
+        let gradians = Context::new(&vars, AngleUnit::Gradians);
+        assert_close(evaluate("90d", &gradians).unwrap(), 100.0);
+        assert_close(evaluate("sin(100)", &gradians).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn length_suffixes_convert_to_drawing_units() {
+        let vars = Variables::default();
+        let millimeters = Context::new(&vars, AngleUnit::Degrees);
+        assert_close(evaluate("25mm + 2cm", &millimeters).unwrap(), 45.0);
+        assert_close(evaluate("1.5m", &millimeters).unwrap(), 1500.0);
+        assert_close(evaluate("1in", &millimeters).unwrap(), 25.4);
+
+        let inches = millimeters.with_linear_unit(LinearUnit::Architectural);
+        assert_close(evaluate("3'6\"", &inches).unwrap(), 42.0);
+        assert_close(evaluate("3'-6\"", &inches).unwrap(), 42.0);
+        assert_close(evaluate("3'6", &inches).unwrap(), 42.0);
+        assert_close(evaluate("3' - 6", &inches).unwrap(), 30.0);
+        assert_close(evaluate("2ft + 25.4mm", &inches).unwrap(), 25.0);
+        assert_close(evaluate("(6 + 1/2)\"", &inches).unwrap(), 6.5);
+    }
+
+    #[test]
+    fn variables_and_assignment() {
+        let mut vars = Variables::default();
+        assert_eq!(
+            execute(
+                "W = 120",
+                &mut vars,
+                AngleUnit::Degrees,
+                LinearUnit::Millimeters
+            ),
+            Ok(Statement::Assignment("W".to_string(), 120.0))
+        );
+        assert_eq!(
+            execute(
+                "W/2",
+                &mut vars,
+                AngleUnit::Degrees,
+                LinearUnit::Millimeters
+            ),
+            Ok(Statement::Value(60.0))
+        );
+        assert_eq!(
+            execute(
+                "1W = 3",
+                &mut vars,
+                AngleUnit::Degrees,
+                LinearUnit::Millimeters
+            ),
+            Err(ExpressionError::InvalidVariableName("1W".to_string()))
+        );
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        assert_eq!(eval(""), Err(ExpressionError::Empty));
+        assert_eq!(eval("1 / 0"), Err(ExpressionError::DivisionByZero));
+        assert_eq!(eval("2 +"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(eval("(1 + 2"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(eval("1 $ 2"), Err(ExpressionError::UnexpectedChar('$', 2)));
+        assert_eq!(
+            eval("foo + 1"),
+            Err(ExpressionError::UnknownVariable("foo".to_string()))
+        );
+        assert_eq!(
+            eval("bar(1)"),
+            Err(ExpressionError::UnknownFunction("bar".to_string()))
+        );
+        assert_eq!(
+            eval("5x"),
+            Err(ExpressionError::UnknownSuffix("x".to_string()))
+        );
+        assert_eq!(eval("sqrt(-1)"), Err(ExpressionError::InvalidResult));
+        assert!(matches!(
+            eval("sqrt(1, 2)"),
+            Err(ExpressionError::WrongArgumentCount { .. })
+        ));
+    }
+
+    #[test]
+    fn top_level_split_ignores_parentheses() {
+        assert_eq!(
+            split_top_level("min(1, 2), 3", ','),
+            vec!["min(1, 2)", " 3"]
+        );
+        assert_eq!(split_top_level("5 < 30d", '<'), vec!["5 ", " 30d"]);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{events::GridStepChangeEvent, render::RenderModes};
+use crate::{
+    events::GridStepChangeEvent, input_field::FieldInput, render::RenderModes,
+    render_origin::DocumentPosition,
+};
 use bevy::{
     color::palettes::css::{BLUE, RED},
     prelude::*,
@@ -107,6 +110,7 @@ pub fn setup_grid(
             Mesh3d(vertical_mesh.clone()),
             MeshMaterial3d(grid_materials.grid_material.clone()),
             Transform::from_xyz(x, 0.0, 0.0),
+            DocumentPosition(Vec3::new(x, 0.0, 0.0).as_dvec3()),
             GridLine,
             Name::new(format!("GridLine_Vertical_{}", i)),
             // Transform и Visibility добавляются автоматически
@@ -121,6 +125,7 @@ pub fn setup_grid(
             Mesh3d(horizontal_mesh.clone()),
             MeshMaterial3d(grid_materials.grid_material.clone()),
             Transform::from_xyz(0.0, 0.0, z),
+            DocumentPosition(Vec3::new(0.0, 0.0, z).as_dvec3()),
             GridLine,
             Name::new(format!("GridLine_Horizontal_{}", i)),
             // Transform и Visibility добавляются автоматически
@@ -133,6 +138,7 @@ pub fn setup_grid(
         Mesh3d(axis_x_mesh),
         MeshMaterial3d(grid_materials.axis_x_material.clone()),
         Transform::from_xyz(0.0, 0.01, 0.0),
+        DocumentPosition(Vec3::new(0.0, 0.01, 0.0).as_dvec3()),
         GridAxis,
         Name::new("GridAxis_X"),
         // Transform и Visibility добавляются автоматически
@@ -143,6 +149,7 @@ pub fn setup_grid(
         Mesh3d(axis_z_mesh),
         MeshMaterial3d(grid_materials.axis_z_material.clone()),
         Transform::from_xyz(0.0, 0.01, 0.0),
+        DocumentPosition(Vec3::new(0.0, 0.01, 0.0).as_dvec3()),
         GridAxis,
         Name::new("GridAxis_Z"),
         // Transform и Visibility добавляются автоматически
@@ -222,3 +229,15 @@ pub fn handle_grid_step_events(
         info!("Grid step changed to: {}", event.new_step);
     }
 }
+
+/// Поле шага сетки
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let step = input.evaluate(input.text)?;
+    if step <= 0.0 {
+        return Err("шаг должен быть положительным".to_string());
+    }
+    input.events.grid.write(GridStepChangeEvent {
+        new_step: step as f32,
+    });
+    Ok(format!("= {}", input.length(step)))
+}
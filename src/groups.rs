@@ -0,0 +1,295 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    attributes::AttributeDefinition,
+    blocks::BlockReference,
+    events::GroupEvent,
+    input_field::FieldInput,
+    layers::{LayerId, Layers},
+    line_drawing::DrawableLine,
+    selection::Selection,
+    Id,
+};
+
+/// Объекты документа, которые получают номера и входят в группы
+type DocumentObject = Or<(
+    With<DrawableLine>,
+    With<BlockReference>,
+    With<AttributeDefinition>,
+)>;
+
+/// Именованный набор объектов документа, выделяемых вместе.
+/// Объекты хранятся по постоянным номерам, а не по сущностям.
+pub struct Group {
+    pub name: String,
+    pub members: Vec<Id>,
+}
+
+/// Группы документа. В отличие от блока объекты группы остаются на чертеже
+/// и правятся по отдельности; группа только связывает их при выделении.
+/// Объект входит не более чем в одну группу.
+#[derive(Resource, Default)]
+pub struct Groups {
+    pub groups: Vec<Group>,
+    /// Группа, открытая в панели
+    pub current: usize,
+    /// Выделять объекты групп по одному
+    pub individual: bool,
+}
+
+impl Groups {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.groups.iter().position(|group| group.name == name)
+    }
+
+    /// Первое незанятое имя вида «Группа N»
+    pub fn free_name(&self) -> String {
+        (1..)
+            .map(|number| format!("Группа {}", number))
+            .find(|name| self.find(name).is_none())
+            .unwrap_or_default()
+    }
+
+    pub fn group_of(&self, id: Id) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.members.contains(&id))
+    }
+
+    /// Объекты, выделяемые щелчком по объекту: вся его группа или только он сам
+    pub fn members_of(&self, id: Id) -> Vec<Id> {
+        match self.group_of(id).filter(|_| !self.individual) {
+            Some(index) => self.groups[index].members.clone(),
+            None => vec![id],
+        }
+    }
+
+    /// Новая группа становится текущей; объекты покидают прежние группы
+    pub fn create(&mut self, name: String, members: Vec<Id>) {
+        self.retain_members(|id| !members.contains(&id));
+        self.groups.push(Group { name, members });
+        self.current = self.groups.len() - 1;
+    }
+
+    pub fn ungroup(&mut self, index: usize) -> Group {
+        let group = self.groups.remove(index);
+        self.current = self.current.min(self.groups.len().saturating_sub(1));
+        group
+    }
+
+    /// Оставить в группах только подходящие объекты; опустевшие группы удаляются
+    pub fn retain_members(&mut self, mut keep: impl FnMut(Id) -> bool) {
+        for group in &mut self.groups {
+            group.members.retain(|id| keep(*id));
+        }
+        self.groups.retain(|group| !group.members.is_empty());
+        self.current = self.current.min(self.groups.len().saturating_sub(1));
+    }
+}
+
+/// Сущности объектов с данными номерами, кроме объектов скрытых
+/// и заблокированных слоев: их нельзя выделить
+pub fn editable_entities(
+    ids: &[Id],
+    object_query: &Query<(Entity, &Id, Option<&LayerId>)>,
+    layers: &Layers,
+) -> Vec<Entity> {
+    object_query
+        .iter()
+        .filter(|(_, id, layer)| {
+            ids.contains(id) && layer.is_none_or(|layer| layers.is_editable(*layer))
+        })
+        .map(|(entity, ..)| entity)
+        .collect()
+}
+
+/// Объекты, выделяемые щелчком по `entity`: его группа или он сам
+pub fn selectable_members(
+    groups: &Groups,
+    entity: Entity,
+    object_query: &Query<(Entity, &Id, Option<&LayerId>)>,
+    layers: &Layers,
+) -> Vec<Entity> {
+    match object_query.get(entity) {
+        Ok((_, id, _)) => editable_entities(&groups.members_of(*id), object_query, layers),
+        Err(_) => vec![entity],
+    }
+}
+
+/// Новые объекты документа получают постоянные номера
+pub fn assign_ids_system(
+    mut commands: Commands,
+    mut next: Local<u32>,
+    new_query: Query<Entity, (Without<Id>, DocumentObject)>,
+) {
+    for entity in new_query.iter() {
+        commands.entity(entity).insert(Id(*next));
+        *next += 1;
+    }
+}
+
+/// Система обработки событий групп
+pub fn handle_group_events(
+    mut events: EventReader<GroupEvent>,
+    mut groups: ResMut<Groups>,
+    mut selection: ResMut<Selection>,
+    layers: Res<Layers>,
+    object_query: Query<(Entity, &Id, Option<&LayerId>)>,
+) {
+    for event in events.read() {
+        let selected: Vec<Id> = selection
+            .entities
+            .iter()
+            .filter_map(|entity| object_query.get(*entity).ok())
+            .map(|(_, id, _)| *id)
+            .collect();
+        match event {
+            GroupEvent::Create => {
+                if selected.is_empty() {
+                    warn!("Nothing selected");
+                    continue;
+                }
+                let name = groups.free_name();
+                info!("Group {}: {} objects", name, selected.len());
+                groups.create(name, selected);
+            }
+            GroupEvent::Ungroup => {
+                // Группы выделенных объектов, а без выделения — открытая в панели
+                let mut indices: Vec<usize> = selected
+                    .iter()
+                    .filter_map(|id| groups.group_of(*id))
+                    .collect();
+                if selection.entities.is_empty() && !groups.groups.is_empty() {
+                    indices.push(groups.current);
+                }
+                indices.sort_unstable();
+                indices.dedup();
+                for index in indices.into_iter().rev() {
+                    let group = groups.ungroup(index);
+                    info!("Group {} removed", group.name);
+                }
+            }
+            GroupEvent::Next => {
+                if !groups.groups.is_empty() {
+                    groups.current = (groups.current + 1) % groups.groups.len();
+                }
+            }
+            GroupEvent::Select => {
+                let Some(group) = groups.groups.get(groups.current) else {
+                    continue;
+                };
+                for entity in editable_entities(&group.members, &object_query, &layers) {
+                    if !selection.contains(entity) {
+                        selection.entities.push(entity);
+                    }
+                }
+                info!("Selected objects: {}", selection.entities.len());
+            }
+            GroupEvent::ToggleIndividual => groups.individual = !groups.individual,
+            GroupEvent::Rename(name) => {
+                let current = groups.current;
+                if groups.groups.is_empty() {
+                    warn!("No groups to rename");
+                    continue;
+                }
+                if groups.find(name).is_some_and(|index| index != current) {
+                    warn!("Group {} already exists", name);
+                    continue;
+                }
+                groups.groups[current].name = name.clone();
+            }
+        }
+    }
+}
+
+/// Поле имени открытой группы
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let name = input.text.trim();
+    if name.is_empty() {
+        return Err("имя группы не может быть пустым".to_string());
+    }
+    input
+        .events
+        .group
+        .write(GroupEvent::Rename(name.to_string()));
+    Ok(format!("группа: {}", name))
+}
+
+/// Удаленные объекты (в том числе собранные в блок или расчлененные) покидают группы
+pub fn prune_groups_system(mut groups: ResMut<Groups>, object_query: Query<&Id, DocumentObject>) {
+    if groups.groups.is_empty() {
+        return;
+    }
+    let existing: HashSet<Id> = object_query.iter().copied().collect();
+    if !groups
+        .groups
+        .iter()
+        .all(|group| group.members.iter().all(|id| existing.contains(id)))
+    {
+        groups.retain_members(|id| existing.contains(&id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_select_together_and_own_each_object_once() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(Id);
+        let mut groups = Groups::default();
+        groups.create(groups.free_name(), vec![a, b, c]);
+        assert_eq!(groups.groups[0].name, "Группа 1");
+        assert_eq!(groups.members_of(b), vec![a, b, c]);
+        assert_eq!(groups.members_of(d), vec![d]);
+
+        groups.individual = true;
+        assert_eq!(groups.members_of(b), vec![b]);
+        groups.individual = false;
+
+        // Объект переходит в новую группу, старая теряет его
+        groups.create(groups.free_name(), vec![c, d]);
+        assert_eq!(groups.groups[1].name, "Группа 2");
+        assert_eq!(groups.members_of(a), vec![a, b]);
+        assert_eq!(groups.members_of(d), vec![c, d]);
+        assert_eq!(groups.current, 1);
+
+        // Группа без объектов исчезает
+        groups.retain_members(|id| id != a && id != b);
+        assert_eq!(groups.groups.len(), 1);
+        assert_eq!(groups.groups[0].name, "Группа 2");
+        assert_eq!(groups.current, 0);
+    }
+
+    #[test]
+    fn group_selection_skips_locked_and_hidden_layers() {
+        let mut layers = Layers::default();
+        let locked = layers.add();
+        layers.layers[locked].locked = true;
+        let hidden = layers.add();
+        layers.layers[hidden].on = false;
+
+        let mut world = World::new();
+        let open = world.spawn((Id(0), LayerId(0))).id();
+        world.spawn((Id(1), LayerId(locked)));
+        world.spawn((Id(2), LayerId(hidden)));
+        let single = world.spawn((Id(3), LayerId(0))).id();
+        let mut state = world.query::<(Entity, &Id, Option<&LayerId>)>();
+        let object_query = state.query(&world);
+
+        let mut groups = Groups::default();
+        groups.create(groups.free_name(), vec![Id(0), Id(1), Id(2)]);
+        let members = &groups.groups[0].members;
+        assert_eq!(editable_entities(members, &object_query, &layers), [open]);
+        assert_eq!(
+            selectable_members(&groups, open, &object_query, &layers),
+            [open]
+        );
+        assert_eq!(
+            selectable_members(&groups, single, &object_query, &layers),
+            [single]
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::{
+    attributes, blocks,
+    coordinate_systems::{self, formatting, parsing, AngleDirection, CoordinateSettings},
+    dimension_style,
+    events::{TypedPointEvent, UiEventWriters},
+    expression::{self, Context, Statement, Variables},
+    grid, groups, layers, leaders, linetypes,
+    snapping::{self, SnapState},
+    tracking, transform,
+    ucs::{self, UcsManager},
+};
+
+/// Назначение поля ввода
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputFieldKind {
+    GridStep,
+    SnapStep,
+    Point,
+    Calculator,
+    UcsName,
+    TransformMatrix,
+    TransformSteps,
+    GeoOrigin,
+    GeoRotation,
+    DimensionSizes,
+    DimensionText,
+    DimensionTolerance,
+    DimensionAngularTolerance,
+    LeaderText,
+    LayerName,
+    LinetypeScale,
+    LinetypeDefinition,
+    BlockName,
+    BlockInsertion,
+    AttributeDefinition,
+    AttributeValue,
+    AttributeCsvPath,
+    GroupName,
+    TrackingAngles,
+}
+
+impl InputFieldKind {
+    /// Подсказка в пустом поле
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            InputFieldKind::GridStep | InputFieldKind::SnapStep => "шаг, напр. 0.25*2",
+            InputFieldKind::Point => {
+                "x, y[, z]  |  r < θ[, h]  |  ρ < θ < φ  |  @dx, dy  |  geo B, L  |  grid E, N"
+            }
+            InputFieldKind::Calculator => "W = 120, W/2",
+            InputFieldKind::UcsName => "имя текущей ПСК",
+            InputFieldKind::TransformMatrix => "a, b, c; d, e, f; 0, 0, 1  |  4×4",
+            InputFieldKind::TransformSteps => "move 10, 0; rotate 30; scale 2; shear 0.5, 0",
+            InputFieldKind::GeoOrigin => "E, N начала чертежа, м",
+            InputFieldKind::GeoRotation => "поворот чертежа от востока",
+            InputFieldKind::DimensionSizes => "стрелка, текст, отступ, выход",
+            InputFieldKind::DimensionText => "префикс<>суффикс, напр. 4 отв. <>",
+            InputFieldKind::DimensionTolerance => "допуск: ±t  |  верхнее, нижнее  |  пусто",
+            InputFieldKind::DimensionAngularTolerance => "угловой допуск: ±t  |  верхнее, нижнее",
+            InputFieldKind::LeaderText => "текст новых выносок",
+            InputFieldKind::LayerName => "имя открытого слоя",
+            InputFieldKind::LinetypeScale => "общий масштаб типов линий",
+            InputFieldKind::LinetypeDefinition => "*Имя,описание; A,штрих,-пробел,0",
+            InputFieldKind::BlockName => "имя нового блока",
+            InputFieldKind::BlockInsertion => "масштаб[, поворот] вставок",
+            InputFieldKind::AttributeDefinition => "ТЕГ; подсказка; значение по умолчанию",
+            InputFieldKind::AttributeValue => "значение атрибута, пусто — оставить",
+            InputFieldKind::AttributeCsvPath => "файл CSV (от текущего каталога)",
+            InputFieldKind::GroupName => "имя открытой группы",
+            InputFieldKind::TrackingAngles => "свои углы, °: 0, 22.5, 90 - 22.5",
+        }
+    }
+}
+
+/// Текстовое поле, значение которого вычисляется как выражение
+#[derive(Component)]
+pub struct InputField {
+    pub kind: InputFieldKind,
+    pub text: String,
+    pub message: Option<(String, bool)>, // (текст, это ошибка)
+}
+
+impl InputField {
+    pub fn new(kind: InputFieldKind) -> Self {
+        Self {
+            kind,
+            text: String::new(),
+            message: None,
+        }
+    }
+}
+
+/// Текст, отображающий содержимое поля
+#[derive(Component)]
+pub struct InputFieldText {
+    pub field: Entity,
+}
+
+/// Строка сообщения под полем (результат или ошибка)
+#[derive(Component)]
+pub struct InputFieldMessage {
+    pub field: Entity,
+}
+
+/// Поле ввода, получающее нажатия клавиш
+#[derive(Resource, Default)]
+pub struct TextInputFocus {
+    pub field: Option<Entity>,
+}
+
+/// Фокус по клику: клик по полю выбирает его, клик в другом месте снимает фокус
+pub fn focus_input_fields_system(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    field_query: Query<(Entity, &Interaction), With<InputField>>,
+    mut focus: ResMut<TextInputFocus>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let pressed = field_query
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        .map(|(entity, _)| entity);
+    if focus.field != pressed {
+        focus.field = pressed;
+    }
+}
+
+/// Ввод текста в поле с фокусом: Enter - применить, Esc - снять фокус
+pub fn text_input_system(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut focus: ResMut<TextInputFocus>,
+    mut field_query: Query<&mut InputField>,
+    mut variables: ResMut<Variables>,
+    coordinate_settings: Res<CoordinateSettings>,
+    snap_state: Res<SnapState>,
+    ucs_manager: Res<UcsManager>,
+    mut point_events: EventWriter<TypedPointEvent>,
+    mut events: UiEventWriters,
+) {
+    let Some(entity) = focus.field else {
+        keyboard_events.clear();
+        return;
+    };
+    let Ok(mut field) = field_query.get_mut(entity) else {
+        focus.field = None;
+        return;
+    };
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let text = field.text.clone();
+                let result = submit(
+                    field.kind,
+                    &text,
+                    &mut variables,
+                    &coordinate_settings,
+                    &snap_state,
+                    &ucs_manager,
+                    &mut point_events,
+                    &mut events,
+                );
+                match result {
+                    Ok(message) => {
+                        field.message = Some((message, false));
+                        // Точки и значения атрибутов вводятся подряд, поэтому поле очищается
+                        if matches!(
+                            field.kind,
+                            InputFieldKind::Point | InputFieldKind::AttributeValue
+                        ) {
+                            field.text.clear();
+                        }
+                    }
+                    Err(error) => field.message = Some((error, true)),
+                }
+            }
+            Key::Escape => {
+                focus.field = None;
+                return;
+            }
+            Key::Backspace => {
+                field.text.pop();
+            }
+            _ => {
+                if let Some(text) = &event.text {
+                    field.text.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+}
+
+/// Введенный текст поля и все, что нужно модулю для его разбора.
+/// Каждый модуль разбирает свои поля сам и отправляет события через `events`.
+pub struct FieldInput<'a, 'w> {
+    pub kind: InputFieldKind,
+    pub text: &'a str,
+    pub context: Context<'a>,
+    pub coordinate_settings: &'a CoordinateSettings,
+    pub events: &'a mut UiEventWriters<'w>,
+}
+
+impl FieldInput<'_, '_> {
+    /// Значение выражения с переменными и единицами чертежа
+    pub fn evaluate(&self, text: &str) -> Result<f64, String> {
+        expression::evaluate(text, &self.context).map_err(|e| e.to_string())
+    }
+
+    /// Угол в текущих единицах с учетом направления отсчета; результат против часовой стрелки
+    pub fn direction_angle(&self, text: &str) -> Result<f64, String> {
+        let angle = parsing::parse_angle(text, &self.context)?;
+        Ok(
+            if self.coordinate_settings.angle_direction == AngleDirection::Clockwise {
+                -angle
+            } else {
+                angle
+            },
+        )
+    }
+
+    /// Длина в текущих единицах для сообщения под полем
+    pub fn length(&self, value: f64) -> String {
+        formatting::format_length(value, self.coordinate_settings)
+    }
+
+    /// Величина угла (радианы) в текущих единицах для сообщения под полем
+    pub fn angle(&self, value: f64) -> String {
+        formatting::format_angle(
+            value,
+            self.coordinate_settings.angle_unit,
+            self.coordinate_settings.precision,
+        )
+    }
+}
+
+/// Применение значения поля; возвращает текст сообщения или ошибку.
+/// Поля точки и калькулятора разбираются здесь, остальные — модулями, к которым они относятся.
+fn submit(
+    kind: InputFieldKind,
+    text: &str,
+    variables: &mut Variables,
+    coordinate_settings: &CoordinateSettings,
+    snap_state: &SnapState,
+    ucs_manager: &UcsManager,
+    point_events: &mut EventWriter<TypedPointEvent>,
+    events: &mut UiEventWriters,
+) -> Result<String, String> {
+    let mut input = FieldInput {
+        kind,
+        text,
+        context: Context::new(variables, coordinate_settings.angle_unit)
+            .with_linear_unit(coordinate_settings.linear_unit),
+        coordinate_settings,
+        events,
+    };
+    match kind {
+        InputFieldKind::Point => {
+            let frame = ucs_manager.frame(snap_state.last_point);
+            let point = parsing::parse_point(
+                text,
+                &input.context,
+                snap_state.last_point,
+                &frame,
+                coordinate_settings,
+            )?;
+            point_events.write(TypedPointEvent { point });
+            let local = frame.local_cartesian(point);
+            Ok(format!(
+                "= {}",
+                formatting::format_cartesian(local, coordinate_settings)
+            ))
+        }
+        InputFieldKind::Calculator => match expression::execute(
+            text,
+            variables,
+            coordinate_settings.angle_unit,
+            coordinate_settings.linear_unit,
+        ) {
+            Ok(Statement::Value(value)) => Ok(format!("= {}", value)),
+            Ok(Statement::Assignment(name, value)) => Ok(format!("{} = {}", name, value)),
+            Err(error) => Err(error.to_string()),
+        },
+        InputFieldKind::GridStep => grid::submit_field(&mut input),
+        InputFieldKind::SnapStep => snapping::submit_field(&mut input),
+        InputFieldKind::UcsName => ucs::submit_field(&mut input, ucs_manager),
+        InputFieldKind::TransformMatrix | InputFieldKind::TransformSteps => {
+            transform::submit_field(&mut input)
+        }
+        InputFieldKind::GeoOrigin | InputFieldKind::GeoRotation => {
+            coordinate_systems::submit_georeference_field(&mut input)
+        }
+        InputFieldKind::DimensionSizes
+        | InputFieldKind::DimensionText
+        | InputFieldKind::DimensionTolerance
+        | InputFieldKind::DimensionAngularTolerance => dimension_style::submit_field(&mut input),
+        InputFieldKind::LeaderText => leaders::submit_field(&mut input),
+        InputFieldKind::LayerName => layers::submit_field(&mut input),
+        InputFieldKind::LinetypeScale | InputFieldKind::LinetypeDefinition => {
+            linetypes::submit_field(&mut input)
+        }
+        InputFieldKind::BlockName | InputFieldKind::BlockInsertion => {
+            blocks::submit_field(&mut input)
+        }
+        InputFieldKind::AttributeDefinition
+        | InputFieldKind::AttributeValue
+        | InputFieldKind::AttributeCsvPath => attributes::submit_field(&mut input),
+        InputFieldKind::GroupName => groups::submit_field(&mut input),
+        InputFieldKind::TrackingAngles => tracking::submit_field(&mut input),
+    }
+}
+
+/// Обновление текста полей, подсветки фокуса и сообщений
+pub fn update_input_fields_system(
+    focus: Res<TextInputFocus>,
+    mut field_query: Query<(Entity, Ref<InputField>, &mut BorderColor)>,
+    mut text_query: Query<(&InputFieldText, &mut Text, &mut TextColor), Without<InputFieldMessage>>,
+    mut message_query: Query<
+        (&InputFieldMessage, &mut Text, &mut TextColor),
+        Without<InputFieldText>,
+    >,
+) {
+    for (entity, field, mut border) in field_query.iter_mut() {
+        if !field.is_changed() && !focus.is_changed() {
+            continue;
+        }
+        let focused = focus.field == Some(entity);
+        *border = if focused {
+            BorderColor(Color::srgb(0.4, 0.7, 1.0))
+        } else {
+            BorderColor(Color::srgb(0.3, 0.3, 0.3))
+        };
+
+        for (owner, mut text, mut color) in text_query.iter_mut() {
+            if owner.field != entity {
+                continue;
+            }
+            if field.text.is_empty() && !focused {
+                **text = field.kind.placeholder().to_string();
+                *color = TextColor(Color::srgb(0.45, 0.45, 0.45));
+            } else {
+                **text = format!("{}{}", field.text, if focused { "|" } else { "" });
+                *color = TextColor(Color::srgb(0.9, 0.9, 0.9));
+            }
+        }
+
+        for (owner, mut text, mut color) in message_query.iter_mut() {
+            if owner.field != entity {
+                continue;
+            }
+            match &field.message {
+                Some((message, is_error)) => {
+                    **text = message.clone();
+                    *color = if *is_error {
+                        TextColor(Color::srgb(1.0, 0.4, 0.4))
+                    } else {
+                        TextColor(Color::srgb(0.6, 0.8, 0.6))
+                    };
+                }
+                None => **text = String::new(),
+            }
+        }
+    }
+}
@@ -0,0 +1,853 @@
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{events::KeyBindingEvent, input_field::TextInputFocus};
+
+/// Файл с пользовательскими назначениями клавиш (рядом с исполняемым файлом)
+pub const KEYBINDINGS_FILE: &str = "keybindings.cfg";
+
+/// Путь к файлу рядом с исполняемым файлом, а не в текущем каталоге,
+/// из которого запущена программа
+pub fn beside_executable(file_name: &str) -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(file_name)
+}
+
+pub fn keybindings_path() -> PathBuf {
+    beside_executable(KEYBINDINGS_FILE)
+}
+
+/// Действие, которое можно назначить на клавишу или кнопку мыши
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputAction {
+    // Камера
+    ToggleOrbitCamera,
+    ResetOrbitCamera,
+    OrbitRotate,
+    ToggleTopView,
+    ResetCamera,
+    PanCamera,
+    CenterCamera,
+    ShowCursorCoordinates,
+    // Рендеринг
+    ToggleWireframe,
+    ToggleLighting,
+    ToggleShadows,
+    ToggleGrid,
+    ShowRenderInfo,
+    SaveRenderSettings,
+    // Линии
+    ToggleLineDrawing,
+    PickPoint,
+    ClearLines,
+    LineInfo,
+    // Координаты
+    ToggleCoordinateSystem,
+    ToggleAngleUnit,
+    // Привязки и отслеживание
+    ToggleOrtho,
+    ToggleGridSnap,
+    TogglePolarTracking,
+    ToggleObjectTracking,
+    // Команды
+    Cancel,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 25] = [
+        InputAction::ToggleOrbitCamera,
+        InputAction::ResetOrbitCamera,
+        InputAction::OrbitRotate,
+        InputAction::ToggleTopView,
+        InputAction::ResetCamera,
+        InputAction::PanCamera,
+        InputAction::CenterCamera,
+        InputAction::ShowCursorCoordinates,
+        InputAction::ToggleWireframe,
+        InputAction::ToggleLighting,
+        InputAction::ToggleShadows,
+        InputAction::ToggleGrid,
+        InputAction::ShowRenderInfo,
+        InputAction::SaveRenderSettings,
+        InputAction::ToggleLineDrawing,
+        InputAction::PickPoint,
+        InputAction::ClearLines,
+        InputAction::LineInfo,
+        InputAction::ToggleCoordinateSystem,
+        InputAction::ToggleAngleUnit,
+        InputAction::ToggleOrtho,
+        InputAction::ToggleGridSnap,
+        InputAction::TogglePolarTracking,
+        InputAction::ToggleObjectTracking,
+        InputAction::Cancel,
+    ];
+
+    /// Имя действия в файле настроек
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            InputAction::ToggleOrbitCamera => "toggle_orbit_camera",
+            InputAction::ResetOrbitCamera => "reset_orbit_camera",
+            InputAction::OrbitRotate => "orbit_rotate",
+            InputAction::ToggleTopView => "toggle_top_view",
+            InputAction::ResetCamera => "reset_camera",
+            InputAction::PanCamera => "pan_camera",
+            InputAction::CenterCamera => "center_camera",
+            InputAction::ShowCursorCoordinates => "show_cursor_coordinates",
+            InputAction::ToggleWireframe => "toggle_wireframe",
+            InputAction::ToggleLighting => "toggle_lighting",
+            InputAction::ToggleShadows => "toggle_shadows",
+            InputAction::ToggleGrid => "toggle_grid",
+            InputAction::ShowRenderInfo => "show_render_info",
+            InputAction::SaveRenderSettings => "save_render_settings",
+            InputAction::ToggleLineDrawing => "toggle_line_drawing",
+            InputAction::PickPoint => "pick_point",
+            InputAction::ClearLines => "clear_lines",
+            InputAction::LineInfo => "line_info",
+            InputAction::ToggleCoordinateSystem => "toggle_coordinate_system",
+            InputAction::ToggleAngleUnit => "toggle_angle_unit",
+            InputAction::ToggleOrtho => "toggle_ortho",
+            InputAction::ToggleGridSnap => "toggle_grid_snap",
+            InputAction::TogglePolarTracking => "toggle_polar_tracking",
+            InputAction::ToggleObjectTracking => "toggle_object_tracking",
+            InputAction::Cancel => "cancel",
+        }
+    }
+
+    /// Название действия в интерфейсе
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputAction::ToggleOrbitCamera => "Орбитальная камера",
+            InputAction::ResetOrbitCamera => "Сброс орбиты",
+            InputAction::OrbitRotate => "Вращение орбиты",
+            InputAction::ToggleTopView => "Вид сверху",
+            InputAction::ResetCamera => "Сброс масштаба",
+            InputAction::PanCamera => "Панорамирование",
+            InputAction::CenterCamera => "Показать все",
+            InputAction::ShowCursorCoordinates => "Координаты курсора",
+            InputAction::ToggleWireframe => "Каркас",
+            InputAction::ToggleLighting => "Освещение",
+            InputAction::ToggleShadows => "Тени",
+            InputAction::ToggleGrid => "Сетка",
+            InputAction::ShowRenderInfo => "Инфо рендеринга",
+            InputAction::SaveRenderSettings => "Сохранить рендеринг",
+            InputAction::ToggleLineDrawing => "Рисование линий",
+            InputAction::PickPoint => "Указать точку",
+            InputAction::ClearLines => "Удалить все линии",
+            InputAction::LineInfo => "Инфо о линиях",
+            InputAction::ToggleCoordinateSystem => "Система координат",
+            InputAction::ToggleAngleUnit => "Единицы углов",
+            InputAction::ToggleOrtho => "ОРТО",
+            InputAction::ToggleGridSnap => "Привязка к сетке",
+            InputAction::TogglePolarTracking => "Полярное отслеживание",
+            InputAction::ToggleObjectTracking => "Объектное отслеживание",
+            InputAction::Cancel => "Отмена команды",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.config_name() == name)
+    }
+}
+
+/// Действия, подсказки для которых выводятся в строке состояния
+const STATUS_BAR_ACTIONS: [(InputAction, &str); 8] = [
+    (InputAction::ToggleLineDrawing, "Линии"),
+    (InputAction::ToggleCoordinateSystem, "Координаты"),
+    (InputAction::ToggleAngleUnit, "Углы"),
+    (InputAction::ToggleOrbitCamera, "Камера"),
+    (InputAction::ToggleOrtho, "Орто"),
+    (InputAction::ToggleGridSnap, "Сетка"),
+    (InputAction::TogglePolarTracking, "Поляр."),
+    (InputAction::ToggleObjectTracking, "Отсл."),
+];
+
+/// Клавиша клавиатуры или кнопка мыши
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputKey {
+    Keyboard(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// Имена клавиш в файле настроек
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Tab", KeyCode::Tab),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Minus", KeyCode::Minus),
+    ("Equal", KeyCode::Equal),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Quote", KeyCode::Quote),
+    ("BracketLeft", KeyCode::BracketLeft),
+    ("BracketRight", KeyCode::BracketRight),
+    ("Backslash", KeyCode::Backslash),
+    ("Backquote", KeyCode::Backquote),
+];
+
+const MOUSE_NAMES: &[(&str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+/// Клавиши-модификаторы не назначаются сами по себе
+const MODIFIER_KEYS: [KeyCode; 6] = [
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+];
+
+impl fmt::Display for InputKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InputKey::Keyboard(code) => KEY_NAMES
+                .iter()
+                .find(|(_, key)| key == code)
+                .map(|(name, _)| *name),
+            InputKey::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(_, key)| key == button)
+                .map(|(name, _)| *name),
+        };
+        match name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl FromStr for InputKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, code)) = KEY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(InputKey::Keyboard(*code));
+        }
+        if let Some((_, button)) = MOUSE_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(InputKey::Mouse(*button));
+        }
+        Err(format!("неизвестная клавиша '{}'", s))
+    }
+}
+
+/// Назначение: клавиша или кнопка мыши с модификаторами (Ctrl+Shift+S)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Binding {
+    pub key: InputKey,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Binding {
+    pub const fn key(code: KeyCode) -> Self {
+        Self {
+            key: InputKey::Keyboard(code),
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn mouse(button: MouseButton) -> Self {
+        Self {
+            key: InputKey::Mouse(button),
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key, modifiers) = parts.split_last().ok_or("пустое назначение")?;
+
+        let mut binding = Binding {
+            key: key.parse()?,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        for modifier in modifiers {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return Err(format!("неизвестный модификатор '{}'", modifier)),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+/// Реестр назначений: действие -> список клавиш
+#[derive(Resource, Clone)]
+pub struct KeyBindings {
+    bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use InputAction::*;
+
+        let defaults = [
+            (ToggleOrbitCamera, Some(Binding::key(KeyCode::KeyC))),
+            (ResetOrbitCamera, Some(Binding::key(KeyCode::KeyR))),
+            // Без назначения орбита вращается любым движением мыши
+            (OrbitRotate, None),
+            (ToggleTopView, Some(Binding::key(KeyCode::KeyV))),
+            (ResetCamera, Some(Binding::key(KeyCode::Home))),
+            (PanCamera, Some(Binding::mouse(MouseButton::Right))),
+            (CenterCamera, Some(Binding::key(KeyCode::KeyF))),
+            (ShowCursorCoordinates, Some(Binding::key(KeyCode::Tab))),
+            (ToggleWireframe, Some(Binding::key(KeyCode::F1))),
+            (ToggleLighting, Some(Binding::key(KeyCode::F2))),
+            (ToggleShadows, Some(Binding::key(KeyCode::F3))),
+            (ToggleGrid, Some(Binding::key(KeyCode::F4))),
+            (ShowRenderInfo, Some(Binding::key(KeyCode::F5))),
+            (SaveRenderSettings, Some(Binding::key(KeyCode::F12))),
+            (ToggleLineDrawing, Some(Binding::key(KeyCode::KeyL))),
+            (PickPoint, Some(Binding::mouse(MouseButton::Left))),
+            (ClearLines, Some(Binding::key(KeyCode::Delete))),
+            (LineInfo, Some(Binding::key(KeyCode::KeyI))),
+            (ToggleCoordinateSystem, Some(Binding::key(KeyCode::KeyX))),
+            (ToggleAngleUnit, Some(Binding::key(KeyCode::KeyU))),
+            (ToggleOrtho, Some(Binding::key(KeyCode::F8))),
+            (ToggleGridSnap, Some(Binding::key(KeyCode::F9))),
+            (TogglePolarTracking, Some(Binding::key(KeyCode::F10))),
+            (ToggleObjectTracking, Some(Binding::key(KeyCode::F11))),
+            (Cancel, Some(Binding::key(KeyCode::Escape))),
+        ];
+
+        Self {
+            bindings: defaults
+                .into_iter()
+                .map(|(action, binding)| (action, binding.into_iter().collect()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Заменяет все назначения действия одним
+    pub fn set(&mut self, action: InputAction, binding: Binding) {
+        self.bindings.insert(action, vec![binding]);
+    }
+
+    /// Назначения действия в виде "Ctrl+S, F2" или "—"
+    pub fn label(&self, action: InputAction) -> String {
+        let bindings = self.get(action);
+        if bindings.is_empty() {
+            return "—".to_string();
+        }
+        bindings
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Действия, которым назначено данное сочетание
+    pub fn actions_for(&self, binding: Binding) -> Vec<InputAction> {
+        InputAction::ALL
+            .into_iter()
+            .filter(|action| self.get(*action).contains(&binding))
+            .collect()
+    }
+
+    /// Сочетания, назначенные сразу нескольким действиям
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<InputAction>)> {
+        let mut conflicts: Vec<(Binding, Vec<InputAction>)> = Vec::new();
+        for action in InputAction::ALL {
+            for binding in self.get(action) {
+                if conflicts.iter().any(|(known, _)| known == binding) {
+                    continue;
+                }
+                let actions = self.actions_for(*binding);
+                if actions.len() > 1 {
+                    conflicts.push((*binding, actions));
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn has_conflict(&self, action: InputAction) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| self.actions_for(*binding).len() > 1)
+    }
+
+    /// Разбор файла настроек; действия, не упомянутые в файле, сохраняют
+    /// назначения по умолчанию. Возвращает также список ошибок по строкам.
+    ///
+    /// Формат: `действие = клавиша[, клавиша]`, пустое значение снимает
+    /// назначение, строки с `#` - комментарии.
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut bindings = Self::default();
+        let mut errors = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                errors.push(format!(
+                    "строка {}: ожидается 'действие = клавиша'",
+                    number + 1
+                ));
+                continue;
+            };
+            let Some(action) = InputAction::from_config_name(name.trim()) else {
+                errors.push(format!(
+                    "строка {}: неизвестное действие '{}'",
+                    number + 1,
+                    name.trim()
+                ));
+                continue;
+            };
+
+            let parsed: Result<Vec<Binding>, String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::parse)
+                .collect();
+            match parsed {
+                Ok(list) => {
+                    bindings.bindings.insert(action, list);
+                }
+                Err(error) => errors.push(format!("строка {}: {}", number + 1, error)),
+            }
+        }
+
+        (bindings, errors)
+    }
+
+    /// Текст файла настроек
+    pub fn to_config(&self) -> String {
+        let mut text = String::from(
+            "# Назначение клавиш: действие = клавиша[, клавиша]\n\
+             # Модификаторы: Ctrl+, Shift+, Alt+; мышь: MouseLeft, MouseRight, MouseMiddle\n",
+        );
+        for action in InputAction::ALL {
+            let value = self
+                .get(action)
+                .iter()
+                .map(Binding::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            text.push_str(&format!("{} = {}\n", action.config_name(), value));
+        }
+        text
+    }
+
+    /// Загрузка из файла; при отсутствии файла - назначения по умолчанию
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = fs::read_to_string(path) else {
+            info!(
+                "Keybindings file '{}' not found, using defaults",
+                path.display()
+            );
+            return Self::default();
+        };
+
+        let (bindings, errors) = Self::parse(&text);
+        for error in errors {
+            warn!("{}: {}", path.display(), error);
+        }
+        for (binding, actions) in bindings.conflicts() {
+            warn!(
+                "Key binding conflict: {} is assigned to {:?}",
+                binding, actions
+            );
+        }
+        bindings
+    }
+
+    pub fn save(&self, path: &Path) {
+        match fs::write(path, self.to_config()) {
+            Ok(()) => info!("Keybindings saved to '{}'", path.display()),
+            Err(error) => warn!(
+                "Failed to save keybindings to '{}': {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+
+    /// Подсказка горячих клавиш для строки состояния
+    pub fn status_bar_hint(&self) -> String {
+        STATUS_BAR_ACTIONS
+            .iter()
+            .filter(|(action, _)| !self.get(*action).is_empty())
+            .map(|(action, name)| format!("{}: {}", self.label(*action), name))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Состояние редактора назначений в панели настроек
+#[derive(Resource, Default)]
+pub struct KeyBindingEditor {
+    /// Действие, ожидающее нажатия новой клавиши
+    pub waiting: Option<InputAction>,
+    pub list_open: bool,
+    pub message: Option<(String, bool)>, // (текст, это ошибка)
+    /// Клик по кнопке, начавший переназначение, не должен стать назначением
+    skip_frame: bool,
+}
+
+/// Доступ к действиям вместо прямой проверки клавиш
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    bindings: Res<'w, KeyBindings>,
+    focus: Res<'w, TextInputFocus>,
+    editor: Res<'w, KeyBindingEditor>,
+}
+
+impl ActionInput<'_> {
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.bindings.get(action).iter().any(|binding| {
+            self.is_active(binding)
+                && match binding.key {
+                    InputKey::Keyboard(code) => self.keyboard.just_pressed(code),
+                    InputKey::Mouse(button) => self.mouse.just_pressed(button),
+                }
+        })
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.bindings.get(action).iter().any(|binding| {
+            self.is_active(binding)
+                && match binding.key {
+                    InputKey::Keyboard(code) => self.keyboard.pressed(code),
+                    InputKey::Mouse(button) => self.mouse.pressed(button),
+                }
+        })
+    }
+
+    pub fn is_bound(&self, action: InputAction) -> bool {
+        !self.bindings.get(action).is_empty()
+    }
+
+    /// Модификаторы совпадают точно, чтобы Ctrl+L не срабатывало как L.
+    /// Пока идет ввод текста, клавиатурные назначения отключены, а во время
+    /// переназначения - все.
+    fn is_active(&self, binding: &Binding) -> bool {
+        if self.editor.waiting.is_some() {
+            return false;
+        }
+        if matches!(binding.key, InputKey::Keyboard(_)) && self.focus.field.is_some() {
+            return false;
+        }
+        let (ctrl, shift, alt) = modifiers(&self.keyboard);
+        binding.ctrl == ctrl && binding.shift == shift && binding.alt == alt
+    }
+}
+
+fn modifiers(keyboard: &ButtonInput<KeyCode>) -> (bool, bool, bool) {
+    (
+        keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+        keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+    )
+}
+
+/// Система обработки событий редактора назначений
+pub fn handle_keybinding_events(
+    mut keybinding_events: EventReader<KeyBindingEvent>,
+    mut bindings: ResMut<KeyBindings>,
+    mut editor: ResMut<KeyBindingEditor>,
+) {
+    for event in keybinding_events.read() {
+        match event {
+            KeyBindingEvent::StartRebind(action) => {
+                editor.waiting = Some(*action);
+                editor.skip_frame = true;
+                editor.message = Some((
+                    format!("{}: нажмите клавишу (Esc - отмена)", action.label()),
+                    false,
+                ));
+            }
+            KeyBindingEvent::ResetDefaults => {
+                *bindings = KeyBindings::default();
+                bindings.save(&keybindings_path());
+                editor.waiting = None;
+                editor.message = Some(("Восстановлены назначения по умолчанию".to_string(), false));
+            }
+            KeyBindingEvent::ToggleList => {
+                editor.list_open = !editor.list_open;
+            }
+        }
+    }
+}
+
+/// Захват новой клавиши для действия, ожидающего переназначения.
+/// Нажатие поглощается, чтобы в этом же кадре не сработало само действие.
+pub fn capture_rebind_system(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    mut bindings: ResMut<KeyBindings>,
+    mut editor: ResMut<KeyBindingEditor>,
+) {
+    let Some(action) = editor.waiting else {
+        return;
+    };
+    if editor.skip_frame {
+        editor.skip_frame = false;
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::Escape) {
+        keyboard.clear_just_pressed(KeyCode::Escape);
+        editor.waiting = None;
+        editor.message = Some(("Переназначение отменено".to_string(), false));
+        return;
+    }
+
+    let pressed_key = keyboard
+        .get_just_pressed()
+        .find(|code| !MODIFIER_KEYS.contains(code))
+        .copied();
+    let pressed_button = mouse.get_just_pressed().next().copied();
+
+    let key = match (pressed_key, pressed_button) {
+        (Some(code), _) => {
+            keyboard.clear_just_pressed(code);
+            InputKey::Keyboard(code)
+        }
+        (None, Some(button)) => {
+            mouse.clear_just_pressed(button);
+            InputKey::Mouse(button)
+        }
+        (None, None) => return,
+    };
+    if let InputKey::Keyboard(code) = key {
+        if !KEY_NAMES.iter().any(|(_, known)| *known == code) {
+            editor.message = Some((format!("Клавиша {:?} не поддерживается", code), true));
+            return;
+        }
+    }
+
+    let (ctrl, shift, alt) = modifiers(&keyboard);
+    let binding = Binding {
+        key,
+        ctrl,
+        shift,
+        alt,
+    };
+    bindings.set(action, binding);
+    bindings.save(&keybindings_path());
+    editor.waiting = None;
+
+    let others: Vec<&str> = bindings
+        .actions_for(binding)
+        .into_iter()
+        .filter(|other| *other != action)
+        .map(|other| other.label())
+        .collect();
+    editor.message = if others.is_empty() {
+        Some((format!("{}: {}", action.label(), binding), false))
+    } else {
+        Some((
+            format!(
+                "Конфликт: {} уже назначено ({})",
+                binding,
+                others.join(", ")
+            ),
+            true,
+        ))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trip_keeps_bindings() {
+        let mut bindings = KeyBindings::default();
+        bindings.set(
+            InputAction::ToggleGrid,
+            "Ctrl+Shift+G".parse::<Binding>().unwrap(),
+        );
+        bindings.set(
+            InputAction::OrbitRotate,
+            Binding::mouse(MouseButton::Middle),
+        );
+        bindings.bindings.insert(InputAction::LineInfo, Vec::new());
+
+        let (parsed, errors) = KeyBindings::parse(&bindings.to_config());
+        assert!(errors.is_empty(), "{:?}", errors);
+        for action in InputAction::ALL {
+            assert_eq!(parsed.get(action), bindings.get(action), "{:?}", action);
+        }
+        assert_eq!(parsed.label(InputAction::ToggleGrid), "Ctrl+Shift+G");
+        assert_eq!(parsed.label(InputAction::LineInfo), "—");
+    }
+
+    #[test]
+    fn unknown_keys_and_actions_are_reported() {
+        let text = "\
+            # комментарий\n\
+            toggle_grid = F7\n\
+            toggle_lasers = F6\n\
+            toggle_ortho = Hyper+F8\n\
+            toggle_shadows = NoSuchKey\n\
+            cancel\n";
+        let (bindings, errors) = KeyBindings::parse(text);
+
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors[0].starts_with("строка 3") && errors[0].contains("toggle_lasers"));
+        assert!(errors[1].starts_with("строка 4") && errors[1].contains("Hyper"));
+        assert!(errors[2].starts_with("строка 5") && errors[2].contains("NoSuchKey"));
+        assert!(errors[3].starts_with("строка 6"));
+
+        // Ошибочные строки не трогают назначения по умолчанию
+        let defaults = KeyBindings::default();
+        assert_eq!(
+            bindings.get(InputAction::ToggleGrid),
+            [Binding::key(KeyCode::F7)]
+        );
+        for action in [InputAction::ToggleOrtho, InputAction::ToggleShadows] {
+            assert_eq!(bindings.get(action), defaults.get(action));
+        }
+    }
+
+    #[test]
+    fn saved_bindings_are_loaded_back() {
+        let path = env::temp_dir().join(format!("keybindings-{}.cfg", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        // Нет файла - назначения по умолчанию
+        let defaults = KeyBindings::load(&path);
+        for action in InputAction::ALL {
+            assert_eq!(
+                defaults.get(action),
+                KeyBindings::default().get(action),
+                "{:?}",
+                action
+            );
+        }
+
+        let mut bindings = KeyBindings::default();
+        bindings.set(
+            InputAction::ToggleGridSnap,
+            "Ctrl+G".parse::<Binding>().unwrap(),
+        );
+        bindings.save(&path);
+        let loaded = KeyBindings::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.label(InputAction::ToggleGridSnap), "Ctrl+G");
+    }
+}
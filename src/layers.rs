@@ -0,0 +1,338 @@
+use bevy::prelude::*;
+
+use crate::{
+    attributes::AttributeDefinition,
+    blocks::BlockReference,
+    dimensions::Dimension,
+    events::LayerEvent,
+    input_field::FieldInput,
+    leaders::Multileader,
+    line_drawing::{DrawableLine, LineEndpoint},
+    linetypes::{Linetypes, CONTINUOUS},
+    selection::Selection,
+};
+
+/// Палитра цветов слоев: название и цвет
+pub const PALETTE: [(&str, Color); 7] = [
+    ("красный", Color::srgb(1.0, 0.2, 0.2)),
+    ("желтый", Color::srgb(1.0, 0.9, 0.2)),
+    ("зеленый", Color::srgb(0.2, 0.9, 0.2)),
+    ("голубой", Color::srgb(0.2, 0.9, 0.9)),
+    ("синий", Color::srgb(0.3, 0.4, 1.0)),
+    ("пурпурный", Color::srgb(0.9, 0.3, 0.9)),
+    ("белый", Color::srgb(0.95, 0.95, 0.95)),
+];
+
+/// Стандартный ряд толщин линий, мм
+pub const LINEWEIGHTS: [f32; 9] = [0.13, 0.18, 0.25, 0.35, 0.5, 0.7, 1.0, 1.4, 2.0];
+
+/// Толщина, которой соответствует `LineSettings::line_thickness`
+pub const DEFAULT_LINEWEIGHT: f32 = 0.25;
+
+/// Ступени прозрачности, доли от 0 (непрозрачно) до 1
+pub const TRANSPARENCIES: [f32; 4] = [0.0, 0.25, 0.5, 0.75];
+
+/// Слой: общие свойства отображения и доступа для объектов на нем
+#[derive(Clone, Debug)]
+pub struct Layer {
+    pub name: String,
+    /// Индекс в `PALETTE`
+    pub color: usize,
+    pub linetype: String,
+    /// Толщина линий, мм
+    pub lineweight: f32,
+    /// Прозрачность, доля из `TRANSPARENCIES`
+    pub transparency: f32,
+    /// Выключенный слой не виден, но может быть текущим
+    pub on: bool,
+    /// Замороженный слой не виден и не может быть текущим
+    pub frozen: bool,
+    /// Объекты заблокированного слоя видны и дают привязки, но не выделяются и не правятся
+    pub locked: bool,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            color: 0,
+            linetype: CONTINUOUS.to_string(),
+            lineweight: DEFAULT_LINEWEIGHT,
+            transparency: 0.0,
+            on: true,
+            frozen: false,
+            locked: false,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.on && !self.frozen
+    }
+
+    /// Объекты слоя можно выделять и изменять
+    pub fn is_editable(&self) -> bool {
+        self.is_visible() && !self.locked
+    }
+}
+
+/// Следующая толщина стандартного ряда в сторону `step` (+1 или -1)
+pub fn step_lineweight(lineweight: f32, step: i32) -> f32 {
+    let index = LINEWEIGHTS
+        .iter()
+        .position(|weight| *weight >= lineweight)
+        .unwrap_or(LINEWEIGHTS.len() - 1);
+    let index = (index as i32 + step).clamp(0, LINEWEIGHTS.len() as i32 - 1);
+    LINEWEIGHTS[index as usize]
+}
+
+/// Следующая ступень прозрачности
+pub fn next_transparency(transparency: f32) -> f32 {
+    TRANSPARENCIES
+        .iter()
+        .copied()
+        .find(|step| *step > transparency + 1e-3)
+        .unwrap_or(0.0)
+}
+
+/// Слой, на котором лежит объект (индекс в `Layers`)
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct LayerId(pub usize);
+
+/// Список слоев, текущий слой для новых объектов и слой, открытый в панели.
+/// Слои не удаляются, поэтому индексы в `LayerId` остаются верными.
+#[derive(Resource)]
+pub struct Layers {
+    pub layers: Vec<Layer>,
+    pub current: usize,
+    /// Слой, свойства которого меняют кнопки панели
+    pub edited: usize,
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self {
+            layers: vec![Layer::new("0")],
+            current: 0,
+            edited: 0,
+        }
+    }
+}
+
+impl Layers {
+    /// Слой объекта; неизвестный индекс дает слой «0»
+    pub fn get(&self, id: LayerId) -> &Layer {
+        self.layers.get(id.0).unwrap_or(&self.layers[0])
+    }
+
+    pub fn is_visible(&self, id: LayerId) -> bool {
+        self.get(id).is_visible()
+    }
+
+    pub fn is_editable(&self, id: LayerId) -> bool {
+        self.get(id).is_editable()
+    }
+
+    pub fn edited_mut(&mut self) -> &mut Layer {
+        let index = self.edited;
+        &mut self.layers[index]
+    }
+
+    /// Новый слой со свободным именем «Слой N»; становится текущим
+    pub fn add(&mut self) -> usize {
+        let name = (1..)
+            .map(|number| format!("Слой {}", number))
+            .find(|name| self.layers.iter().all(|layer| layer.name != *name))
+            .unwrap_or_default();
+        self.layers.push(Layer::new(name));
+        self.current = self.layers.len() - 1;
+        self.edited = self.current;
+        self.current
+    }
+}
+
+/// Система обработки событий слоев
+pub fn handle_layer_events(
+    mut commands: Commands,
+    mut layer_events: EventReader<LayerEvent>,
+    mut layers: ResMut<Layers>,
+    linetypes: Res<Linetypes>,
+    selection: Res<Selection>,
+) {
+    for event in layer_events.read() {
+        match event {
+            LayerEvent::New => {
+                let index = layers.add();
+                info!("Layer created: {}", layers.layers[index].name);
+            }
+            LayerEvent::EditNext => layers.edited = (layers.edited + 1) % layers.layers.len(),
+            LayerEvent::MakeCurrent => {
+                if layers.edited_mut().frozen {
+                    warn!("Frozen layer can't be current");
+                } else {
+                    layers.current = layers.edited;
+                }
+            }
+            LayerEvent::CycleColor => {
+                let layer = layers.edited_mut();
+                layer.color = (layer.color + 1) % PALETTE.len();
+            }
+            LayerEvent::CycleLinetype => {
+                let layer = layers.edited_mut();
+                layer.linetype = linetypes.next(&layer.linetype);
+            }
+            LayerEvent::Thicker => {
+                let layer = layers.edited_mut();
+                layer.lineweight = step_lineweight(layer.lineweight, 1);
+            }
+            LayerEvent::Thinner => {
+                let layer = layers.edited_mut();
+                layer.lineweight = step_lineweight(layer.lineweight, -1);
+            }
+            LayerEvent::CycleTransparency => {
+                let layer = layers.edited_mut();
+                layer.transparency = next_transparency(layer.transparency);
+            }
+            LayerEvent::ToggleOn => {
+                let layer = layers.edited_mut();
+                layer.on = !layer.on;
+            }
+            LayerEvent::ToggleFrozen => {
+                if layers.edited == layers.current {
+                    warn!("Current layer can't be frozen");
+                    continue;
+                }
+                let layer = layers.edited_mut();
+                layer.frozen = !layer.frozen;
+            }
+            LayerEvent::ToggleLocked => {
+                let layer = layers.edited_mut();
+                layer.locked = !layer.locked;
+            }
+            LayerEvent::Rename(name) => {
+                let edited = layers.edited;
+                if layers
+                    .layers
+                    .iter()
+                    .enumerate()
+                    .any(|(index, layer)| index != edited && layer.name == *name)
+                {
+                    warn!("Layer {} already exists", name);
+                    continue;
+                }
+                layers.edited_mut().name = name.clone();
+            }
+            LayerEvent::MoveSelection => {
+                for entity in &selection.entities {
+                    commands.entity(*entity).insert(LayerId(layers.current));
+                }
+                info!(
+                    "Moved {} objects to layer {}",
+                    selection.entities.len(),
+                    layers.layers[layers.current].name
+                );
+            }
+        }
+    }
+}
+
+/// Поле имени открытого слоя
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let name = input.text.trim();
+    if name.is_empty() {
+        return Err("имя слоя не может быть пустым".to_string());
+    }
+    input
+        .events
+        .layer
+        .write(LayerEvent::Rename(name.to_string()));
+    Ok(format!("слой: {}", name))
+}
+
+/// Новые объекты ложатся на текущий слой
+pub fn assign_current_layer_system(
+    mut commands: Commands,
+    layers: Res<Layers>,
+    new_query: Query<
+        Entity,
+        (
+            Without<LayerId>,
+            Or<(
+                With<DrawableLine>,
+                With<LineEndpoint>,
+                With<Dimension>,
+                With<Multileader>,
+                With<BlockReference>,
+                With<AttributeDefinition>,
+            )>,
+        ),
+    >,
+) {
+    for entity in new_query.iter() {
+        commands.entity(entity).insert(LayerId(layers.current));
+    }
+}
+
+/// Видимость отрезков и маркеров концов по их слоям
+pub fn apply_layers_system(
+    layers: Res<Layers>,
+    mut query: Query<(Ref<LayerId>, &mut Visibility), Or<(With<DrawableLine>, With<LineEndpoint>)>>,
+) {
+    for (layer_id, mut visibility) in query.iter_mut() {
+        if !layers.is_changed() && !layer_id.is_changed() {
+            continue;
+        }
+        visibility.set_if_neq(if layers.is_visible(*layer_id) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_states_control_visibility_and_editing() {
+        let mut layers = Layers::default();
+        let first = layers.add();
+        assert_eq!(layers.layers[first].name, "Слой 1");
+        assert_eq!((layers.current, layers.edited), (first, first));
+
+        // Занятое имя пропускается
+        layers.layers[first].name = "Слой 2".to_string();
+        let second = layers.add();
+        assert_eq!(layers.layers[second].name, "Слой 1");
+
+        let layer = layers.edited_mut();
+        assert!(layer.is_visible() && layer.is_editable());
+        layer.locked = true;
+        assert!(layer.is_visible() && !layer.is_editable());
+        layer.locked = false;
+        layer.on = false;
+        assert!(!layer.is_visible() && !layer.is_editable());
+        layer.on = true;
+        layer.frozen = true;
+        assert!(!layers.is_visible(LayerId(second)));
+        assert!(!layers.is_editable(LayerId(second)));
+
+        // Неизвестный индекс дает слой «0»
+        assert_eq!(layers.get(LayerId(99)).name, "0");
+        assert!(layers.is_editable(LayerId(99)));
+    }
+
+    #[test]
+    fn lineweight_and_transparency_steps_stay_in_range() {
+        assert_eq!(step_lineweight(DEFAULT_LINEWEIGHT, 1), 0.35);
+        assert_eq!(step_lineweight(DEFAULT_LINEWEIGHT, -1), 0.18);
+        assert_eq!(step_lineweight(0.13, -1), 0.13);
+        assert_eq!(step_lineweight(2.0, 1), 2.0);
+        // Нестандартная толщина переходит на ряд
+        assert_eq!(step_lineweight(0.3, 0), 0.35);
+
+        assert_eq!(next_transparency(0.0), 0.25);
+        assert_eq!(next_transparency(0.5), 0.75);
+        assert_eq!(next_transparency(0.75), 0.0);
+    }
+}
@@ -0,0 +1,765 @@
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+use std::collections::HashSet;
+use std::f64::consts::TAU;
+
+use crate::{
+    attributes::BlockAttributes,
+    blocks::{spawn_reference, BlockReference, Blocks},
+    dimension_style::{DimensionStyle, DimensionStyleId, DimensionStyles},
+    dimensions::{self, lift, place_label, plan, DimensionLayout},
+    events::{LeaderEvent, TypedPointEvent},
+    font_resource::GlobalFont,
+    input_field::FieldInput,
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers},
+    render_origin::RenderOrigin,
+    selection::ActiveCommandsMut,
+    snapping::{candidates, SnapSettings, SnapState},
+};
+
+/// Вид содержимого новой выноски
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LeaderKind {
+    /// Текст над полкой
+    Text,
+    /// Номер позиции в кружке
+    Balloon,
+    /// Вставка текущего блока палитры
+    Block,
+}
+
+/// Содержимое выноски
+#[derive(Clone, PartialEq, Debug)]
+pub enum LeaderContent {
+    Text(String),
+    /// Номера позиций в кружках; после сбора выносок их несколько в ряд
+    Balloons(Vec<u32>),
+    /// Вставка блока, базовая точка которой совпадает с началом полки
+    Block {
+        block: usize,
+        scale: f64,
+        rotation: f64,
+    },
+}
+
+/// Мультивыноска: одна или несколько линий со стрелками, сходящихся
+/// к началу общей полки, и содержимое на полке
+#[derive(Component, Clone, Debug)]
+pub struct Multileader {
+    /// Ломаные от острия стрелки к полке (без точки начала полки)
+    pub leaders: Vec<Vec<DVec3>>,
+    /// Начало полки
+    pub landing: DVec3,
+    pub content: LeaderContent,
+}
+
+impl Multileader {
+    /// Направление полки в плане: в сторону от последнего излома первой линии
+    fn side(&self) -> f64 {
+        let from = self
+            .leaders
+            .first()
+            .and_then(|leader| leader.last())
+            .map_or(self.landing.x, |point| point.x);
+        if self.landing.x < from {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Линии, стрелки, полка, кружки и подписи содержимого
+    pub fn layout(&self, style: &DimensionStyle) -> (DimensionLayout, Vec<(String, DVec3)>) {
+        let height = self.landing.y;
+        let landing = plan(self.landing);
+        let side = DVec2::X * self.side();
+        let text_height = style.text_height();
+        let mut layout = DimensionLayout::default();
+
+        for leader in &self.leaders {
+            let points: Vec<DVec2> = leader.iter().copied().map(plan).chain([landing]).collect();
+            for segment in points.windows(2) {
+                layout
+                    .lines
+                    .push((lift(segment[0], height), lift(segment[1], height)));
+            }
+            if let Some(direction) = (points[0] - points[1]).try_normalize() {
+                layout.arrows.push((lift(points[0], height), direction));
+            }
+        }
+
+        let mut labels = Vec::new();
+        match &self.content {
+            LeaderContent::Text(text) => {
+                // Полка под текстом длиной примерно в строку
+                let length = text_height * (0.9 * text.chars().count() as f64 + 1.0);
+                let end = landing + side * length;
+                layout
+                    .lines
+                    .push((lift(landing, height), lift(end, height)));
+                let above = (landing + end) / 2.0 + DVec2::Y * style.text_gap();
+                labels.push((text.clone(), lift(above, height)));
+            }
+            LeaderContent::Balloons(numbers) => {
+                let radius = text_height;
+                let segments = 24;
+                for (index, number) in numbers.iter().enumerate() {
+                    let center = landing + side * radius * (1.0 + 2.0 * index as f64);
+                    let point = |i: usize| {
+                        let angle = i as f64 * TAU / segments as f64;
+                        lift(center + DVec2::from_angle(angle) * radius, height)
+                    };
+                    for i in 0..segments {
+                        layout.lines.push((point(i), point(i + 1)));
+                    }
+                    labels.push((number.to_string(), lift(center, height)));
+                }
+            }
+            // Блок рисует его вставка (`update_leader_blocks_system`)
+            LeaderContent::Block { .. } => {}
+        }
+        (layout, labels)
+    }
+
+    /// Вставка блока на полке выноски с блоком
+    pub fn block_reference(&self) -> Option<BlockReference> {
+        match self.content {
+            LeaderContent::Block {
+                block,
+                scale,
+                rotation,
+            } => Some(BlockReference {
+                block,
+                position: self.landing,
+                scale,
+                rotation,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Расстояние в плане от точки до ближайшей линии выноски
+    fn distance(&self, point: DVec3, style: &DimensionStyle) -> f64 {
+        let (layout, labels) = self.layout(style);
+        let lines = layout.lines.iter().map(|&(start, end)| {
+            let closest = candidates::closest_on_segment(point, start, end);
+            (plan(closest) - plan(point)).length()
+        });
+        let labels = labels
+            .iter()
+            .map(|(_, position)| (plan(*position) - plan(point)).length());
+        lines.chain(labels).fold(f64::INFINITY, f64::min)
+    }
+
+    /// Присоединение линий другой выноски: кружки встают в ряд, выноски того же
+    /// блока сходятся к одной вставке. Тексты и разные блоки не собираются.
+    pub fn collect(&mut self, other: &Multileader) -> bool {
+        match (&mut self.content, &other.content) {
+            (LeaderContent::Balloons(numbers), LeaderContent::Balloons(other_numbers)) => {
+                numbers.extend(other_numbers);
+            }
+            (LeaderContent::Block { block, .. }, LeaderContent::Block { block: other, .. })
+                if block == other => {}
+            _ => return false,
+        }
+        self.leaders.extend(other.leaders.iter().cloned());
+        true
+    }
+}
+
+/// Подпись содержимого выноски (текст или номер в кружке)
+#[derive(Component)]
+pub struct LeaderLabel {
+    pub leader: Entity,
+    pub index: usize,
+}
+
+/// Вставка блока на полке выноски. Ее ведет выноска, поэтому отдельно
+/// она не выделяется.
+#[derive(Component)]
+pub struct LeaderBlock {
+    pub leader: Entity,
+}
+
+/// Команда работы с выносками
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LeaderCommand {
+    /// Острие, изломы и начало полки новой выноски
+    Create(LeaderKind),
+    /// Выбор выноски, затем острия новых линий к ее полке
+    AddLeader,
+    /// Выбор выносок; полки выравниваются по первой
+    Align,
+    /// Выбор выносок с кружками или одним блоком; все собираются в первую
+    Collect,
+}
+
+impl LeaderCommand {
+    pub fn label(self) -> &'static str {
+        match self {
+            LeaderCommand::Create(LeaderKind::Text) => "Выноска с текстом",
+            LeaderCommand::Create(LeaderKind::Balloon) => "Выноска-позиция",
+            LeaderCommand::Create(LeaderKind::Block) => "Выноска с блоком",
+            LeaderCommand::AddLeader => "Добавить линию",
+            LeaderCommand::Align => "Выровнять выноски",
+            LeaderCommand::Collect => "Собрать позиции",
+        }
+    }
+}
+
+/// Указание точек и выносок текущей команды
+#[derive(Clone, Debug)]
+pub struct LeaderPick {
+    pub command: LeaderCommand,
+    pub points: Vec<DVec3>,
+    /// Выбранные выноски
+    pub leaders: Vec<Entity>,
+    /// Клик по кнопке, начавший указание, не должен стать точкой
+    armed: bool,
+}
+
+impl LeaderPick {
+    pub fn prompt(&self) -> String {
+        let what = match self.command {
+            LeaderCommand::Create(_) if self.points.is_empty() => {
+                "укажите острие стрелки".to_string()
+            }
+            LeaderCommand::Create(_) => {
+                "укажите излом или начало полки, затем «Завершить»".to_string()
+            }
+            LeaderCommand::AddLeader if self.leaders.is_empty() => "выберите выноску".to_string(),
+            LeaderCommand::AddLeader => "укажите острия новых линий".to_string(),
+            LeaderCommand::Align | LeaderCommand::Collect => format!(
+                "выбрано {}, выберите выноски и «Завершить»",
+                self.leaders.len()
+            ),
+        };
+        format!("{}: {}", self.command.label(), what)
+    }
+}
+
+/// Состояние команд выносок и текст для новых выносок
+#[derive(Resource)]
+pub struct LeaderTool {
+    pub pick: Option<LeaderPick>,
+    pub text: String,
+}
+
+impl Default for LeaderTool {
+    fn default() -> Self {
+        Self {
+            pick: None,
+            text: "Примечание".to_string(),
+        }
+    }
+}
+
+impl LeaderTool {
+    pub fn is_picking(&self) -> bool {
+        self.pick.is_some()
+    }
+}
+
+/// Выноска под курсором: ближайшая в пределах апертуры привязки
+fn leader_under_cursor<'a>(
+    point: DVec3,
+    leaders: impl Iterator<Item = (Entity, &'a Multileader, &'a DimensionStyleId)>,
+    styles: &DimensionStyles,
+    aperture: f64,
+) -> Option<Entity> {
+    leaders
+        .map(|(entity, leader, style)| (entity, leader.distance(point, styles.get(*style))))
+        .filter(|(_, distance)| *distance <= aperture)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+/// Создание выноски; подписи добавляет система подписей
+pub fn spawn_leader(
+    commands: &mut Commands,
+    leader: Multileader,
+    style: DimensionStyleId,
+) -> Entity {
+    commands
+        .spawn((leader, style, Name::new("Multileader")))
+        .id()
+}
+
+/// Система обработки событий выносок
+pub fn handle_leader_events(
+    mut commands: Commands,
+    mut leader_events: EventReader<LeaderEvent>,
+    mut active: ActiveCommandsMut,
+    mut leader_query: Query<&mut Multileader>,
+    styles: Res<DimensionStyles>,
+    blocks: Res<Blocks>,
+) {
+    for event in leader_events.read() {
+        match event {
+            LeaderEvent::Start(command) => {
+                if *command == LeaderCommand::Create(LeaderKind::Block)
+                    && blocks.definitions.is_empty()
+                {
+                    warn!("No blocks defined");
+                    continue;
+                }
+                // Новая команда прерывает рисование и другие указания точек
+                active.cancel_all();
+                active.leader_tool.pick = Some(LeaderPick {
+                    command: *command,
+                    points: Vec::new(),
+                    leaders: Vec::new(),
+                    armed: false,
+                });
+            }
+            LeaderEvent::Finish => {
+                let Some(pick) = active.leader_tool.pick.take() else {
+                    continue;
+                };
+                match pick.command {
+                    LeaderCommand::Create(kind) => {
+                        let Some((&landing, vertices)) = pick.points.split_last() else {
+                            continue;
+                        };
+                        if vertices.is_empty() {
+                            warn!("Leader needs an arrow point and a landing point");
+                            continue;
+                        }
+                        let content = match kind {
+                            LeaderKind::Text => {
+                                LeaderContent::Text(active.leader_tool.text.clone())
+                            }
+                            LeaderKind::Balloon => {
+                                LeaderContent::Balloons(vec![next_balloon(&leader_query)])
+                            }
+                            LeaderKind::Block => LeaderContent::Block {
+                                block: blocks.current,
+                                scale: active.block_tool.scale,
+                                rotation: active.block_tool.rotation,
+                            },
+                        };
+                        let leader = Multileader {
+                            leaders: vec![vertices.to_vec()],
+                            landing,
+                            content,
+                        };
+                        info!("Leader created: {:?}", leader.content);
+                        spawn_leader(&mut commands, leader, DimensionStyleId(styles.current));
+                    }
+                    LeaderCommand::AddLeader => {}
+                    LeaderCommand::Align => {
+                        // Полки встают в столбец под полкой первой выноски
+                        let Some(x) = pick
+                            .leaders
+                            .first()
+                            .and_then(|entity| leader_query.get(*entity).ok())
+                            .map(|leader| leader.landing.x)
+                        else {
+                            continue;
+                        };
+                        for entity in &pick.leaders[1..] {
+                            if let Ok(mut leader) = leader_query.get_mut(*entity) {
+                                leader.landing.x = x;
+                            }
+                        }
+                    }
+                    LeaderCommand::Collect => {
+                        let Some((&target, others)) = pick.leaders.split_first() else {
+                            continue;
+                        };
+                        for entity in others {
+                            let Ok(other) = leader_query.get(*entity).cloned() else {
+                                continue;
+                            };
+                            let Ok(mut leader) = leader_query.get_mut(target) else {
+                                break;
+                            };
+                            if leader.collect(&other) {
+                                commands.entity(*entity).despawn();
+                            } else {
+                                warn!(
+                                    "Only balloon leaders or leaders of one block can be collected"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            LeaderEvent::Cancel => active.leader_tool.pick = None,
+            LeaderEvent::SetText(text) => active.leader_tool.text = text.clone(),
+        }
+    }
+}
+
+/// Поле текста новых выносок
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let text = input.text.trim();
+    if text.is_empty() {
+        return Err("текст выноски не может быть пустым".to_string());
+    }
+    input
+        .events
+        .leader
+        .write(LeaderEvent::SetText(text.to_string()));
+    Ok(format!("текст: {}", text))
+}
+
+/// Следующий свободный номер позиции
+fn next_balloon(leader_query: &Query<&mut Multileader>) -> u32 {
+    leader_query
+        .iter()
+        .filter_map(|leader| match &leader.content {
+            LeaderContent::Balloons(numbers) => numbers.iter().max().copied(),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/// Указание точек и выбор выносок (клики и ввод с клавиатуры)
+pub fn leader_pick_system(
+    input: ActionInput,
+    mut snap_state: ResMut<SnapState>,
+    snap_settings: Res<SnapSettings>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    mut leader_query: Query<(
+        Entity,
+        &mut Multileader,
+        &DimensionStyleId,
+        Option<&LayerId>,
+    )>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
+    mut tool: ResMut<LeaderTool>,
+) {
+    let Some(mut pick) = tool.pick.clone() else {
+        return;
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        tool.pick = None;
+        info!("Leader command cancelled");
+        return;
+    }
+    if !pick.armed {
+        typed_point_events.clear();
+        pick.armed = true;
+        tool.pick = Some(pick);
+        return;
+    }
+
+    let mut points: Vec<DVec3> = typed_point_events.read().map(|event| event.point).collect();
+    if input.just_pressed(InputAction::PickPoint) {
+        if let Some(point) = snap_state.point() {
+            points.push(point);
+        }
+    }
+    snap_state.remember(points.iter().copied());
+    if points.is_empty() {
+        return;
+    }
+
+    for point in points {
+        let choose = match pick.command {
+            LeaderCommand::Create(_) => false,
+            LeaderCommand::AddLeader => pick.leaders.is_empty(),
+            LeaderCommand::Align | LeaderCommand::Collect => true,
+        };
+        if !choose {
+            if pick.command == LeaderCommand::AddLeader {
+                // Новая линия сразу идет от острия к полке выбранной выноски
+                if let Ok((_, mut leader, _, _)) = leader_query.get_mut(pick.leaders[0]) {
+                    leader.leaders.push(vec![point]);
+                }
+            } else {
+                pick.points.push(point);
+            }
+            continue;
+        }
+        // Выноски скрытых и заблокированных слоев не выбираются
+        let leaders = leader_query
+            .iter()
+            .filter(|(.., layer)| layer.is_none_or(|layer| layers.is_editable(*layer)))
+            .map(|(entity, leader, style, _)| (entity, leader, style));
+        let found = leader_under_cursor(point, leaders, &styles, snap_settings.aperture as f64);
+        match found {
+            Some(entity) if pick.leaders.contains(&entity) => {
+                pick.leaders.retain(|picked| *picked != entity);
+            }
+            Some(entity) => pick.leaders.push(entity),
+            None => warn!("No leader under the picked point"),
+        }
+    }
+    tool.pick = Some(pick);
+}
+
+/// Отрисовка выносок и предпросмотра новой выноски
+pub fn draw_leaders_system(
+    leader_query: Query<(Entity, &Multileader, &DimensionStyleId, Option<&LayerId>)>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    tool: Res<LeaderTool>,
+    snap_state: Res<SnapState>,
+    render_origin: Res<RenderOrigin>,
+    mut gizmos: Gizmos,
+) {
+    let picked = tool
+        .pick
+        .as_ref()
+        .map_or(&[][..], |pick| pick.leaders.as_slice());
+    for (entity, leader, style, layer) in leader_query.iter() {
+        if layer.is_some_and(|layer| !layers.is_visible(*layer)) {
+            continue;
+        }
+        let style = styles.get(*style);
+        let (layout, _) = leader.layout(style);
+        let color = if picked.contains(&entity) {
+            Color::srgb(0.3, 0.9, 0.9)
+        } else {
+            Color::srgb(0.9, 0.9, 0.5)
+        };
+        dimensions::draw_layout(&mut gizmos, &render_origin, &layout, style, color);
+    }
+
+    let Some(pick) = &tool.pick else {
+        return;
+    };
+    let Some(cursor) = snap_state.point() else {
+        return;
+    };
+    let preview_color = Color::srgba(0.9, 0.9, 0.5, 0.5);
+    match pick.command {
+        LeaderCommand::Create(_) if !pick.points.is_empty() => {
+            let points: Vec<Vec3> = pick
+                .points
+                .iter()
+                .chain([&cursor])
+                .map(|point| render_origin.to_render(*point))
+                .collect();
+            gizmos.linestrip(points, preview_color);
+        }
+        LeaderCommand::AddLeader => {
+            if let Some((_, leader, ..)) = pick
+                .leaders
+                .first()
+                .and_then(|entity| leader_query.get(*entity).ok())
+            {
+                gizmos.line(
+                    render_origin.to_render(cursor),
+                    render_origin.to_render(leader.landing),
+                    preview_color,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Вставки блоков на полках выносок: появляются у новых выносок, следуют
+/// за полкой и слоем выноски и исчезают вместе с ней (в том числе при сборе)
+pub fn update_leader_blocks_system(
+    mut commands: Commands,
+    render_origin: Res<RenderOrigin>,
+    leader_query: Query<(Entity, &Multileader, Option<&LayerId>), Without<LeaderBlock>>,
+    mut block_query: Query<(
+        Entity,
+        &LeaderBlock,
+        &mut BlockReference,
+        Option<&mut LayerId>,
+    )>,
+) {
+    let mut present = HashSet::new();
+    for (entity, owner, mut reference, layer) in block_query.iter_mut() {
+        let Some((leader, leader_layer)) = leader_query
+            .get(owner.leader)
+            .ok()
+            .and_then(|(_, leader, layer)| Some((leader.block_reference()?, layer)))
+        else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        present.insert(owner.leader);
+        reference.set_if_neq(leader);
+        if let (Some(mut layer), Some(leader_layer)) = (layer, leader_layer) {
+            layer.set_if_neq(*leader_layer);
+        }
+    }
+
+    for (entity, leader, layer) in leader_query.iter() {
+        let Some(reference) = leader.block_reference() else {
+            continue;
+        };
+        if present.contains(&entity) {
+            continue;
+        }
+        let block = spawn_reference(
+            &mut commands,
+            &render_origin,
+            reference,
+            BlockAttributes::default(),
+        );
+        commands
+            .entity(block)
+            .insert(LeaderBlock { leader: entity });
+        if let Some(layer) = layer {
+            commands.entity(block).insert(*layer);
+        }
+    }
+}
+
+/// Подписи выносок: по одной на текст или номер позиции
+pub fn update_leader_labels_system(
+    mut commands: Commands,
+    global_font: Res<GlobalFont>,
+    leader_query: Query<(Entity, &Multileader, &DimensionStyleId, Option<&LayerId>)>,
+    styles: Res<DimensionStyles>,
+    layers: Res<Layers>,
+    render_origin: Res<RenderOrigin>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut label_query: Query<(
+        Entity,
+        &LeaderLabel,
+        &mut Text,
+        &mut TextFont,
+        &mut Node,
+        &mut Visibility,
+        &ComputedNode,
+    )>,
+) {
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+    let mut present = HashSet::new();
+    for (entity, label, mut text, mut font, mut node, mut visibility, computed) in
+        label_query.iter_mut()
+    {
+        let Ok((_, leader, style, layer)) = leader_query.get(label.leader) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let style = styles.get(*style);
+        let (_, labels) = leader.layout(style);
+        let Some((content, position)) = labels.get(label.index) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        present.insert((label.leader, label.index));
+        if layer.is_some_and(|layer| !layers.is_visible(*layer)) {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        if **text != *content {
+            **text = content.clone();
+        }
+        place_label(
+            camera,
+            render_origin.to_render(*position),
+            style.text_height(),
+            &mut font,
+            &mut node,
+            &mut visibility,
+            computed,
+        );
+    }
+
+    // Новые выноски и добавленные при сборе кружки получают подписи
+    for (entity, leader, style, _) in leader_query.iter() {
+        let (_, labels) = leader.layout(styles.get(*style));
+        for index in 0..labels.len() {
+            if present.contains(&(entity, index)) {
+                continue;
+            }
+            commands.spawn((
+                Text::new(""),
+                TextFont {
+                    font: global_font.handle.clone(),
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.5)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                Visibility::Hidden,
+                LeaderLabel {
+                    leader: entity,
+                    index,
+                },
+                Name::new("LeaderLabel"),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render_origin::FAR_POINT;
+
+    #[test]
+    fn leader_layout_and_collect() {
+        let style = DimensionStyle::default();
+        let tip = FAR_POINT;
+        let landing = tip + DVec3::new(10.0, 0.0, 5.0);
+        let text = Multileader {
+            leaders: vec![vec![tip]],
+            landing,
+            content: LeaderContent::Text("Ra 3.2".to_string()),
+        };
+        let (layout, labels) = text.layout(&style);
+        // Линия выноски и полка вправо, стрелка смотрит на острие
+        assert_eq!(layout.lines.len(), 2);
+        assert!(layout.lines[1].1.x > landing.x);
+        let (arrow_tip, direction) = layout.arrows[0];
+        assert!(arrow_tip.abs_diff_eq(tip, 1e-9));
+        assert!(direction.x < 0.0 && direction.y < 0.0);
+        assert_eq!(labels.len(), 1);
+        assert!(labels[0].1.z > landing.z);
+
+        let balloon = |number, tip: DVec3| Multileader {
+            leaders: vec![vec![tip]],
+            landing,
+            content: LeaderContent::Balloons(vec![number]),
+        };
+        let mut first = balloon(1, tip);
+        assert!(first.collect(&balloon(2, tip + DVec3::X)));
+        assert!(!first.collect(&text));
+        assert_eq!(first.content, LeaderContent::Balloons(vec![1, 2]));
+        assert_eq!(first.leaders.len(), 2);
+        let (_, labels) = first.layout(&style);
+        assert_eq!(
+            labels
+                .iter()
+                .map(|(text, _)| text.as_str())
+                .collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+
+        // Выноски одного блока сходятся к одной вставке на полке первой
+        let block = |block, tip: DVec3| Multileader {
+            leaders: vec![vec![tip]],
+            landing,
+            content: LeaderContent::Block {
+                block,
+                scale: 2.0,
+                rotation: 0.0,
+            },
+        };
+        let mut first = block(0, tip);
+        assert!(first.collect(&block(0, tip + DVec3::Z)));
+        assert!(!first.collect(&block(1, tip)));
+        assert!(!first.collect(&balloon(3, tip)));
+        assert_eq!(first.leaders.len(), 2);
+        let (layout, labels) = first.layout(&style);
+        assert_eq!(layout.lines.len(), 2);
+        assert!(labels.is_empty());
+        let reference = first.block_reference().unwrap();
+        assert_eq!((reference.block, reference.scale), (0, 2.0));
+        assert_eq!(reference.position, landing);
+        assert!(text.block_reference().is_none());
+    }
+}
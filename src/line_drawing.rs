@@ -1,19 +1,27 @@
 use bevy::color::palettes::css::{GREEN, YELLOW};
+use bevy::math::DVec3;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
 use crate::{
-    coordinate_systems::{
-        formatting, CoordinatePoint, CoordinateSettings, CoordinateSystem,
-    },
+    coordinate_systems::{formatting, CoordinatePoint, CoordinateSettings},
+    dimension_style::DimensionStyle,
+    dimensions,
     events::*, // Используем централизованные события
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers},
+    properties::EntityStyle,
+    render_origin::{DocumentPosition, RenderOrigin},
+    selection::ActiveCommandsMut,
+    snapping::SnapState,
+    ucs::{CoordinateFrame, UcsManager},
 };
 
 // Компоненты для системы рисования линий
 #[derive(Component)]
 pub struct DrawableLine {
-    pub start: Vec3,
-    pub end: Vec3,
+    pub start: DVec3,
+    pub end: DVec3,
     pub id: u32,
     // Добавляем информацию о координатах
     pub start_coord: CoordinatePoint,
@@ -27,7 +35,7 @@ pub struct LineEndpoint;
 #[derive(Resource, Default)]
 pub struct LineDrawingState {
     pub is_drawing: bool,
-    pub start_point: Option<Vec3>,
+    pub start_point: Option<DVec3>,
     pub line_counter: u32,
     pub is_enabled: bool, // Включено ли рисование линий
     // Добавляем поддержку полярного режима ввода
@@ -35,9 +43,18 @@ pub struct LineDrawingState {
     pub polar_start_point: Option<CoordinatePoint>,
 }
 
+impl LineDrawingState {
+    /// Выключить рисование, бросив начатый отрезок
+    pub fn stop(&mut self) {
+        self.is_enabled = false;
+        self.is_drawing = false;
+        self.start_point = None;
+        self.polar_start_point = None;
+    }
+}
+
 #[derive(Resource)]
 pub struct LineSettings {
-    pub line_color: Color,
     pub line_thickness: f32,
     pub endpoint_size: f32,
     pub endpoint_color: Color,
@@ -48,7 +65,6 @@ pub struct LineSettings {
 impl Default for LineSettings {
     fn default() -> Self {
         Self {
-            line_color: Color::srgb(1.0, 0.2, 0.2), // Красный цвет
             line_thickness: 0.1,
             endpoint_size: 0.2,
             endpoint_color: Color::srgb(0.2, 0.8, 0.2), // Зеленый цвет
@@ -62,21 +78,57 @@ pub fn line_drawing_system(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut line_state: ResMut<LineDrawingState>,
+    active: ActiveCommandsMut,
     line_settings: Res<LineSettings>,
     coordinate_settings: Res<CoordinateSettings>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut snap_state: ResMut<SnapState>,
+    render_origin: Res<RenderOrigin>,
+    input: ActionInput,
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     mut gizmos: Gizmos,
     mut line_events: EventWriter<LineCreatedEvent>,
     mut point_events: EventWriter<PointSelectedEvent>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
 ) {
+    // Пока другая команда указывает точки, щелчки и ввод достаются ей
+    let picking = active.is_picking();
+    let ActiveCommandsMut {
+        mut line_state,
+        ucs_manager,
+        ..
+    } = active;
+
     // Ранний выход если система отключена
     if !line_state.is_enabled {
+        if typed_point_events.read().count() > 0 {
+            warn!("Line drawing mode is disabled, typed point ignored");
+        }
         return;
     }
 
+    if picking {
+        typed_point_events.clear();
+    }
+
+    // Точки, введенные с клавиатуры, обрабатываются так же, как клики
+    for event in typed_point_events.read() {
+        handle_line_click_enhanced(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut line_state,
+            &mut snap_state,
+            &line_settings,
+            &coordinate_settings,
+            &ucs_manager,
+            &render_origin,
+            event.point,
+            &mut line_events,
+            &mut point_events,
+        );
+    }
+
     // Получаем камеру и окно
     let Ok((camera, camera_transform)) = camera_query.single() else {
         return;
@@ -86,7 +138,7 @@ pub fn line_drawing_system(
     };
 
     // Обрабатываем клик левой кнопкой мыши
-    if mouse_input.just_pressed(MouseButton::Left) {
+    if input.just_pressed(InputAction::PickPoint) && !picking {
         if let Some(cursor_position) = window.cursor_position() {
             // Преобразуем курсор в мировые координаты на плоскости Y=0
             if let Some(world_position) = screen_to_world_plane(
@@ -96,13 +148,18 @@ pub fn line_drawing_system(
                 Vec3::Y, // Нормаль плоскости сетки
                 0.0,     // Y-координата плоскости сетки
             ) {
+                // Применяем объектную привязку, если она найдена
+                let world_position = snap_state.resolve(render_origin.to_document(world_position));
                 handle_line_click_enhanced(
                     &mut commands,
                     &mut meshes,
                     &mut materials,
                     &mut line_state,
+                    &mut snap_state,
                     &line_settings,
                     &coordinate_settings,
+                    &ucs_manager,
+                    &render_origin,
                     world_position,
                     &mut line_events,
                     &mut point_events,
@@ -121,10 +178,12 @@ pub fn line_drawing_system(
                     draw_line_preview(
                         &mut gizmos,
                         start,
-                        current_position,
+                        snap_state.resolve(render_origin.to_document(current_position)),
                         &coordinate_settings,
                         &line_settings,
                         &line_state,
+                        &ucs_manager.frame(snap_state.last_point),
+                        &render_origin,
                     );
                 }
             }
@@ -135,39 +194,57 @@ pub fn line_drawing_system(
 /// Отрисовка preview линии
 fn draw_line_preview(
     gizmos: &mut Gizmos,
-    start: Vec3,
-    current: Vec3,
+    start: DVec3,
+    current: DVec3,
     coordinate_settings: &CoordinateSettings,
     line_settings: &LineSettings,
     line_state: &LineDrawingState,
+    frame: &CoordinateFrame,
+    render_origin: &RenderOrigin,
 ) {
     // Выбираем цвет в зависимости от режима
-    let preview_color = match coordinate_settings.coordinate_system {
-        CoordinateSystem::Cartesian => Color::srgb(1.0, 1.0, 0.0), // Желтый
-        CoordinateSystem::Polar => line_settings.polar_preview_color, // Синий
+    let preview_color = if coordinate_settings.coordinate_system.is_polar() {
+        line_settings.polar_preview_color // Синий
+    } else {
+        Color::srgb(1.0, 1.0, 0.0) // Желтый
     };
 
     // Рисуем preview линию
-    gizmos.line(start, current, preview_color);
+    let (start_render, current_render) = (
+        render_origin.to_render(start),
+        render_origin.to_render(current),
+    );
+    gizmos.line(start_render, current_render, preview_color);
 
     // Показываем координаты в gizmos
-    gizmos.sphere(start, 0.1, GREEN);
-    gizmos.sphere(current, 0.1, YELLOW);
+    gizmos.sphere(start_render, 0.1, GREEN);
+    gizmos.sphere(current_render, 0.1, YELLOW);
 
-    // В полярном режиме показываем дополнительную информацию
-    if coordinate_settings.coordinate_system == CoordinateSystem::Polar {
+    // В полярных режимах показываем дополнительную информацию
+    if coordinate_settings.coordinate_system.is_polar() {
         if let Some(_start_coord) = &line_state.polar_start_point {
-            let current_coord = CoordinatePoint::from_world(current);
+            let current_coord = frame.coordinate_point(current);
 
-            // Рисуем радиус-вектор
-            gizmos.line(Vec3::ZERO, current, Color::srgb(0.5, 0.5, 1.0));
+            // Рисуем радиус-вектор от полюса
+            gizmos.line(
+                render_origin.to_render(frame.pole),
+                current_render,
+                Color::srgb(0.5, 0.5, 1.0),
+            );
 
-            // Можно добавить дугу для показа угла (опционально)
-            draw_angle_arc(
-                gizmos,
-                current_coord.polar.theta,
+            // Дуга угла от оси X ПСК в виде углового размера
+            let start = frame.world_angle(0.0);
+            let style = DimensionStyle::default();
+            let arc = dimensions::angular_arc(
+                frame.pole,
+                start,
+                frame.world_angle(current_coord.polar.theta) - start,
                 current_coord.polar.r.min(2.0),
+                &[],
+                &style,
             );
+            let color = Color::srgb(0.7, 0.7, 1.0);
+            dimensions::draw_layout(gizmos, render_origin, &arc, &style, color);
         }
     }
 }
@@ -178,39 +255,44 @@ fn handle_line_click_enhanced(
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     line_state: &mut ResMut<LineDrawingState>,
+    snap_state: &mut SnapState,
     line_settings: &Res<LineSettings>,
     coordinate_settings: &Res<CoordinateSettings>,
-    world_position: Vec3,
+    ucs_manager: &UcsManager,
+    render_origin: &RenderOrigin,
+    world_position: DVec3,
     line_events: &mut EventWriter<LineCreatedEvent>,
     point_events: &mut EventWriter<PointSelectedEvent>,
 ) {
+    // Координаты отсчитываются от полюса, заданного до этой точки
+    let frame = ucs_manager.frame(snap_state.last_point);
+    snap_state.remember([world_position]);
+
     if !line_state.is_drawing {
         // Начинаем рисование - сохраняем стартовую точку
         line_state.start_point = Some(world_position);
         line_state.is_drawing = true;
 
         // Сохраняем координатную информацию
-        let coord_point = CoordinatePoint::from_world(world_position);
+        let coord_point = frame.coordinate_point(world_position);
         line_state.polar_start_point = Some(coord_point.clone());
 
         // Выводим информацию в зависимости от системы координат
-        match coordinate_settings.coordinate_system {
-            CoordinateSystem::Cartesian => {
-                info!(
-                    "Start point (Cartesian): {}",
-                    formatting::format_cartesian(coord_point.cartesian)
-                );
-            }
-            CoordinateSystem::Polar => {
-                info!(
-                    "Start point (Polar): {}",
-                    formatting::format_polar(coord_point.polar, coordinate_settings.angle_unit)
-                );
-            }
-        }
+        info!(
+            "Start point ({:?}): {}",
+            coordinate_settings.coordinate_system,
+            formatting::format_coordinate_point(&coord_point, coordinate_settings)
+        );
 
         // Создаем маркер начальной точки
-        spawn_endpoint(commands, meshes, materials, world_position, line_settings);
+        spawn_endpoint(
+            commands,
+            meshes,
+            materials,
+            render_origin,
+            world_position,
+            line_settings,
+        );
 
         // Отправляем событие выбора точки
         point_events.write(PointSelectedEvent {
@@ -221,34 +303,22 @@ fn handle_line_click_enhanced(
         // Завершаем рисование - создаем линию
         if let Some(start) = line_state.start_point {
             if let Some(start_coord) = &line_state.polar_start_point {
-                let end_coord = CoordinatePoint::from_world(world_position);
+                let end_coord = frame.coordinate_point(world_position);
 
                 // Выводим информацию о конечной точке
-                match coordinate_settings.coordinate_system {
-                    CoordinateSystem::Cartesian => {
-                        info!(
-                            "End point (Cartesian): {}",
-                            formatting::format_cartesian(end_coord.cartesian)
-                        );
-                    }
-                    CoordinateSystem::Polar => {
-                        info!(
-                            "End point (Polar): {}",
-                            formatting::format_polar(
-                                end_coord.polar,
-                                coordinate_settings.angle_unit
-                            )
-                        );
-                    }
-                }
+                info!(
+                    "End point ({:?}): {}",
+                    coordinate_settings.coordinate_system,
+                    formatting::format_coordinate_point(&end_coord, coordinate_settings)
+                );
 
                 // Создаем линию с расширенной информацией
                 create_line_enhanced(
                     commands,
                     meshes,
-                    materials,
                     line_state,
                     line_settings,
+                    render_origin,
                     start,
                     world_position,
                     start_coord.clone(),
@@ -257,16 +327,24 @@ fn handle_line_click_enhanced(
                 );
 
                 // Создаем маркер конечной точки
-                spawn_endpoint(commands, meshes, materials, world_position, line_settings);
+                spawn_endpoint(
+                    commands,
+                    meshes,
+                    materials,
+                    render_origin,
+                    world_position,
+                    line_settings,
+                );
 
                 // Выводим информацию о созданной линии
                 info!(
                     "Line created: Length = {}, Angle = {}",
-                    formatting::format_line_length(start, world_position),
+                    formatting::format_line_length(start, world_position, coordinate_settings),
                     formatting::format_line_angle(
                         start,
                         world_position,
-                        coordinate_settings.angle_unit
+                        &frame,
+                        coordinate_settings
                     )
                 );
 
@@ -285,33 +363,13 @@ fn handle_line_click_enhanced(
     }
 }
 
-/// Создание линии с расширенной информацией о координатах - Bevy 0.15+ синтаксис
-fn create_line_enhanced(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    line_state: &mut ResMut<LineDrawingState>,
-    line_settings: &Res<LineSettings>,
-    start: Vec3,
-    end: Vec3,
-    start_coord: CoordinatePoint,
-    end_coord: CoordinatePoint,
-    line_events: &mut EventWriter<LineCreatedEvent>,
-) {
-    let direction = end - start;
+/// Центр, поворот цилиндра (ось Y вдоль отрезка) и длина меша отрезка
+pub fn line_placement(start: DVec3, end: DVec3) -> (DVec3, Quat, f32) {
+    // Меш строится в f32: длина и направление отрезка не зависят от его удаленности
+    let direction = (end - start).as_vec3();
     let length = direction.length();
     let center = (start + end) / 2.0;
 
-    // Создаем материал линии
-    let line_material = materials.add(StandardMaterial {
-        base_color: line_settings.line_color,
-        unlit: true,
-        ..default()
-    });
-
-    // Создаем меш цилиндра для линии
-    let line_mesh = meshes.add(Cylinder::new(line_settings.line_thickness, length));
-
     // Вычисляем поворот для ориентации цилиндра
     let rotation = if direction.length() > 1e-6 {
         let normalized_direction = direction.normalize();
@@ -326,30 +384,31 @@ fn create_line_enhanced(
     } else {
         Quat::IDENTITY
     };
+    (center, rotation, length)
+}
 
-    // Спавним линию с расширенной информацией - новый Bevy 0.15+ синтаксис
-    let line_id = line_state.line_counter;
-    line_state.line_counter += 1;
-
-    commands.spawn((
-        // Bevy 0.15+: Используем Mesh3d и MeshMaterial3d
-        Mesh3d(line_mesh),
-        MeshMaterial3d(line_material),
-        Transform {
-            translation: center,
-            rotation,
-            scale: Vec3::ONE,
-        },
-        DrawableLine {
-            start,
-            end,
-            id: line_id,
-            start_coord,
-            end_coord,
-        },
-        Name::new(format!("Line_{}", line_id)),
-        // Transform и Visibility добавляются автоматически через Required Components
-    ));
+/// Создание линии с расширенной информацией о координатах - Bevy 0.15+ синтаксис
+fn create_line_enhanced(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    line_state: &mut ResMut<LineDrawingState>,
+    line_settings: &Res<LineSettings>,
+    render_origin: &RenderOrigin,
+    start: DVec3,
+    end: DVec3,
+    start_coord: CoordinatePoint,
+    end_coord: CoordinatePoint,
+    line_events: &mut EventWriter<LineCreatedEvent>,
+) {
+    let (_, line_id) = spawn_line(
+        commands,
+        meshes,
+        line_state,
+        line_settings,
+        render_origin,
+        (start, end),
+        (start_coord, end_coord),
+    );
 
     // Отправляем событие создания линии
     line_events.write(LineCreatedEvent {
@@ -359,17 +418,50 @@ fn create_line_enhanced(
     });
 }
 
-/// Вспомогательная функция для рисования дуги угла в полярном режиме
-fn draw_angle_arc(gizmos: &mut Gizmos, angle: f32, radius: f32) {
-    let segments = 16;
-    let step = angle / segments as f32;
-    for i in 0..segments {
-        let angle1 = i as f32 * step;
-        let angle2 = (i + 1) as f32 * step;
-        let point1 = Vec3::new(radius * angle1.cos(), 0.0, radius * angle1.sin());
-        let point2 = Vec3::new(radius * angle2.cos(), 0.0, radius * angle2.sin());
-        gizmos.line(point1, point2, Color::srgb(0.7, 0.7, 1.0));
-    }
+/// Создание отрезка с мешем; материал по свойствам слоя создает
+/// `apply_styles_system`. Возвращает сущность и номер отрезка
+pub fn spawn_line(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    line_state: &mut ResMut<LineDrawingState>,
+    line_settings: &Res<LineSettings>,
+    render_origin: &RenderOrigin,
+    (start, end): (DVec3, DVec3),
+    (start_coord, end_coord): (CoordinatePoint, CoordinatePoint),
+) -> (Entity, u32) {
+    let (center, rotation, length) = line_placement(start, end);
+
+    // Создаем меш цилиндра для линии
+    let line_mesh = meshes.add(Cylinder::new(line_settings.line_thickness, length));
+
+    // Спавним линию с расширенной информацией - новый Bevy 0.15+ синтаксис
+    let line_id = line_state.line_counter;
+    line_state.line_counter += 1;
+
+    let entity = commands
+        .spawn((
+            // Bevy 0.15+: Используем Mesh3d и MeshMaterial3d
+            Mesh3d(line_mesh),
+            Transform {
+                translation: render_origin.to_render(center),
+                rotation,
+                scale: Vec3::ONE,
+            },
+            DocumentPosition(center),
+            DrawableLine {
+                start,
+                end,
+                id: line_id,
+                start_coord,
+                end_coord,
+            },
+            // Цвет и толщина берутся у слоя, пока у отрезка нет своих
+            EntityStyle::default(),
+            Name::new(format!("Line_{}", line_id)),
+            // Transform и Visibility добавляются автоматически через Required Components
+        ))
+        .id();
+    (entity, line_id)
 }
 
 // Функция для преобразования экранных координат в мировые на плоскости
@@ -397,11 +489,12 @@ pub fn screen_to_world_plane(
 }
 
 /// Создание маркера конечной точки - Bevy 0.15+ синтаксис
-fn spawn_endpoint(
+pub fn spawn_endpoint(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-    position: Vec3,
+    render_origin: &RenderOrigin,
+    position: DVec3,
     line_settings: &Res<LineSettings>,
 ) {
     let endpoint_material = materials.add(StandardMaterial {
@@ -415,7 +508,8 @@ fn spawn_endpoint(
         // Bevy 0.15+: Используем Mesh3d и MeshMaterial3d
         Mesh3d(endpoint_mesh),
         MeshMaterial3d(endpoint_material),
-        Transform::from_translation(position),
+        Transform::from_translation(render_origin.to_render(position)),
+        DocumentPosition(position),
         LineEndpoint,
         Name::new("LineEndpoint"),
         // Transform и Visibility добавляются автоматически
@@ -424,96 +518,117 @@ fn spawn_endpoint(
 
 /// Система для переключения режима рисования линий (обновленная)
 pub fn toggle_line_drawing_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut line_state: ResMut<LineDrawingState>,
+    input: ActionInput,
+    mut active: ActiveCommandsMut,
     coordinate_settings: Res<CoordinateSettings>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyL) {
-        line_state.is_enabled = !line_state.is_enabled;
-
-        // Сбрасываем состояние при отключении
-        if !line_state.is_enabled {
-            line_state.is_drawing = false;
-            line_state.start_point = None;
-            line_state.polar_start_point = None;
-        }
+    if input.just_pressed(InputAction::ToggleLineDrawing) {
+        // Включение рисования прерывает указание точек другими командами,
+        // выключение сбрасывает начатый отрезок
+        let enable = !active.line_state.is_enabled;
+        active.cancel_all();
+        active.line_state.is_enabled = enable;
 
         info!(
             "Line drawing mode: {} (System: {:?})",
-            if line_state.is_enabled {
-                "enabled"
-            } else {
-                "disabled"
-            },
+            if enable { "enabled" } else { "disabled" },
             coordinate_settings.coordinate_system
         );
     }
 }
 
+/// Удаление отрезков слоев, доступных для правки, вместе с маркерами концов.
+/// Отрезки скрытых и заблокированных слоев остаются, как и их маркеры.
+/// Возвращает число оставшихся отрезков.
+fn clear_editable_lines(
+    commands: &mut Commands,
+    layers: &Layers,
+    line_query: &Query<(Entity, &DrawableLine, Option<&LayerId>)>,
+    endpoint_query: &Query<(Entity, &DocumentPosition), With<LineEndpoint>>,
+) -> usize {
+    // Отрезок без слоя только что создан и попадет на текущий слой
+    let editable = |layer: Option<&LayerId>| layer.is_none_or(|layer| layers.is_editable(*layer));
+    let mut kept_ends = Vec::new();
+    for (entity, line, layer) in line_query.iter() {
+        if editable(layer) {
+            commands.entity(entity).despawn();
+        } else {
+            kept_ends.extend([line.start, line.end]);
+        }
+    }
+    for (entity, position) in endpoint_query.iter() {
+        if !kept_ends.iter().any(|end| end.distance(position.0) < 1e-9) {
+            commands.entity(entity).despawn();
+        }
+    }
+    kept_ends.len() / 2
+}
+
 /// Система для очистки всех линий (обновленная)
 pub fn clear_lines_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut commands: Commands,
-    line_query: Query<Entity, Or<(With<DrawableLine>, With<LineEndpoint>)>>,
+    layers: Res<Layers>,
+    line_query: Query<(Entity, &DrawableLine, Option<&LayerId>)>,
+    endpoint_query: Query<(Entity, &DocumentPosition), With<LineEndpoint>>,
     mut line_state: ResMut<LineDrawingState>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Delete) {
-        // Удаляем все линии и точки
-        for entity in line_query.iter() {
-            commands.entity(entity).despawn();
-        }
+    if input.just_pressed(InputAction::ClearLines) {
+        // Удаляем все линии и точки, кроме заблокированных
+        let kept = clear_editable_lines(&mut commands, &layers, &line_query, &endpoint_query);
 
         // Сбрасываем состояние
         line_state.is_drawing = false;
         line_state.start_point = None;
         line_state.polar_start_point = None;
-        line_state.line_counter = 0;
-        info!("All lines cleared");
+        if kept == 0 {
+            line_state.line_counter = 0;
+        }
+        info!("Lines cleared, kept on locked or hidden layers: {}", kept);
     }
 }
 
 /// Система для отображения информации о линиях (обновленная)
 pub fn line_info_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     line_query: Query<&DrawableLine>,
     coordinate_settings: Res<CoordinateSettings>,
+    ucs_manager: Res<UcsManager>,
+    snap_state: Res<SnapState>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyI) {
+    if input.just_pressed(InputAction::LineInfo) {
         info!("=== LINE INFORMATION ===");
         info!(
             "Current coordinate system: {:?}",
             coordinate_settings.coordinate_system
         );
         info!("Current angle unit: {:?}", coordinate_settings.angle_unit);
+        info!(
+            "UCS: {}, polar pole: {}",
+            ucs_manager.active().name,
+            ucs_manager.pole_label(&coordinate_settings)
+        );
 
+        // Координаты пересчитываются в текущую ПСК
+        let frame = ucs_manager.frame(snap_state.last_point);
         for line in line_query.iter() {
-            let length = (line.end - line.start).length();
-            match coordinate_settings.coordinate_system {
-                CoordinateSystem::Cartesian => {
-                    info!(
-                        "Line {}: Start {}, End {}, Length: {:.2}",
-                        line.id,
-                        formatting::format_cartesian(line.start_coord.cartesian),
-                        formatting::format_cartesian(line.end_coord.cartesian),
-                        length
-                    );
-                }
-                CoordinateSystem::Polar => {
-                    info!(
-                        "Line {}: Start {}, End {}, Length: {:.2}",
-                        line.id,
-                        formatting::format_polar(
-                            line.start_coord.polar,
-                            coordinate_settings.angle_unit
-                        ),
-                        formatting::format_polar(
-                            line.end_coord.polar,
-                            coordinate_settings.angle_unit
-                        ),
-                        length
-                    );
-                }
-            }
+            let length = formatting::format_line_length(line.start, line.end, &coordinate_settings);
+            let angle =
+                formatting::format_line_angle(line.start, line.end, &frame, &coordinate_settings);
+            info!(
+                "Line {}: Start {}, End {}, Length: {}, Angle: {}",
+                line.id,
+                formatting::format_coordinate_point(
+                    &frame.coordinate_point(line.start),
+                    &coordinate_settings
+                ),
+                formatting::format_coordinate_point(
+                    &frame.coordinate_point(line.end),
+                    &coordinate_settings
+                ),
+                length,
+                angle
+            );
         }
         info!("Total lines: {}", line_query.iter().count());
     }
@@ -523,27 +638,33 @@ pub fn line_info_system(
 pub fn handle_line_events(
     mut create_events: EventReader<CreateLineEvent>,
     mut delete_events: EventReader<DeleteAllLinesEvent>,
-    mut line_state: ResMut<LineDrawingState>,
+    mut active: ActiveCommandsMut,
     mut commands: Commands,
-    line_query: Query<Entity, Or<(With<DrawableLine>, With<LineEndpoint>)>>,
+    layers: Res<Layers>,
+    line_query: Query<(Entity, &DrawableLine, Option<&LayerId>)>,
+    endpoint_query: Query<(Entity, &DocumentPosition), With<LineEndpoint>>,
 ) {
     // Обработка событий создания линии
     for _event in create_events.read() {
-        line_state.is_enabled = true;
+        active.cancel_all();
+        active.line_state.is_enabled = true;
         info!("Line drawing mode enabled");
     }
 
     // Обработка событий удаления всех линий
     for _event in delete_events.read() {
-        // Удаляем все линии и точки
-        for entity in line_query.iter() {
-            commands.entity(entity).despawn();
-        }
+        // Удаляем все линии и точки, кроме заблокированных
+        let kept = clear_editable_lines(&mut commands, &layers, &line_query, &endpoint_query);
 
         // Сбрасываем состояние
-        line_state.is_drawing = false;
-        line_state.start_point = None;
-        line_state.line_counter = 0;
-        info!("All lines cleared via UI");
+        active.line_state.is_drawing = false;
+        active.line_state.start_point = None;
+        if kept == 0 {
+            active.line_state.line_counter = 0;
+        }
+        info!(
+            "Lines cleared via UI, kept on locked or hidden layers: {}",
+            kept
+        );
     }
 }
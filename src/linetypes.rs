@@ -0,0 +1,418 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::{
+    events::LinetypeEvent,
+    input_field::{FieldInput, InputFieldKind},
+    keybindings::beside_executable,
+};
+
+/// Имя сплошной линии; она есть всегда
+pub const CONTINUOUS: &str = "Сплошная";
+
+/// Файл, в который сохраняются определения типов линий (рядом с исполняемым файлом)
+pub const LINETYPES_FILE: &str = "linetypes.lin";
+
+/// Встроенные типы линий в формате .lin. Длины штрихов — в единицах чертежа
+/// при общем масштабе 1.
+const STANDARD_LIN: &str = "\
+*Сплошная,Сплошная ________________
+A
+*Штриховая,Невидимый контур __ __ __ __
+A,0.5,-0.25
+*Штрихпунктирная,Осевая ____ _ ____ _ ____
+A,1.25,-0.25,0.25,-0.25
+*Штрихпунктирная с двумя точками,Линия сгиба ____ . . ____ . . ____
+A,1.25,-0.25,0,-0.25,0,-0.25
+*Пунктирная,Точки . . . . . . . . .
+A,0,-0.25
+";
+
+/// Больше штрихов на одном отрезке не строится, отрезок рисуется сплошным
+const MAX_DASHES: f64 = 2000.0;
+
+/// Тип линии: штрихи (> 0), пробелы (< 0) и точки (0) повторяются вдоль линии
+#[derive(Clone, PartialEq, Debug)]
+pub struct Linetype {
+    pub name: String,
+    pub description: String,
+    /// Пустой узор — сплошная линия
+    pub pattern: Vec<f64>,
+}
+
+impl Linetype {
+    /// Длина одного повторения узора
+    pub fn period(&self) -> f64 {
+        self.pattern.iter().map(|element| element.abs()).sum()
+    }
+
+    /// Видимые участки `(от, до)` вдоль пути длиной `length`; точки — участки
+    /// нулевой длины. Узор идет от начала пути без разрывов на изломах, поэтому
+    /// ломаная или дуга, разбитая на отрезки, передается одной общей длиной.
+    /// Если на пути не помещается ни одного повторения, путь рисуется сплошным.
+    pub fn dashes(&self, length: f64, scale: f64) -> Vec<(f64, f64)> {
+        let period = self.period() * scale;
+        if period <= 0.0 || length < period || length / period > MAX_DASHES {
+            return vec![(0.0, length)];
+        }
+        let mut dashes = Vec::new();
+        let mut position = 0.0;
+        'path: loop {
+            for element in &self.pattern {
+                if position >= length {
+                    break 'path;
+                }
+                let size = element.abs() * scale;
+                if *element >= 0.0 {
+                    dashes.push((position, (position + size).min(length)));
+                }
+                position += size;
+            }
+        }
+        dashes
+    }
+
+    /// Участки узора `(отрезок, от, до)` на ломаной с длинами отрезков `lengths`.
+    /// Узор идет через изломы без разрыва: штрих, переходящий излом, делится на
+    /// два участка. Расстояния отсчитываются от начала своего отрезка.
+    pub fn path_dashes(&self, lengths: &[f64], scale: f64) -> Vec<(usize, f64, f64)> {
+        let total: f64 = lengths.iter().sum();
+        let mut pieces = Vec::new();
+        for (from, to) in self.dashes(total, scale) {
+            let mut start = 0.0;
+            for (index, length) in lengths.iter().enumerate() {
+                let end = start + length;
+                if from == to {
+                    // Точка достается первому отрезку, на котором лежит
+                    if from <= end && *length > 0.0 {
+                        pieces.push((index, from - start, from - start));
+                        break;
+                    }
+                } else if from.max(start) < to.min(end) {
+                    pieces.push((index, from.max(start) - start, to.min(end) - start));
+                }
+                start = end;
+            }
+        }
+        pieces
+    }
+
+    /// Определение в формате .lin, обратное `parse_lin`
+    pub fn to_lin(&self) -> String {
+        let mut text = format!("*{},{}\nA", self.name, self.description);
+        for element in &self.pattern {
+            text.push_str(&format!(",{}", element));
+        }
+        text
+    }
+}
+
+/// Файл .lin со всеми определениями
+pub fn write_lin(linetypes: &[Linetype]) -> String {
+    linetypes
+        .iter()
+        .map(|linetype| linetype.to_lin() + "\n")
+        .collect()
+}
+
+/// Разбор определений в формате .lin: строка `*Имя,описание`, за ней
+/// `A,штрих,пробел,...`. Строки разделяются переводом строки или `;`.
+pub fn parse_lin(text: &str) -> Result<Vec<Linetype>, String> {
+    let mut linetypes = Vec::new();
+    let mut header: Option<(String, String)> = None;
+    for line in text
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        if let Some(definition) = line.strip_prefix('*') {
+            if let Some((name, _)) = header {
+                return Err(format!("у типа «{}» нет строки узора", name));
+            }
+            let (name, description) = definition.split_once(',').unwrap_or((definition, ""));
+            if name.trim().is_empty() {
+                return Err("пустое имя типа линии".to_string());
+            }
+            header = Some((name.trim().to_string(), description.trim().to_string()));
+            continue;
+        }
+        let Some((name, description)) = header.take() else {
+            return Err("ожидается «*имя,описание»".to_string());
+        };
+        let mut elements = line.split(',').map(str::trim);
+        if !elements
+            .next()
+            .is_some_and(|alignment| alignment.eq_ignore_ascii_case("A"))
+        {
+            return Err("строка узора начинается с «A»".to_string());
+        }
+        let pattern = elements
+            .filter(|element| !element.is_empty())
+            .map(|element| {
+                element
+                    .parse::<f64>()
+                    .map_err(|_| format!("«{}» — не число", element))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if pattern.first().is_some_and(|first| *first < 0.0) {
+            return Err("узор начинается со штриха или точки".to_string());
+        }
+        linetypes.push(Linetype {
+            name,
+            description,
+            pattern,
+        });
+    }
+    if let Some((name, _)) = header {
+        return Err(format!("у типа «{}» нет строки узора", name));
+    }
+    Ok(linetypes)
+}
+
+/// Меш отрезка длиной `length` вдоль оси Y с центром в начале координат:
+/// цилиндр для сплошной линии или штрихи-цилиндры по узору
+pub fn line_mesh(length: f32, radius: f32, linetype: Option<&Linetype>, scale: f64) -> Mesh {
+    let half = Vec3::Y * length / 2.0;
+    path_mesh(&[-half, half], radius, linetype, scale)
+}
+
+/// Меш ломаной (в том числе дуги, разбитой на отрезки) с узором, непрерывным
+/// на изломах
+pub fn path_mesh(path: &[Vec3], radius: f32, linetype: Option<&Linetype>, scale: f64) -> Mesh {
+    let lengths: Vec<f64> = path
+        .windows(2)
+        .map(|segment| segment[0].distance(segment[1]) as f64)
+        .collect();
+    let pieces = match linetype {
+        Some(linetype) => linetype.path_dashes(&lengths, scale),
+        None => lengths
+            .iter()
+            .enumerate()
+            .map(|(index, length)| (index, 0.0, *length))
+            .collect(),
+    };
+    if let ([length], [(_, from, to)]) = (lengths.as_slice(), pieces.as_slice()) {
+        if *from <= 0.0 && *to >= *length {
+            let (start, end) = (path[0], path[1]);
+            return Cylinder::new(radius, *length as f32)
+                .mesh()
+                .build()
+                .rotated_by(Quat::from_rotation_arc(
+                    Vec3::Y,
+                    (end - start).normalize_or(Vec3::Y),
+                ))
+                .translated_by((start + end) / 2.0);
+        }
+    }
+    let mut mesh: Option<Mesh> = None;
+    for (index, from, to) in pieces {
+        let (start, end) = (path[index], path[index + 1]);
+        let direction = (end - start).normalize_or(Vec3::Y);
+        // Точка рисуется коротким штрихом длиной в толщину линии
+        let dash = ((to - from) as f32).max(radius * 2.0);
+        let center = start + direction * ((from + to) / 2.0) as f32;
+        let part = Cylinder::new(radius, dash)
+            .mesh()
+            .resolution(8)
+            .build()
+            .rotated_by(Quat::from_rotation_arc(Vec3::Y, direction))
+            .translated_by(center);
+        match &mut mesh {
+            Some(mesh) => {
+                if let Err(error) = mesh.merge(&part) {
+                    warn!("Dash mesh merge failed: {}", error);
+                }
+            }
+            None => mesh = Some(part),
+        }
+    }
+    mesh.unwrap_or_else(|| Cylinder::new(radius, 0.0).into())
+}
+
+/// Определения типов линий и общий масштаб узоров
+#[derive(Resource)]
+pub struct Linetypes {
+    pub linetypes: Vec<Linetype>,
+    pub scale: f64,
+}
+
+impl Default for Linetypes {
+    fn default() -> Self {
+        Self {
+            linetypes: parse_lin(STANDARD_LIN).expect("встроенные типы линий"),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Linetypes {
+    pub fn get(&self, name: &str) -> Option<&Linetype> {
+        self.linetypes.iter().find(|linetype| linetype.name == name)
+    }
+
+    /// Имя типа, следующего за `name`
+    pub fn next(&self, name: &str) -> String {
+        let index = self
+            .linetypes
+            .iter()
+            .position(|linetype| linetype.name == name)
+            .map_or(0, |index| (index + 1) % self.linetypes.len());
+        self.linetypes[index].name.clone()
+    }
+
+    /// Новое определение заменяет одноименное
+    pub fn define(&mut self, linetype: Linetype) {
+        match self
+            .linetypes
+            .iter_mut()
+            .find(|existing| existing.name == linetype.name)
+        {
+            Some(existing) => *existing = linetype,
+            None => self.linetypes.push(linetype),
+        }
+    }
+}
+
+/// Система обработки событий типов линий
+pub fn handle_linetype_events(
+    mut linetype_events: EventReader<LinetypeEvent>,
+    mut linetypes: ResMut<Linetypes>,
+) {
+    for event in linetype_events.read() {
+        match event {
+            LinetypeEvent::SetScale(scale) => linetypes.scale = *scale,
+            LinetypeEvent::MultiplyScale(factor) => linetypes.scale *= factor,
+            LinetypeEvent::Define(definitions) => {
+                for linetype in definitions {
+                    info!("Linetype defined: {}", linetype.name);
+                    linetypes.define(linetype.clone());
+                }
+            }
+            LinetypeEvent::Export => {
+                let path = beside_executable(LINETYPES_FILE);
+                match fs::write(&path, write_lin(&linetypes.linetypes)) {
+                    Ok(()) => info!("Linetypes saved to '{}'", path.display()),
+                    Err(error) => {
+                        warn!(
+                            "Failed to save linetypes to '{}': {}",
+                            path.display(),
+                            error
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Поля общего масштаба и определений типов линий в формате .lin
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    if input.kind == InputFieldKind::LinetypeScale {
+        let scale = input.evaluate(input.text)?;
+        if scale <= 0.0 {
+            return Err("масштаб должен быть положительным".to_string());
+        }
+        input.events.linetype.write(LinetypeEvent::SetScale(scale));
+        return Ok(format!("масштаб {}", scale));
+    }
+    let definitions = parse_lin(input.text)?;
+    if definitions.is_empty() {
+        return Err("нет определений".to_string());
+    }
+    let names: Vec<_> = definitions
+        .iter()
+        .map(|linetype| linetype.name.clone())
+        .collect();
+    input
+        .events
+        .linetype
+        .write(LinetypeEvent::Define(definitions));
+    Ok(format!("определено: {}", names.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_are_parsed_and_repeated() {
+        let linetypes = Linetypes::default();
+        let center = linetypes.get("Штрихпунктирная").unwrap();
+        assert_eq!(center.pattern, [1.25, -0.25, 0.25, -0.25]);
+        assert!(linetypes.get(CONTINUOUS).unwrap().pattern.is_empty());
+
+        // Узор продолжается с того же места; последний штрих обрезается концом
+        let dashes = center.dashes(5.0, 1.0);
+        assert_eq!(
+            dashes,
+            [
+                (0.0, 1.25),
+                (1.5, 1.75),
+                (2.0, 3.25),
+                (3.5, 3.75),
+                (4.0, 5.0)
+            ]
+        );
+        // Масштаб 2 удлиняет повторение до 4, короткий отрезок остается сплошным
+        assert_eq!(center.dashes(3.0, 2.0), [(0.0, 3.0)]);
+
+        let defined = parse_lin("*Осевая тонкая,ось;A,2,-0.5,0,-0.5").unwrap();
+        assert_eq!(defined[0].name, "Осевая тонкая");
+        assert_eq!(defined[0].pattern, [2.0, -0.5, 0.0, -0.5]);
+        assert!(parse_lin("*Без узора").is_err());
+        assert!(parse_lin("*Пробел;A,-1,1").is_err());
+        assert!(parse_lin("A,1,-1").is_err());
+    }
+
+    #[test]
+    fn lin_export_round_trips() {
+        let mut linetypes = Linetypes::default();
+        linetypes.define(parse_lin("*Граница,участок — · —;A,2.5,-0.5,0,-0.5").unwrap()[0].clone());
+        let text = write_lin(&linetypes.linetypes);
+        assert!(text.contains("*Граница,участок — · —\nA,2.5,-0.5,0,-0.5\n"));
+        assert!(text.contains("*Сплошная,Сплошная ________________\nA\n"));
+        assert_eq!(parse_lin(&text).unwrap(), linetypes.linetypes);
+    }
+
+    #[test]
+    fn pattern_continues_across_polyline_vertices() {
+        let dashed = Linetypes::default().get("Штриховая").unwrap().clone();
+        // Период 0.75: штрих 0.5 с 0.75 переходит излом на 1.0
+        let pieces = dashed.path_dashes(&[1.0, 1.0], 1.0);
+        assert_eq!(
+            pieces,
+            [(0, 0.0, 0.5), (0, 0.75, 1.0), (1, 0.0, 0.25), (1, 0.5, 1.0)]
+        );
+        // Дуга из мелких отрезков дает те же штрихи, что и прямая той же длины
+        let arc: Vec<(f64, f64)> = dashed
+            .path_dashes(&[0.3; 10], 1.0)
+            .into_iter()
+            .map(|(index, from, to)| (index as f64 * 0.3 + from, index as f64 * 0.3 + to))
+            .collect();
+        let mut joined: Vec<(f64, f64)> = Vec::new();
+        for (from, to) in arc {
+            match joined.last_mut() {
+                Some(last) if (last.1 - from).abs() < 1e-9 => last.1 = to,
+                _ => joined.push((from, to)),
+            }
+        }
+        let straight = dashed.dashes(3.0, 1.0);
+        assert_eq!(joined.len(), straight.len());
+        for (a, b) in joined.iter().zip(&straight) {
+            assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9);
+        }
+
+        let dotted = Linetypes::default().get("Пунктирная").unwrap().clone();
+        // Точка на изломе достается первому отрезку
+        assert_eq!(
+            dotted.path_dashes(&[0.5, 0.5], 1.0),
+            [
+                (0, 0.0, 0.0),
+                (0, 0.25, 0.25),
+                (0, 0.5, 0.5),
+                (1, 0.25, 0.25)
+            ]
+        );
+    }
+}
@@ -11,46 +11,121 @@ use bevy::{
 };
 
 // Импорты модулей
+mod attributes;
+mod blocks;
 mod camera_system;
 mod coordinate_systems;
+mod dimension_style;
+mod dimensions;
 mod events;
+mod expression;
 mod font_resource; // НОВЫЙ модуль для шрифта
 mod grid;
+mod groups;
+mod input_field;
+mod keybindings;
+mod layers;
+mod leaders;
 mod line_drawing;
+mod linetypes;
 mod orbit_camera;
+mod properties;
 mod render;
+mod render_origin;
+mod selection;
+mod snapping;
+mod tracking;
+mod transform;
+mod ucs;
 mod ui_panels;
 
 // Используем события из централизованного модуля
 use events::*;
 use font_resource::{setup_global_font}; // ИМПОРТ глобального шрифта
 
+use attributes::{handle_attribute_events, update_attribute_labels_system, AttributeTool};
+use blocks::{
+    block_pick_system, explode_blocks_system, handle_block_events, update_block_meshes_system,
+    BlockTool, Blocks,
+};
 use camera_system::{
     camera_drag_pan_system, camera_scroll_zoom_system, center_camera_on_lines_system,
-    cursor_coordinates_system, handle_camera_reset_events, handle_camera_toggle_events, CameraZoom,
+    cursor_coordinates_system, handle_camera_reset_events, handle_camera_toggle_events,
+    keyboard_camera_system, CameraZoom,
 };
 use coordinate_systems::{
     handle_coordinate_system_events, keyboard_coordinate_system, CoordinateSettings,
 };
+use dimension_style::{handle_dimension_style_events, DimensionStyles};
+use dimensions::{
+    dimension_pick_system, draw_dimensions_system, handle_dimension_events,
+    update_dimension_associations_system, update_dimension_labels_system, DimensionTool,
+};
+use expression::Variables;
 use grid::{setup_grid, toggle_grid_visibility, update_grid_system, GridSettings};
+use groups::{assign_ids_system, handle_group_events, prune_groups_system, Groups};
+use input_field::{
+    focus_input_fields_system, text_input_system, update_input_fields_system, TextInputFocus,
+};
+use keybindings::{
+    capture_rebind_system, handle_keybinding_events, keybindings_path, KeyBindingEditor,
+    KeyBindings,
+};
+use layers::{apply_layers_system, assign_current_layer_system, handle_layer_events, Layers};
+use leaders::{
+    draw_leaders_system, handle_leader_events, leader_pick_system, update_leader_blocks_system,
+    update_leader_labels_system, LeaderTool,
+};
 use line_drawing::{
     clear_lines_system, handle_line_events, line_drawing_system, line_info_system,
     toggle_line_drawing_system, LineDrawingState, LineSettings,
 };
+use linetypes::{handle_linetype_events, Linetypes};
 use orbit_camera::{
     orbit_camera_system, orbit_camera_zoom_system, reset_orbit_camera_system,
     toggle_orbit_mode_system, OrbitCamera, OrbitCenter,
 };
+use properties::{
+    apply_styles_system, handle_property_events, match_properties_system, PropertyTool,
+};
 use render::{
     display_render_info_system, save_render_settings_system, toggle_lighting_system,
     toggle_render_mode_system, update_materials_system, RenderModes,
 };
+use render_origin::{
+    apply_render_origin_system, rebase_render_origin_system, DocumentPosition, RenderOrigin,
+};
+use selection::{
+    draw_selection_system, handle_selection_events, prune_selection_system, selection_click_system,
+    Selection,
+};
+use snapping::{
+    draw_snap_marker_system, grid_snap_system, handle_grid_snap_events, handle_snap_toggle_events,
+    object_snap_system, toggle_grid_snap_system, update_snap_aperture_system, GridSnapSettings,
+    SnapSettings, SnapState,
+};
+use tracking::{
+    acquire_tracking_points_system, draw_tracking_system, handle_tracking_events,
+    keyboard_tracking_system, tracking_system, TrackingAcquisition, TrackingSettings,
+};
+use transform::{
+    align_pick_system, draw_align_pick_system, handle_transform_events, TransformTool,
+};
+use ucs::{draw_ucs_icon_system, handle_ucs_events, ucs_pick_system, UcsManager};
 use ui_panels::{
-    handle_ui_interactions, setup_ui_panels, track_cursor_position, update_cursor_coordinates,
-    update_line_info, CursorInfo,
+    handle_ui_interactions, scroll_settings_panel, setup_ui_panels, track_cursor_position,
+    update_attribute_display, update_block_display, update_cursor_coordinates,
+    update_dimension_display, update_dimension_style_display, update_georeference_display,
+    update_grid_snap_display, update_group_display, update_hotkey_labels,
+    update_keybinding_display, update_layer_display, update_leader_display, update_line_info,
+    update_linetype_display, update_properties_display, update_snap_buttons,
+    update_tracking_display, update_tracking_tooltip, update_transform_display, update_ucs_display,
+    update_units_display, CursorInfo,
 };
 
-#[derive(Component)]
+/// Постоянный номер объекта документа, по которому на него ссылаются группы.
+/// Номера не переиспользуются и не привязаны к сущностям сцены.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Id(pub u32);
 
 #[derive(Resource)]
@@ -99,11 +174,31 @@ fn main() {
         .add_event::<CameraResetEvent>()
         .add_event::<CoordinateSystemChangeEvent>()
         .add_event::<AngleUnitChangeEvent>()
+        .add_event::<AngleOrientationEvent>()
+        .add_event::<UnitsEvent>()
+        .add_event::<GeoreferenceEvent>()
         .add_event::<CreateLineEvent>()
         .add_event::<DeleteAllLinesEvent>()
         .add_event::<LineCreatedEvent>()
         .add_event::<PointSelectedEvent>()
+        .add_event::<TypedPointEvent>()
         .add_event::<GridStepChangeEvent>()
+        .add_event::<ObjectSnapToggleEvent>()
+        .add_event::<GridSnapEvent>()
+        .add_event::<TrackingEvent>()
+        .add_event::<KeyBindingEvent>()
+        .add_event::<UcsEvent>()
+        .add_event::<SelectionEvent>()
+        .add_event::<TransformEvent>()
+        .add_event::<DimensionEvent>()
+        .add_event::<DimensionStyleEvent>()
+        .add_event::<LeaderEvent>()
+        .add_event::<LayerEvent>()
+        .add_event::<PropertyEvent>()
+        .add_event::<LinetypeEvent>()
+        .add_event::<BlockEvent>()
+        .add_event::<AttributeEvent>()
+        .add_event::<GroupEvent>()
         // Ресурсы
         .insert_resource(WinitSettings::desktop_app())
         .insert_resource(OrbitCenter::default())
@@ -116,18 +211,52 @@ fn main() {
         .insert_resource(CameraZoom::default())
         .insert_resource(CoordinateSettings::default())
         .insert_resource(CursorInfo::default())
+        .insert_resource(SnapSettings::default())
+        .insert_resource(SnapState::default())
+        .insert_resource(GridSnapSettings::default())
+        .insert_resource(TrackingSettings::default())
+        .insert_resource(TrackingAcquisition::default())
+        .insert_resource(Variables::default())
+        .insert_resource(TextInputFocus::default())
+        .insert_resource(KeyBindings::load(&keybindings_path()))
+        .insert_resource(KeyBindingEditor::default())
+        .insert_resource(UcsManager::default())
+        .insert_resource(RenderOrigin::default())
+        .insert_resource(Selection::default())
+        .insert_resource(TransformTool::default())
+        .insert_resource(DimensionTool::default())
+        .insert_resource(DimensionStyles::default())
+        .insert_resource(LeaderTool::default())
+        .insert_resource(Layers::default())
+        .insert_resource(PropertyTool::default())
+        .insert_resource(Linetypes::default())
+        .insert_resource(Blocks::default())
+        .insert_resource(BlockTool::default())
+        .insert_resource(AttributeTool::default())
+        .insert_resource(Groups::default())
         // ДОБАВЛЯЕМ setup_global_font в Startup системы
         .add_systems(
             Startup,
             ((setup_global_font, setup, setup_grid, setup_ui_panels).chain(),),
         )
         // Update системы - хорошая организация для Bevy 0.15+
+        .add_systems(
+            Update,
+            (
+                // === ТЕКСТОВЫЙ ВВОД И ПЕРЕНАЗНАЧЕНИЕ (перед горячими клавишами) ===
+                focus_input_fields_system,
+                text_input_system,
+                capture_rebind_system,
+            )
+                .chain(),
+        )
         .add_systems(
             Update,
             (
                 // === ВВОД И ГЕНЕРАЦИЯ СОБЫТИЙ ===
                 toggle_orbit_mode_system,
                 reset_orbit_camera_system,
+                keyboard_camera_system,
                 orbit_camera_zoom_system,
                 toggle_render_mode_system,
                 display_render_info_system,
@@ -136,9 +265,13 @@ fn main() {
                 clear_lines_system,
                 line_info_system,
                 keyboard_coordinate_system,
-                handle_ui_interactions,
-            ),
+                toggle_grid_snap_system,
+                keyboard_tracking_system,
+                // Пока поле ввода в фокусе, ActionInput не сообщает о нажатиях
+            )
+                .after(capture_rebind_system),
         )
+        .add_systems(Update, handle_ui_interactions)
         .add_systems(
             Update,
             (
@@ -148,6 +281,35 @@ fn main() {
                 handle_coordinate_system_events,
                 handle_line_events,
                 grid::handle_grid_step_events,
+                handle_snap_toggle_events,
+                handle_grid_snap_events,
+                handle_tracking_events,
+                handle_keybinding_events,
+                handle_ucs_events,
+                handle_selection_events,
+                handle_transform_events,
+                prune_selection_system,
+                handle_dimension_events,
+                handle_dimension_style_events,
+                update_dimension_associations_system,
+                handle_leader_events,
+                handle_block_events,
+                handle_attribute_events,
+                (
+                    handle_layer_events,
+                    handle_linetype_events,
+                    handle_property_events,
+                    explode_blocks_system,
+                    assign_ids_system,
+                    handle_group_events,
+                    prune_groups_system,
+                    update_leader_blocks_system,
+                    assign_current_layer_system,
+                    apply_layers_system,
+                    apply_styles_system,
+                    update_block_meshes_system,
+                )
+                    .chain(),
             ),
         )
         .add_systems(
@@ -159,20 +321,72 @@ fn main() {
                 camera_scroll_zoom_system,
                 cursor_coordinates_system,
                 center_camera_on_lines_system,
-                track_cursor_position,
-                line_drawing_system,
-            ),
+                // Начало сцены переносится до пересчета курсора в координаты документа
+                rebase_render_origin_system,
+                // Курсор -> привязка -> команды ввода точек
+                (
+                    track_cursor_position,
+                    update_snap_aperture_system,
+                    object_snap_system,
+                    grid_snap_system,
+                    acquire_tracking_points_system,
+                    tracking_system,
+                    line_drawing_system,
+                    ucs_pick_system,
+                    align_pick_system,
+                    dimension_pick_system,
+                    leader_pick_system,
+                    block_pick_system,
+                    match_properties_system,
+                    selection_click_system,
+                )
+                    .chain(),
+            )
+                .after(capture_rebind_system),
         )
         .add_systems(
             Update,
             (
                 // === РЕНДЕРИНГ И UI ===
+                apply_render_origin_system,
                 update_grid_system,
                 toggle_grid_visibility,
                 update_materials_system,
                 toggle_lighting_system,
                 update_cursor_coordinates,
                 update_line_info,
+                update_snap_buttons,
+                update_units_display,
+                update_georeference_display,
+                update_grid_snap_display,
+                update_tracking_display,
+                update_tracking_tooltip,
+                update_input_fields_system,
+                (update_keybinding_display, update_hotkey_labels),
+                update_ucs_display,
+                update_transform_display,
+                scroll_settings_panel,
+                (
+                    draw_snap_marker_system,
+                    draw_tracking_system,
+                    draw_ucs_icon_system,
+                    draw_selection_system,
+                    draw_align_pick_system,
+                    draw_dimensions_system,
+                    update_dimension_labels_system,
+                    update_dimension_display,
+                    update_dimension_style_display,
+                    draw_leaders_system,
+                    update_leader_labels_system,
+                    update_leader_display,
+                    update_layer_display,
+                    update_properties_display,
+                    update_linetype_display,
+                    update_block_display,
+                    update_attribute_display,
+                    update_group_display,
+                    update_attribute_labels_system,
+                ),
             ),
         )
         .run();
@@ -196,6 +410,7 @@ fn setup(
         Mesh3d(meshes.add(Circle::new(4.0))),
         MeshMaterial3d(base_material),
         Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+        DocumentPosition(bevy::math::DVec3::ZERO),
         Name::new("GroundPlane"),
         // Transform и Visibility добавляются автоматически через Required Components
     ));
@@ -211,6 +426,7 @@ fn setup(
             ..default()
         },
         Transform::from_xyz(4.0, 8.0, 4.0),
+        DocumentPosition(bevy::math::DVec3::new(4.0, 8.0, 4.0)),
         Name::new("MainLight"),
         // Visibility добавляется автоматически
     ));
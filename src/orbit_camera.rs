@@ -1,5 +1,7 @@
 use bevy::{input::mouse::MouseMotion, prelude::*};
 
+use crate::keybindings::{ActionInput, InputAction};
+
 #[derive(Resource)]
 pub struct OrbitCenter {
     pub position: Vec3,
@@ -58,6 +60,7 @@ pub fn orbit_camera_system(
     mut orbit: ResMut<OrbitCamera>,
     orbit_center: Res<OrbitCenter>,
     mut mouse_motion_events: EventReader<MouseMotion>,
+    input: ActionInput,
     mut query: Query<&mut Transform, With<Camera3d>>,
 ) {
     // Ранний выход если режим выключен
@@ -76,8 +79,12 @@ pub fn orbit_camera_system(
         has_movement = true;
     }
 
+    // Если вращению назначена кнопка, орбита вращается только при ее нажатии
+    let rotating =
+        !input.is_bound(InputAction::OrbitRotate) || input.pressed(InputAction::OrbitRotate);
+
     // Обновляем углы только если есть движение
-    if has_movement {
+    if has_movement && rotating {
         orbit.azimuth -= delta.x * orbit.sensitivity;
         orbit.elevation += delta.y * orbit.sensitivity;
 
@@ -106,12 +113,9 @@ pub fn orbit_camera_system(
 }
 
 // Оптимизированная система переключения режима орбиты
-pub fn toggle_orbit_mode_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut orbit: ResMut<OrbitCamera>,
-) {
+pub fn toggle_orbit_mode_system(input: ActionInput, mut orbit: ResMut<OrbitCamera>) {
     // Используем just_pressed для предотвращения множественных срабатываний
-    if keyboard_input.just_pressed(KeyCode::KeyC) {
+    if input.just_pressed(InputAction::ToggleOrbitCamera) {
         orbit.enabled = !orbit.enabled;
 
         // Сбрасываем кэш при переключении режима
@@ -126,11 +130,8 @@ pub fn toggle_orbit_mode_system(
 }
 
 // Дополнительная система для сброса камеры
-pub fn reset_orbit_camera_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut orbit: ResMut<OrbitCamera>,
-) {
-    if keyboard_input.just_pressed(KeyCode::KeyR) {
+pub fn reset_orbit_camera_system(input: ActionInput, mut orbit: ResMut<OrbitCamera>) {
+    if input.just_pressed(InputAction::ResetOrbitCamera) {
         orbit.azimuth = 0.0;
         orbit.elevation = 0.0;
         orbit.radius = 10.0;
@@ -0,0 +1,361 @@
+use bevy::prelude::*;
+
+use crate::{
+    events::PropertyEvent,
+    keybindings::{ActionInput, InputAction},
+    layers::{
+        next_transparency, step_lineweight, Layer, LayerId, Layers, DEFAULT_LINEWEIGHT, PALETTE,
+    },
+    line_drawing::{self, DrawableLine, LineSettings},
+    linetypes::{self, Linetypes},
+    selection::{line_under_cursor, ActiveCommandsMut, Selection},
+    snapping::SnapSettings,
+    ui_panels::CursorInfo,
+};
+
+/// Свойство объекта: берется у слоя или задано самому объекту
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum Property<T> {
+    #[default]
+    ByLayer,
+    Value(T),
+}
+
+impl<T: Clone> Property<T> {
+    pub fn resolve(&self, layer_value: T) -> T {
+        match self {
+            Property::ByLayer => layer_value,
+            Property::Value(value) => value.clone(),
+        }
+    }
+}
+
+/// Собственные свойства отображения объекта. По умолчанию все берутся у слоя.
+#[derive(Component, Clone, PartialEq, Debug, Default)]
+pub struct EntityStyle {
+    /// Индекс в `PALETTE`
+    pub color: Property<usize>,
+    pub linetype: Property<String>,
+    /// Толщина, мм
+    pub lineweight: Property<f32>,
+    pub transparency: Property<f32>,
+}
+
+/// Свойства объекта после подстановки значений слоя
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResolvedStyle {
+    pub color: usize,
+    pub linetype: String,
+    pub lineweight: f32,
+    pub transparency: f32,
+}
+
+impl ResolvedStyle {
+    /// Цвет с учетом прозрачности
+    pub fn color(&self) -> Color {
+        PALETTE[self.color % PALETTE.len()]
+            .1
+            .with_alpha(1.0 - self.transparency)
+    }
+}
+
+impl EntityStyle {
+    pub fn resolve(&self, layer: &Layer) -> ResolvedStyle {
+        ResolvedStyle {
+            color: self.color.resolve(layer.color),
+            linetype: self.linetype.resolve(layer.linetype.clone()),
+            lineweight: self.lineweight.resolve(layer.lineweight),
+            transparency: self.transparency.resolve(layer.transparency),
+        }
+    }
+}
+
+/// Указание образца и объектов для копирования свойств
+#[derive(Clone, Debug)]
+pub struct MatchPick {
+    /// Объект-образец; пока не выбран, щелчок указывает его
+    pub source: Option<Entity>,
+    /// Клик по кнопке, начавший указание, не должен стать выбором
+    armed: bool,
+}
+
+impl MatchPick {
+    pub fn prompt(&self) -> &'static str {
+        match self.source {
+            None => "Копирование свойств: выберите образец",
+            Some(_) => "Копирование свойств: выберите объекты, Esc — завершить",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PropertyTool {
+    pub matching: Option<MatchPick>,
+}
+
+impl PropertyTool {
+    pub fn is_picking(&self) -> bool {
+        self.matching.is_some()
+    }
+}
+
+/// Правка свойств выделенных объектов.
+/// Перебор значений начинается от значения первого выделенного объекта.
+pub fn handle_property_events(
+    mut property_events: EventReader<PropertyEvent>,
+    selection: Res<Selection>,
+    layers: Res<Layers>,
+    linetypes: Res<Linetypes>,
+    mut style_query: Query<(&mut EntityStyle, &LayerId)>,
+    mut active: ActiveCommandsMut,
+) {
+    for event in property_events.read() {
+        match event {
+            PropertyEvent::StartMatch => {
+                // Новая команда прерывает рисование и другие указания точек
+                active.cancel_all();
+                active.property_tool.matching = Some(MatchPick {
+                    source: None,
+                    armed: false,
+                });
+                continue;
+            }
+            PropertyEvent::CancelMatch => {
+                active.property_tool.matching = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(first) = selection
+            .entities
+            .first()
+            .and_then(|entity| style_query.get(*entity).ok())
+            .map(|(style, layer)| style.resolve(layers.get(*layer)))
+        else {
+            warn!("Nothing selected");
+            continue;
+        };
+        for entity in &selection.entities {
+            let Ok((mut style, _)) = style_query.get_mut(*entity) else {
+                continue;
+            };
+            match event {
+                PropertyEvent::ColorByLayer => style.color = Property::ByLayer,
+                PropertyEvent::CycleColor => {
+                    style.color = Property::Value((first.color + 1) % PALETTE.len());
+                }
+                PropertyEvent::LinetypeByLayer => style.linetype = Property::ByLayer,
+                PropertyEvent::CycleLinetype => {
+                    style.linetype = Property::Value(linetypes.next(&first.linetype));
+                }
+                PropertyEvent::LineweightByLayer => style.lineweight = Property::ByLayer,
+                PropertyEvent::Thicker => {
+                    style.lineweight = Property::Value(step_lineweight(first.lineweight, 1));
+                }
+                PropertyEvent::Thinner => {
+                    style.lineweight = Property::Value(step_lineweight(first.lineweight, -1));
+                }
+                PropertyEvent::TransparencyByLayer => style.transparency = Property::ByLayer,
+                PropertyEvent::CycleTransparency => {
+                    style.transparency = Property::Value(next_transparency(first.transparency));
+                }
+                PropertyEvent::StartMatch | PropertyEvent::CancelMatch => {}
+            }
+        }
+    }
+}
+
+/// Копирование свойств: щелчок по образцу, затем по объектам, которым они передаются
+pub fn match_properties_system(
+    input: ActionInput,
+    cursor_info: Res<CursorInfo>,
+    snap_settings: Res<SnapSettings>,
+    layers: Res<Layers>,
+    line_query: Query<(Entity, &DrawableLine, &LayerId)>,
+    mut style_query: Query<&mut EntityStyle>,
+    mut tool: ResMut<PropertyTool>,
+) {
+    let Some(mut pick) = tool.matching.clone() else {
+        return;
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        tool.matching = None;
+        info!("Match properties finished");
+        return;
+    }
+    if !pick.armed {
+        pick.armed = true;
+        tool.matching = Some(pick);
+        return;
+    }
+    if !input.just_pressed(InputAction::PickPoint) {
+        return;
+    }
+    let Some(cursor) = cursor_info.world_position.filter(|_| cursor_info.is_valid) else {
+        return;
+    };
+
+    // Образцом может быть любой видимый объект, менять можно только доступные для правки
+    let source = pick.source;
+    let lines = line_query
+        .iter()
+        .filter(|(_, _, layer)| {
+            if source.is_none() {
+                layers.is_visible(**layer)
+            } else {
+                layers.is_editable(**layer)
+            }
+        })
+        .map(|(entity, line, _)| (entity, line));
+    let Some(entity) = line_under_cursor(cursor, lines, snap_settings.aperture as f64) else {
+        warn!("No line under the picked point");
+        return;
+    };
+    match source.and_then(|source| style_query.get(source).ok().cloned()) {
+        None => pick.source = Some(entity),
+        Some(source_style) => {
+            if let Ok(mut style) = style_query.get_mut(entity) {
+                *style = source_style;
+            }
+        }
+    }
+    tool.matching = Some(pick);
+}
+
+/// Свойства, по которым построены материал, масштаб и меш отрезка
+#[derive(Component)]
+pub struct AppliedStyle(ResolvedStyle);
+
+/// Цвет, прозрачность, толщина и тип линий отрезков по их свойствам и слоям.
+/// Материал создается по свойствам при первом применении; меш отрезка
+/// перестраивается по узору, только если сменился тип линии или сам отрезок.
+pub fn apply_styles_system(
+    mut commands: Commands,
+    layers: Res<Layers>,
+    linetypes: Res<Linetypes>,
+    line_settings: Res<LineSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut line_query: Query<(
+        Entity,
+        Ref<DrawableLine>,
+        Ref<LayerId>,
+        Ref<EntityStyle>,
+        &Mesh3d,
+        &mut Transform,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&mut AppliedStyle>,
+    )>,
+) {
+    for (entity, line, layer_id, style, mesh, mut transform, material, applied) in
+        line_query.iter_mut()
+    {
+        let restyled = layers.is_changed() || layer_id.is_changed() || style.is_changed();
+        if applied.is_some() && !restyled && !linetypes.is_changed() && !line.is_changed() {
+            continue;
+        }
+        let resolved = style.resolve(layers.get(*layer_id));
+        let previous = applied.as_ref().map(|applied| &applied.0);
+        let recolored = previous.is_none_or(|previous| {
+            (previous.color, previous.transparency) != (resolved.color, resolved.transparency)
+        });
+        let reweighted = previous.is_none_or(|previous| previous.lineweight != resolved.lineweight);
+        let remeshed = line.is_changed()
+            || linetypes.is_changed()
+            || previous.is_none_or(|previous| previous.linetype != resolved.linetype);
+
+        if recolored {
+            let alpha_mode = if resolved.transparency > 0.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            };
+            match material.and_then(|material| materials.get_mut(&material.0)) {
+                Some(material) => {
+                    material.base_color = resolved.color();
+                    material.alpha_mode = alpha_mode;
+                }
+                None => {
+                    let material = materials.add(StandardMaterial {
+                        base_color: resolved.color(),
+                        alpha_mode,
+                        unlit: true,
+                        ..default()
+                    });
+                    commands.entity(entity).insert(MeshMaterial3d(material));
+                }
+            }
+        }
+        if reweighted {
+            // Меш построен с толщиной по умолчанию, толщина объекта задается масштабом
+            let width = resolved.lineweight / DEFAULT_LINEWEIGHT;
+            transform.scale = Vec3::new(width, 1.0, width);
+        }
+        if remeshed {
+            // Меш отрезка принадлежит только ему и заменяется под той же ручкой
+            let (_, _, length) = line_drawing::line_placement(line.start, line.end);
+            meshes.insert(
+                &mesh.0,
+                linetypes::line_mesh(
+                    length,
+                    line_settings.line_thickness,
+                    linetypes.get(&resolved.linetype),
+                    linetypes.scale,
+                ),
+            );
+        }
+
+        match applied {
+            Some(mut applied) => applied.0 = resolved,
+            None => {
+                commands.entity(entity).insert(AppliedStyle(resolved));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_style_overrides_layer_properties() {
+        let mut layer = Layer::new("Оси");
+        layer.color = 2;
+        layer.linetype = "DASHED".to_string();
+        layer.lineweight = 0.5;
+        layer.transparency = 0.25;
+
+        // По умолчанию все свойства берутся у слоя
+        let by_layer = EntityStyle::default().resolve(&layer);
+        assert_eq!(
+            by_layer,
+            ResolvedStyle {
+                color: 2,
+                linetype: "DASHED".to_string(),
+                lineweight: 0.5,
+                transparency: 0.25,
+            }
+        );
+        assert_eq!(by_layer.color(), PALETTE[2].1.with_alpha(0.75));
+
+        let style = EntityStyle {
+            color: Property::Value(4),
+            lineweight: Property::Value(1.0),
+            ..default()
+        };
+        let resolved = style.resolve(&layer);
+        assert_eq!(resolved.color, 4);
+        assert_eq!(resolved.linetype, "DASHED");
+        assert_eq!(resolved.lineweight, 1.0);
+        assert_eq!(resolved.transparency, 0.25);
+
+        // Смена свойств слоя видна только у свойств «по слою»
+        layer.color = 0;
+        layer.lineweight = DEFAULT_LINEWEIGHT;
+        let resolved = style.resolve(&layer);
+        assert_eq!((resolved.color, resolved.lineweight), (4, 1.0));
+        assert_eq!(Property::<usize>::ByLayer.resolve(layer.color), 0);
+    }
+}
@@ -1,15 +1,17 @@
 use bevy::{pbr::wireframe::WireframeConfig, prelude::*};
 
+use crate::keybindings::{ActionInput, InputAction};
+
 // Оптимизированная система переключения режимов рендеринга
 pub fn toggle_render_mode_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut render_modes: ResMut<RenderModes>,
     mut wireframe_config: ResMut<WireframeConfig>,
 ) {
     let mut changed = false;
 
     // F1 - переключение wireframe
-    if keyboard_input.just_pressed(KeyCode::F1) {
+    if input.just_pressed(InputAction::ToggleWireframe) {
         render_modes.wireframe_mode = !render_modes.wireframe_mode;
         wireframe_config.global = render_modes.wireframe_mode;
         info!("Wireframe mode: {}", render_modes.wireframe_mode);
@@ -17,21 +19,21 @@ pub fn toggle_render_mode_system(
     }
 
     // F2 - переключение освещения
-    if keyboard_input.just_pressed(KeyCode::F2) {
+    if input.just_pressed(InputAction::ToggleLighting) {
         render_modes.lighting_enabled = !render_modes.lighting_enabled;
         info!("Lighting: {}", render_modes.lighting_enabled);
         changed = true;
     }
 
     // F3 - переключение теней
-    if keyboard_input.just_pressed(KeyCode::F3) {
+    if input.just_pressed(InputAction::ToggleShadows) {
         render_modes.shadows_enabled = !render_modes.shadows_enabled;
         info!("Shadows: {}", render_modes.shadows_enabled);
         changed = true;
     }
 
     // F4 - переключение сетки
-    if keyboard_input.just_pressed(KeyCode::F4) {
+    if input.just_pressed(InputAction::ToggleGrid) {
         render_modes.grid_visible = !render_modes.grid_visible;
         info!("Grid visible: {}", render_modes.grid_visible);
         changed = true;
@@ -144,21 +146,15 @@ impl RenderModes {
 }
 
 // Дополнительная система для отображения информации о режимах рендеринга
-pub fn display_render_info_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    render_modes: Res<RenderModes>,
-) {
-    if keyboard_input.just_pressed(KeyCode::F5) {
+pub fn display_render_info_system(input: ActionInput, render_modes: Res<RenderModes>) {
+    if input.just_pressed(InputAction::ShowRenderInfo) {
         info!("{}", render_modes.get_info());
     }
 }
 
 // Система для сохранения настроек рендеринга
-pub fn save_render_settings_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    render_modes: Res<RenderModes>,
-) {
-    if keyboard_input.just_pressed(KeyCode::F12) {
+pub fn save_render_settings_system(input: ActionInput, render_modes: Res<RenderModes>) {
+    if input.just_pressed(InputAction::SaveRenderSettings) {
         // В реальном приложении здесь можно сохранять настройки в файл
         info!("Saved render settings: {}", render_modes.get_info());
     }
@@ -0,0 +1,89 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::orbit_camera::OrbitCenter;
+
+/// Расстояние от начала сцены, после которого начало сцены переносится к камере.
+/// Пока координаты сцены меньше ~1000, f32 держит их с точностью лучше 0.0001.
+pub const REBASE_DISTANCE: f32 = 1000.0;
+
+/// Точка для тестов в метрах UTM: на таком удалении от начала координат
+/// f32 уже не различает миллиметры
+#[cfg(test)]
+pub const FAR_POINT: DVec3 = DVec3::new(500_000.0, 0.0, 6_100_000.0);
+
+/// Точка документа, совпадающая с началом координат сцены Bevy.
+///
+/// Геометрия документа хранится в f64 (`DVec3`), а сцена строится в f32
+/// относительно этой точки: так объекты с координатами порядка 500000
+/// отображаются без потери точности рядом с камерой.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct RenderOrigin {
+    pub origin: DVec3,
+}
+
+impl RenderOrigin {
+    /// Точка документа -> координаты сцены
+    pub fn to_render(self, point: DVec3) -> Vec3 {
+        (point - self.origin).as_vec3()
+    }
+
+    /// Координаты сцены -> точка документа
+    pub fn to_document(self, point: Vec3) -> DVec3 {
+        self.origin + point.as_dvec3()
+    }
+}
+
+/// Положение объекта в координатах документа; `Transform` вычисляется из него
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DocumentPosition(pub DVec3);
+
+/// Перенос начала сцены к камере, когда она уходит далеко от него.
+/// Камера и центр орбиты сдвигаются так, что вид на экране не меняется.
+pub fn rebase_render_origin_system(
+    mut render_origin: ResMut<RenderOrigin>,
+    mut orbit_center: ResMut<OrbitCenter>,
+    mut camera_query: Query<&mut Transform, With<Camera3d>>,
+) {
+    let Ok(mut camera) = camera_query.single_mut() else {
+        return;
+    };
+    // Переносим только в плоскости чертежа: высота сцены совпадает с высотой документа
+    let shift = Vec3::new(camera.translation.x, 0.0, camera.translation.z);
+    if shift.length() < REBASE_DISTANCE {
+        return;
+    }
+
+    render_origin.origin += shift.as_dvec3();
+    camera.translation -= shift;
+    orbit_center.position -= shift;
+    info!("Render origin moved to {:?}", render_origin.origin);
+}
+
+/// Обновление `Transform` объектов при переносе начала сцены или их перемещении
+pub fn apply_render_origin_system(
+    render_origin: Res<RenderOrigin>,
+    mut query: Query<(Ref<DocumentPosition>, &mut Transform)>,
+) {
+    for (position, mut transform) in query.iter_mut() {
+        if render_origin.is_changed() || position.is_changed() {
+            transform.translation = render_origin.to_render(position.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearby_points_survive_render_round_trip() {
+        let origin = RenderOrigin { origin: FAR_POINT };
+        for i in 0..1000 {
+            let offset = REBASE_DISTANCE as f64 * (i as f64 / 1000.0 - 0.5);
+            let point = origin.origin + DVec3::new(offset + 0.001, 1.5, -offset);
+            let back = origin.to_document(origin.to_render(point));
+            // f32 относительно начала сцены: лучше 0.1 мм в пределах REBASE_DISTANCE
+            assert!(back.abs_diff_eq(point, 1e-4), "{point} -> {back}");
+        }
+    }
+}
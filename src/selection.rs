@@ -0,0 +1,305 @@
+use bevy::{ecs::system::SystemParam, math::DVec3, prelude::*};
+
+use crate::{
+    attributes::{attribute_under_cursor, AttributeDefinition},
+    blocks::{reference_under_cursor, BlockReference, BlockTool, Blocks},
+    dimensions::DimensionTool,
+    events::SelectionEvent,
+    groups::{selectable_members, Groups},
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers},
+    leaders::{LeaderBlock, LeaderTool},
+    line_drawing::{DrawableLine, LineDrawingState},
+    properties::PropertyTool,
+    render_origin::RenderOrigin,
+    snapping::{candidates, SnapSettings},
+    tracking::draw_dashed_line,
+    transform::TransformTool,
+    ucs::UcsManager,
+    ui_panels::CursorInfo,
+    Id,
+};
+
+/// Выделенные объекты, к которым применяются команды редактирования
+#[derive(Resource, Default)]
+pub struct Selection {
+    pub entities: Vec<Entity>,
+}
+
+impl Selection {
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Добавить объект вместе с его группой к выделению или снять с них выделение
+    pub fn toggle_all(&mut self, entity: Entity, members: &[Entity]) {
+        if self.contains(entity) {
+            self.entities.retain(|selected| !members.contains(selected));
+        } else {
+            for member in members {
+                if !self.contains(*member) {
+                    self.entities.push(*member);
+                }
+            }
+        }
+    }
+}
+
+/// Рисование и команды, которые ждут указания точек.
+/// Пока одна из них активна, щелчок достается ей, а не выделению.
+#[derive(SystemParam)]
+pub struct ActiveCommands<'w> {
+    pub line_state: Res<'w, LineDrawingState>,
+    pub ucs_manager: Res<'w, UcsManager>,
+    pub transform_tool: Res<'w, TransformTool>,
+    pub dimension_tool: Res<'w, DimensionTool>,
+    pub leader_tool: Res<'w, LeaderTool>,
+    pub property_tool: Res<'w, PropertyTool>,
+    pub block_tool: Res<'w, BlockTool>,
+}
+
+/// Список команд указания точек один для `ActiveCommands` и `ActiveCommandsMut`
+fn any_picking(
+    ucs_manager: &UcsManager,
+    transform_tool: &TransformTool,
+    dimension_tool: &DimensionTool,
+    leader_tool: &LeaderTool,
+    property_tool: &PropertyTool,
+    block_tool: &BlockTool,
+) -> bool {
+    ucs_manager.is_picking()
+        || transform_tool.is_picking()
+        || dimension_tool.is_picking()
+        || leader_tool.is_picking()
+        || property_tool.is_picking()
+        || block_tool.is_picking()
+}
+
+impl ActiveCommands<'_> {
+    /// Идет указание точек какой-либо командой, кроме рисования
+    pub fn is_picking(&self) -> bool {
+        any_picking(
+            &self.ucs_manager,
+            &self.transform_tool,
+            &self.dimension_tool,
+            &self.leader_tool,
+            &self.property_tool,
+            &self.block_tool,
+        )
+    }
+}
+
+/// Те же команды с правом изменения: запуск одной из них прерывает остальные
+#[derive(SystemParam)]
+pub struct ActiveCommandsMut<'w> {
+    pub line_state: ResMut<'w, LineDrawingState>,
+    pub ucs_manager: ResMut<'w, UcsManager>,
+    pub transform_tool: ResMut<'w, TransformTool>,
+    pub dimension_tool: ResMut<'w, DimensionTool>,
+    pub leader_tool: ResMut<'w, LeaderTool>,
+    pub property_tool: ResMut<'w, PropertyTool>,
+    pub block_tool: ResMut<'w, BlockTool>,
+}
+
+impl ActiveCommandsMut<'_> {
+    /// Идет указание точек какой-либо командой, кроме рисования
+    pub fn is_picking(&self) -> bool {
+        any_picking(
+            &self.ucs_manager,
+            &self.transform_tool,
+            &self.dimension_tool,
+            &self.leader_tool,
+            &self.property_tool,
+            &self.block_tool,
+        )
+    }
+
+    /// Прервать рисование и все указания точек перед запуском новой команды
+    pub fn cancel_all(&mut self) {
+        self.line_state.stop();
+        self.ucs_manager.picking = None;
+        self.transform_tool.align = None;
+        self.dimension_tool.placing = None;
+        self.leader_tool.pick = None;
+        self.property_tool.matching = None;
+        self.block_tool.pick = None;
+    }
+}
+
+/// Отрезок под курсором: ближайший в пределах апертуры привязки
+pub fn line_under_cursor<'a>(
+    cursor: DVec3,
+    lines: impl Iterator<Item = (Entity, &'a DrawableLine)>,
+    aperture: f64,
+) -> Option<Entity> {
+    lines
+        .map(|(entity, line)| {
+            let closest = candidates::closest_on_segment(cursor, line.start, line.end);
+            let distance = (closest - cursor).with_y(0.0).length();
+            (entity, distance)
+        })
+        .filter(|(_, distance)| *distance <= aperture)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entity, _)| entity)
+}
+
+/// Выделение щелчком, пока не активна команда, которой нужны точки.
+/// Щелчок по отрезку, вставке блока или определению атрибута добавляет объект к выделению
+/// или снимает выделение (вместе с его группой, если не включен выбор по одному), Esc очищает.
+pub fn selection_click_system(
+    input: ActionInput,
+    cursor_info: Res<CursorInfo>,
+    snap_settings: Res<SnapSettings>,
+    active: ActiveCommands,
+    layers: Res<Layers>,
+    groups: Res<Groups>,
+    blocks: Res<Blocks>,
+    line_query: Query<(Entity, &DrawableLine, &LayerId)>,
+    reference_query: Query<(Entity, &BlockReference, &LayerId), Without<LeaderBlock>>,
+    definition_query: Query<(Entity, &AttributeDefinition, &LayerId)>,
+    object_query: Query<(Entity, &Id, Option<&LayerId>)>,
+    mut selection: ResMut<Selection>,
+) {
+    if active.line_state.is_enabled || active.is_picking() {
+        return;
+    }
+
+    if input.just_pressed(InputAction::Cancel) && !selection.entities.is_empty() {
+        selection.entities.clear();
+        info!("Selection cleared");
+        return;
+    }
+
+    if !input.just_pressed(InputAction::PickPoint) {
+        return;
+    }
+    let Some(cursor) = cursor_info.world_position.filter(|_| cursor_info.is_valid) else {
+        return;
+    };
+    // Объекты скрытых и заблокированных слоев не выделяются
+    let lines = line_query
+        .iter()
+        .filter(|(_, _, layer)| layers.is_editable(**layer))
+        .map(|(entity, line, _)| (entity, line));
+    let aperture = snap_settings.aperture as f64;
+    let references = reference_query
+        .iter()
+        .filter(|(_, _, layer)| layers.is_editable(**layer))
+        .map(|(entity, reference, _)| (entity, reference));
+    let definitions = definition_query
+        .iter()
+        .filter(|(_, _, layer)| layers.is_editable(**layer))
+        .map(|(entity, definition, _)| (entity, definition));
+    let found = attribute_under_cursor(cursor, definitions, aperture)
+        .or_else(|| line_under_cursor(cursor, lines, aperture))
+        .or_else(|| reference_under_cursor(cursor, references, &blocks, &layers, aperture));
+    if let Some(entity) = found {
+        let members = selectable_members(&groups, entity, &object_query, &layers);
+        selection.toggle_all(entity, &members);
+        info!("Selected objects: {}", selection.entities.len());
+    }
+}
+
+/// Система обработки событий выделения
+pub fn handle_selection_events(
+    mut selection_events: EventReader<SelectionEvent>,
+    layers: Res<Layers>,
+    line_query: Query<
+        (Entity, &LayerId),
+        (
+            Or<(
+                With<DrawableLine>,
+                With<BlockReference>,
+                With<AttributeDefinition>,
+            )>,
+            Without<LeaderBlock>,
+        ),
+    >,
+    mut selection: ResMut<Selection>,
+) {
+    for event in selection_events.read() {
+        match event {
+            SelectionEvent::SelectAll => {
+                selection.entities = line_query
+                    .iter()
+                    .filter(|(_, layer)| layers.is_editable(**layer))
+                    .map(|(entity, _)| entity)
+                    .collect();
+            }
+            SelectionEvent::Clear => selection.entities.clear(),
+        }
+        info!("Selected objects: {}", selection.entities.len());
+    }
+}
+
+/// Удаленные объекты и объекты скрытых или заблокированных слоев
+/// не должны оставаться в выделении
+pub fn prune_selection_system(
+    layers: Res<Layers>,
+    line_query: Query<
+        &LayerId,
+        Or<(
+            With<DrawableLine>,
+            With<BlockReference>,
+            With<AttributeDefinition>,
+        )>,
+    >,
+    mut selection: ResMut<Selection>,
+) {
+    let editable = |entity: &Entity| {
+        line_query
+            .get(*entity)
+            .is_ok_and(|layer| layers.is_editable(*layer))
+    };
+    if !selection.entities.iter().all(editable) {
+        selection.entities.retain(editable);
+    }
+}
+
+/// Подсветка выделенных отрезков и вставок блоков пунктиром,
+/// определений атрибутов — окружностью у точки подписи
+pub fn draw_selection_system(
+    selection: Res<Selection>,
+    render_origin: Res<RenderOrigin>,
+    blocks: Res<Blocks>,
+    layers: Res<Layers>,
+    line_query: Query<&DrawableLine>,
+    reference_query: Query<&BlockReference>,
+    definition_query: Query<&AttributeDefinition>,
+    mut gizmos: Gizmos,
+) {
+    let lift = Vec3::Y * 0.15;
+    let color = Color::srgb(0.4, 0.7, 1.0);
+    let mut segments = Vec::new();
+    for entity in &selection.entities {
+        if let Ok(line) = line_query.get(*entity) {
+            segments.push((line.start, line.end));
+        } else if let Ok(reference) = reference_query.get(*entity) {
+            segments.extend(
+                blocks
+                    .segments(reference, &layers)
+                    .into_iter()
+                    .map(|(start, end, _)| (start, end)),
+            );
+        } else if let Ok(definition) = definition_query.get(*entity) {
+            gizmos.circle(
+                Isometry3d::new(
+                    render_origin.to_render(definition.position) + lift,
+                    Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+                ),
+                definition.height as f32,
+                color,
+            );
+        }
+    }
+    for (start, end) in segments {
+        draw_dashed_line(
+            &mut gizmos,
+            render_origin.to_render(start) + lift,
+            render_origin.to_render(end) + lift,
+            0.2,
+            0.1,
+            color,
+        );
+    }
+}
@@ -0,0 +1,745 @@
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    blocks::{BlockReference, Blocks},
+    coordinate_systems::{CartesianPoint, CoordinateSettings, CoordinateSystem, PolarPoint},
+    events::{GridSnapEvent, ObjectSnapToggleEvent},
+    grid::GridSettings,
+    input_field::FieldInput,
+    keybindings::{ActionInput, InputAction},
+    layers::{LayerId, Layers},
+    line_drawing::{self, DrawableLine, LineDrawingState},
+    render_origin::RenderOrigin,
+    tracking::TrackingPoint,
+    ucs::{CoordinateFrame, UcsManager},
+    ui_panels::CursorInfo,
+};
+
+/// Тип объектной привязки. Центр и касательная появятся вместе с окружностями и дугами.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum SnapType {
+    Endpoint,
+    Midpoint,
+    Intersection,
+    Perpendicular,
+    Nearest,
+}
+
+impl SnapType {
+    pub const ALL: [SnapType; 5] = [
+        SnapType::Endpoint,
+        SnapType::Midpoint,
+        SnapType::Intersection,
+        SnapType::Perpendicular,
+        SnapType::Nearest,
+    ];
+
+    /// Название для панели настроек
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapType::Endpoint => "Конечная точка",
+            SnapType::Midpoint => "Середина",
+            SnapType::Intersection => "Пересечение",
+            SnapType::Perpendicular => "Нормаль",
+            SnapType::Nearest => "Ближайшая",
+        }
+    }
+
+    /// Цвет маркера привязки
+    pub fn color(&self) -> Color {
+        match self {
+            SnapType::Endpoint => Color::srgb(1.0, 0.8, 0.0),
+            SnapType::Midpoint => Color::srgb(0.0, 1.0, 0.6),
+            SnapType::Intersection => Color::srgb(1.0, 0.3, 0.3),
+            SnapType::Perpendicular => Color::srgb(0.3, 0.8, 1.0),
+            SnapType::Nearest => Color::srgb(0.8, 0.8, 0.8),
+        }
+    }
+}
+
+/// Настройки объектных привязок
+#[derive(Resource)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    pub endpoint: bool,
+    pub midpoint: bool,
+    pub intersection: bool,
+    pub perpendicular: bool,
+    pub nearest: bool,
+    pub aperture_pixels: f32, // радиус захвата на экране
+    pub aperture: f32,        // тот же радиус в мировых единицах при текущем виде
+    pub marker_size: f32,     // размер маркера привязки
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            endpoint: true,
+            midpoint: true,
+            intersection: true,
+            perpendicular: false,
+            nearest: false,
+            aperture_pixels: 10.0,
+            aperture: 0.4,
+            marker_size: 0.15,
+        }
+    }
+}
+
+impl SnapSettings {
+    pub fn is_enabled(&self, snap_type: SnapType) -> bool {
+        match snap_type {
+            SnapType::Endpoint => self.endpoint,
+            SnapType::Midpoint => self.midpoint,
+            SnapType::Intersection => self.intersection,
+            SnapType::Perpendicular => self.perpendicular,
+            SnapType::Nearest => self.nearest,
+        }
+    }
+
+    pub fn toggle(&mut self, snap_type: SnapType) {
+        let flag = match snap_type {
+            SnapType::Endpoint => &mut self.endpoint,
+            SnapType::Midpoint => &mut self.midpoint,
+            SnapType::Intersection => &mut self.intersection,
+            SnapType::Perpendicular => &mut self.perpendicular,
+            SnapType::Nearest => &mut self.nearest,
+        };
+        *flag = !*flag;
+    }
+}
+
+/// Найденная точка привязки
+#[derive(Clone, Copy, Debug)]
+pub struct SnapPoint {
+    pub position: DVec3,
+    pub snap_type: SnapType,
+}
+
+/// Текущее состояние привязки курсора (общее для всех команд ввода точек)
+#[derive(Resource, Default)]
+pub struct SnapState {
+    pub cursor: Option<DVec3>,
+    pub snap: Option<SnapPoint>,
+    pub grid: Option<DVec3>, // точка, округленная по сетке привязки
+    pub tracking: Option<TrackingPoint>,
+    /// Последняя точка, указанная любой командой: база относительного ввода
+    /// и полюс полярных координат
+    pub last_point: Option<DVec3>,
+}
+
+impl SnapState {
+    /// Точка, которую должна использовать команда ввода вместо исходной.
+    /// Приоритет: объектная привязка, отслеживание, привязка к сетке.
+    pub fn resolve(&self, raw: DVec3) -> DVec3 {
+        self.snap
+            .map(|snap| snap.position)
+            .or(self.tracking.map(|tracking| tracking.position))
+            .or(self.grid)
+            .unwrap_or(raw)
+    }
+
+    /// Итоговая точка под курсором с учетом всех привязок
+    pub fn point(&self) -> Option<DVec3> {
+        self.cursor.map(|cursor| self.resolve(cursor))
+    }
+
+    /// Запомнить последнюю из точек, указанных команде
+    pub fn remember(&mut self, points: impl IntoIterator<Item = DVec3>) {
+        if let Some(point) = points.into_iter().last() {
+            self.last_point = Some(point);
+        }
+    }
+}
+
+/// Предустановленные шаги привязки к сетке
+pub const GRID_SNAP_STEPS: [f32; 5] = [0.1, 0.25, 0.5, 1.0, 2.0];
+
+/// Предустановленные угловые шаги полярной привязки (в градусах)
+pub const POLAR_SNAP_ANGLES: [f32; 5] = [5.0, 10.0, 15.0, 30.0, 45.0];
+
+/// Настройки привязки к сетке (F9)
+#[derive(Resource)]
+pub struct GridSnapSettings {
+    pub enabled: bool,
+    pub step: f32,          // собственный шаг привязки
+    pub link_to_grid: bool, // использовать шаг отображаемой сетки
+    pub polar_radius_step: f32,
+    pub polar_angle_step: f32, // в градусах
+}
+
+impl Default for GridSnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            step: 0.5,
+            link_to_grid: false,
+            polar_radius_step: 0.5,
+            polar_angle_step: 15.0,
+        }
+    }
+}
+
+impl GridSnapSettings {
+    /// Действующий шаг привязки с учетом связи с сеткой
+    pub fn effective_step(&self, grid_settings: &GridSettings) -> f32 {
+        if self.link_to_grid {
+            grid_settings.step
+        } else {
+            self.step
+        }
+    }
+
+    /// Действующий шаг радиуса в полярном режиме
+    pub fn effective_radius_step(&self, grid_settings: &GridSettings) -> f32 {
+        if self.link_to_grid {
+            grid_settings.step
+        } else {
+            self.polar_radius_step
+        }
+    }
+}
+
+/// Округление значения до ближайшего кратного шагу
+fn round_to_step(value: f64, step: f32) -> f64 {
+    let step = step as f64;
+    if step > f32::EPSILON as f64 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+/// Привязка точки к сетке: в декартовых режимах округляются координаты в ПСК,
+/// в полярных - радиус и угол относительно полюса (курсор всегда на плоскости)
+pub fn snap_to_grid(
+    point: DVec3,
+    settings: &GridSnapSettings,
+    grid_settings: &GridSettings,
+    coordinate_system: CoordinateSystem,
+    frame: &CoordinateFrame,
+) -> DVec3 {
+    match coordinate_system {
+        CoordinateSystem::Cartesian | CoordinateSystem::Cartesian3D => {
+            let step = settings.effective_step(grid_settings);
+            let local = frame.local_cartesian(point);
+            frame.cartesian_point(CartesianPoint::new(
+                round_to_step(local.x, step),
+                round_to_step(local.y, step),
+            ))
+        }
+        CoordinateSystem::Polar | CoordinateSystem::Cylindrical | CoordinateSystem::Spherical => {
+            let polar = frame.local_polar(point);
+            let r = round_to_step(polar.r, settings.effective_radius_step(grid_settings));
+            let theta = round_to_step(polar.theta.to_degrees(), settings.polar_angle_step);
+            frame.polar_point(PolarPoint::from_degrees(r, theta))
+        }
+    }
+}
+
+/// Геометрия, к которой возможна привязка
+#[derive(Clone, Copy, Debug)]
+pub enum SnapGeometry {
+    Segment { start: DVec3, end: DVec3 },
+}
+
+/// Поиск кандидатов привязки на плоскости XZ
+pub mod candidates {
+    use super::*;
+
+    fn flat(point: DVec3) -> DVec2 {
+        DVec2::new(point.x, point.z)
+    }
+
+    fn world(point: DVec2) -> DVec3 {
+        DVec3::new(point.x, 0.0, point.y)
+    }
+
+    /// Ближайшая к точке точка отрезка
+    pub fn closest_on_segment(point: DVec3, start: DVec3, end: DVec3) -> DVec3 {
+        let (p, a, b) = (flat(point), flat(start), flat(end));
+        let ab = b - a;
+        let len_sq = ab.length_squared();
+        if len_sq < 1e-12 {
+            return start;
+        }
+        let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+        world(a + ab * t)
+    }
+
+    /// Основание перпендикуляра, опущенного из точки на отрезок
+    pub fn perpendicular_foot(from: DVec3, start: DVec3, end: DVec3) -> Option<DVec3> {
+        let (p, a, b) = (flat(from), flat(start), flat(end));
+        let ab = b - a;
+        let len_sq = ab.length_squared();
+        if len_sq < 1e-12 {
+            return None;
+        }
+        let t = (p - a).dot(ab) / len_sq;
+        (0.0..=1.0).contains(&t).then(|| world(a + ab * t))
+    }
+
+    /// Точка пересечения двух отрезков
+    pub fn segment_intersection(a1: DVec3, a2: DVec3, b1: DVec3, b2: DVec3) -> Option<DVec3> {
+        let (p, r) = (flat(a1), flat(a2) - flat(a1));
+        let (q, s) = (flat(b1), flat(b2) - flat(b1));
+        let denominator = r.perp_dot(s);
+        if denominator.abs() < 1e-9 {
+            return None; // параллельные отрезки
+        }
+        let t = (q - p).perp_dot(s) / denominator;
+        let u = (q - p).perp_dot(r) / denominator;
+        ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| world(p + r * t))
+    }
+
+    /// Все кандидаты привязки рядом с курсором
+    pub fn collect(
+        cursor: DVec3,
+        reference: Option<DVec3>,
+        geometry: &[SnapGeometry],
+        settings: &SnapSettings,
+    ) -> Vec<SnapPoint> {
+        let aperture = settings.aperture as f64;
+        let near = |point: DVec3| flat(point).distance(flat(cursor)) <= aperture;
+        let mut found = Vec::new();
+        let mut push = |position: DVec3, snap_type: SnapType| {
+            if settings.is_enabled(snap_type) && near(position) {
+                found.push(SnapPoint {
+                    position,
+                    snap_type,
+                });
+            }
+        };
+
+        for item in geometry {
+            match *item {
+                SnapGeometry::Segment { start, end } => {
+                    push(start, SnapType::Endpoint);
+                    push(end, SnapType::Endpoint);
+                    push((start + end) / 2.0, SnapType::Midpoint);
+                    push(closest_on_segment(cursor, start, end), SnapType::Nearest);
+                    if let Some(from) = reference {
+                        if let Some(foot) = perpendicular_foot(from, start, end) {
+                            push(foot, SnapType::Perpendicular);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Пересечения ищем только среди отрезков, проходящих рядом с курсором
+        let nearby: Vec<(DVec3, DVec3)> = geometry
+            .iter()
+            .filter_map(|item| match *item {
+                SnapGeometry::Segment { start, end }
+                    if near(closest_on_segment(cursor, start, end)) =>
+                {
+                    Some((start, end))
+                }
+                _ => None,
+            })
+            .collect();
+        for (i, &(a1, a2)) in nearby.iter().enumerate() {
+            for &(b1, b2) in &nearby[i + 1..] {
+                if let Some(point) = segment_intersection(a1, a2, b1, b2) {
+                    push(point, SnapType::Intersection);
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Выбор лучшей привязки: ближайшая к курсору, "Ближайшая" - только если других нет
+    pub fn best(cursor: DVec3, found: &[SnapPoint]) -> Option<SnapPoint> {
+        let distance = |snap: &SnapPoint| flat(snap.position).distance(flat(cursor));
+        found.iter().copied().min_by(|a, b| {
+            let a_key = (a.snap_type == SnapType::Nearest, distance(a));
+            let b_key = (b.snap_type == SnapType::Nearest, distance(b));
+            a_key
+                .partial_cmp(&b_key)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+/// Пересчет радиуса захвата из пикселей в мировые единицы у курсора, чтобы
+/// апертура оставалась одинаковой на экране при любом масштабе вида
+pub fn update_snap_aperture_system(
+    mut settings: ResMut<SnapSettings>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (camera_query.single(), window_query.single())
+    else {
+        return;
+    };
+    let at = window.cursor_position().unwrap_or(window.size() / 2.0);
+    let on_plane = |position: Vec2| {
+        line_drawing::screen_to_world_plane(position, camera, camera_transform, Vec3::Y, 0.0)
+    };
+    let (Some(center), Some(side)) = (on_plane(at), on_plane(at + Vec2::X)) else {
+        return;
+    };
+    let aperture = center.distance(side) * settings.aperture_pixels;
+    // Мелкие колебания не пересчитываем, чтобы не отмечать настройки измененными
+    if (aperture - settings.aperture).abs() > settings.aperture * 1e-3 {
+        settings.aperture = aperture;
+    }
+}
+
+/// Система поиска объектной привязки под курсором
+pub fn object_snap_system(
+    cursor_info: Res<CursorInfo>,
+    settings: Res<SnapSettings>,
+    line_state: Res<LineDrawingState>,
+    layers: Res<Layers>,
+    blocks: Res<Blocks>,
+    line_query: Query<(&DrawableLine, &LayerId)>,
+    reference_query: Query<(&BlockReference, &LayerId)>,
+    mut snap_state: ResMut<SnapState>,
+) {
+    let cursor = cursor_info.world_position.filter(|_| cursor_info.is_valid);
+    snap_state.cursor = cursor;
+    snap_state.snap = None;
+
+    let Some(cursor) = cursor else {
+        return;
+    };
+    if !settings.enabled {
+        return;
+    }
+
+    // Объекты скрытых слоев не дают привязок; вставки блоков дают привязки к своим отрезкам
+    let mut geometry: Vec<SnapGeometry> = line_query
+        .iter()
+        .filter(|(_, layer)| layers.is_visible(**layer))
+        .map(|(line, _)| SnapGeometry::Segment {
+            start: line.start,
+            end: line.end,
+        })
+        .collect();
+    for (reference, _) in reference_query
+        .iter()
+        .filter(|(_, layer)| layers.is_visible(**layer))
+    {
+        geometry.extend(
+            blocks
+                .segments(reference, &layers)
+                .into_iter()
+                .map(|(start, end, _)| SnapGeometry::Segment { start, end }),
+        );
+    }
+
+    let reference = line_state.start_point.filter(|_| line_state.is_drawing);
+    let found = candidates::collect(cursor, reference, &geometry, &settings);
+    snap_state.snap = candidates::best(cursor, &found);
+}
+
+/// Система привязки курсора к сетке
+pub fn grid_snap_system(
+    settings: Res<GridSnapSettings>,
+    grid_settings: Res<GridSettings>,
+    coordinate_settings: Res<CoordinateSettings>,
+    ucs_manager: Res<UcsManager>,
+    mut snap_state: ResMut<SnapState>,
+) {
+    snap_state.grid = None;
+    if !settings.enabled {
+        return;
+    }
+
+    if let Some(cursor) = snap_state.cursor {
+        snap_state.grid = Some(snap_to_grid(
+            cursor,
+            &settings,
+            &grid_settings,
+            coordinate_settings.coordinate_system,
+            &ucs_manager.frame(snap_state.last_point),
+        ));
+    }
+}
+
+/// F9 - переключение привязки к сетке
+pub fn toggle_grid_snap_system(
+    input: ActionInput,
+    mut grid_snap_events: EventWriter<GridSnapEvent>,
+) {
+    if input.just_pressed(InputAction::ToggleGridSnap) {
+        grid_snap_events.write(GridSnapEvent::Toggle);
+    }
+}
+
+/// Система обработки событий привязки к сетке
+pub fn handle_grid_snap_events(
+    mut grid_snap_events: EventReader<GridSnapEvent>,
+    mut settings: ResMut<GridSnapSettings>,
+) {
+    for event in grid_snap_events.read() {
+        match event {
+            GridSnapEvent::Toggle => {
+                settings.enabled = !settings.enabled;
+                info!(
+                    "Grid snap: {}",
+                    if settings.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            GridSnapEvent::ToggleLink => {
+                settings.link_to_grid = !settings.link_to_grid;
+                info!("Grid snap linked to grid step: {}", settings.link_to_grid);
+            }
+            GridSnapEvent::CycleStep => {
+                settings.step = next_preset(&GRID_SNAP_STEPS, settings.step);
+                settings.polar_radius_step = settings.step;
+                info!("Grid snap step changed to: {}", settings.step);
+            }
+            GridSnapEvent::SetStep(step) => {
+                settings.step = *step;
+                settings.polar_radius_step = *step;
+                info!("Grid snap step changed to: {}", settings.step);
+            }
+            GridSnapEvent::CycleAngleStep => {
+                settings.polar_angle_step =
+                    next_preset(&POLAR_SNAP_ANGLES, settings.polar_angle_step);
+                info!(
+                    "Polar snap angle step changed to: {}°",
+                    settings.polar_angle_step
+                );
+            }
+        }
+    }
+}
+
+/// Поле шага привязки к сетке
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let step = input.evaluate(input.text)?;
+    if step <= 0.0 {
+        return Err("шаг должен быть положительным".to_string());
+    }
+    input
+        .events
+        .grid_snap
+        .write(GridSnapEvent::SetStep(step as f32));
+    Ok(format!("= {}", input.length(step)))
+}
+
+/// Следующее значение из списка предустановок (по кругу)
+fn next_preset(presets: &[f32], current: f32) -> f32 {
+    presets
+        .iter()
+        .copied()
+        .find(|value| *value > current + f32::EPSILON)
+        .unwrap_or(presets[0])
+}
+
+/// Отрисовка маркера привязки - свой символ для каждого типа
+pub fn draw_snap_marker_system(
+    snap_state: Res<SnapState>,
+    settings: Res<SnapSettings>,
+    render_origin: Res<RenderOrigin>,
+    mut gizmos: Gizmos,
+) {
+    let Some(snap) = snap_state.snap else {
+        // Маркер привязки к сетке - небольшой крестик
+        if let Some(grid_point) = snap_state.grid {
+            let s = settings.marker_size * 0.6;
+            let c = render_origin.to_render(grid_point) + Vec3::Y * 0.02;
+            let color = Color::srgb(0.5, 0.9, 0.9);
+            gizmos.line(c - Vec3::X * s, c + Vec3::X * s, color);
+            gizmos.line(c - Vec3::Z * s, c + Vec3::Z * s, color);
+        }
+        return;
+    };
+
+    let s = settings.marker_size;
+    let c = render_origin.to_render(snap.position) + Vec3::Y * 0.02;
+    let color = snap.snap_type.color();
+    let p = |x: f32, z: f32| c + Vec3::new(x * s, 0.0, z * s);
+
+    match snap.snap_type {
+        SnapType::Endpoint => {
+            // Квадрат
+            draw_polyline(
+                &mut gizmos,
+                &[p(-1.0, -1.0), p(1.0, -1.0), p(1.0, 1.0), p(-1.0, 1.0)],
+                true,
+                color,
+            );
+        }
+        SnapType::Midpoint => {
+            // Треугольник
+            draw_polyline(
+                &mut gizmos,
+                &[p(-1.0, 1.0), p(1.0, 1.0), p(0.0, -1.0)],
+                true,
+                color,
+            );
+        }
+        SnapType::Intersection => {
+            // Косой крест
+            gizmos.line(p(-1.0, -1.0), p(1.0, 1.0), color);
+            gizmos.line(p(-1.0, 1.0), p(1.0, -1.0), color);
+        }
+        SnapType::Perpendicular => {
+            // Знак перпендикуляра
+            draw_polyline(
+                &mut gizmos,
+                &[p(-1.0, -1.0), p(-1.0, 1.0), p(1.0, 1.0)],
+                false,
+                color,
+            );
+            draw_polyline(
+                &mut gizmos,
+                &[p(-1.0, 0.0), p(0.0, 0.0), p(0.0, 1.0)],
+                false,
+                color,
+            );
+        }
+        SnapType::Nearest => {
+            // Песочные часы
+            draw_polyline(
+                &mut gizmos,
+                &[p(-1.0, -1.0), p(1.0, -1.0), p(-1.0, 1.0), p(1.0, 1.0)],
+                true,
+                color,
+            );
+        }
+    }
+}
+
+fn draw_polyline(gizmos: &mut Gizmos, points: &[Vec3], closed: bool, color: Color) {
+    for pair in points.windows(2) {
+        gizmos.line(pair[0], pair[1], color);
+    }
+    if closed && points.len() > 2 {
+        gizmos.line(points[points.len() - 1], points[0], color);
+    }
+}
+
+/// Система обработки переключения типов привязок
+pub fn handle_snap_toggle_events(
+    mut toggle_events: EventReader<ObjectSnapToggleEvent>,
+    mut settings: ResMut<SnapSettings>,
+) {
+    for event in toggle_events.read() {
+        match event.snap_type {
+            Some(snap_type) => {
+                settings.toggle(snap_type);
+                info!(
+                    "Object snap {:?}: {}",
+                    snap_type,
+                    if settings.is_enabled(snap_type) {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            None => {
+                settings.enabled = !settings.enabled;
+                info!(
+                    "Object snaps: {}",
+                    if settings.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::candidates::*;
+    use super::*;
+    use crate::render_origin::FAR_POINT;
+
+    fn kinds(found: &[SnapPoint]) -> Vec<SnapType> {
+        found.iter().map(|snap| snap.snap_type).collect()
+    }
+
+    #[test]
+    fn snap_candidates_near_cursor() {
+        let o = FAR_POINT;
+        // Крест из двух отрезков длиной 4 с центром в o
+        let geometry = [
+            SnapGeometry::Segment {
+                start: o - DVec3::X * 2.0,
+                end: o + DVec3::X * 2.0,
+            },
+            SnapGeometry::Segment {
+                start: o - DVec3::Z * 2.0,
+                end: o + DVec3::Z * 2.0,
+            },
+        ];
+        let mut settings = SnapSettings {
+            aperture: 0.5,
+            ..default()
+        };
+
+        // У центра креста: середины обоих отрезков и пересечение
+        let cursor = o + DVec3::new(0.1, 0.0, 0.2);
+        let found = collect(cursor, None, &geometry, &settings);
+        assert_eq!(
+            kinds(&found),
+            [
+                SnapType::Midpoint,
+                SnapType::Midpoint,
+                SnapType::Intersection
+            ]
+        );
+        assert!(best(cursor, &found).unwrap().position.abs_diff_eq(o, 1e-9));
+
+        // У конца: конечная точка важнее более близкой «Ближайшей»
+        settings.nearest = true;
+        let cursor = o + DVec3::new(1.7, 0.0, 0.05);
+        let found = collect(cursor, None, &geometry, &settings);
+        assert_eq!(kinds(&found), [SnapType::Endpoint, SnapType::Nearest]);
+        let snap = best(cursor, &found).unwrap();
+        assert_eq!(snap.snap_type, SnapType::Endpoint);
+        assert!(snap.position.abs_diff_eq(o + DVec3::X * 2.0, 1e-9));
+
+        // Перпендикуляр из предыдущей точки; отключенные типы не ищутся
+        settings.nearest = false;
+        settings.perpendicular = true;
+        settings.endpoint = false;
+        let from = o + DVec3::new(1.0, 0.0, 5.0);
+        let cursor = o + DVec3::new(1.1, 0.0, 0.1);
+        let found = collect(cursor, Some(from), &geometry, &settings);
+        assert_eq!(kinds(&found), [SnapType::Perpendicular]);
+        assert!(found[0].position.abs_diff_eq(o + DVec3::X, 1e-9));
+
+        // Вне апертуры привязок нет
+        let cursor = o + DVec3::new(1.0, 0.0, 1.0);
+        assert!(collect(cursor, Some(from), &geometry, &settings).is_empty());
+    }
+
+    #[test]
+    fn segment_helpers_stay_on_segments() {
+        let o = FAR_POINT;
+        let (start, end) = (o, o + DVec3::new(4.0, 0.0, 0.0));
+        let outside = o + DVec3::new(6.0, 0.0, 1.0);
+        assert_eq!(closest_on_segment(outside, start, end), end);
+        assert!(perpendicular_foot(outside, start, end).is_none());
+        // Параллельные и не достающие друг друга отрезки не пересекаются
+        let shifted = (start + DVec3::Z, end + DVec3::Z);
+        assert!(segment_intersection(start, end, shifted.0, shifted.1).is_none());
+        let short = (
+            o + DVec3::new(5.0, 0.0, -1.0),
+            o + DVec3::new(5.0, 0.0, 1.0),
+        );
+        assert!(segment_intersection(start, end, short.0, short.1).is_none());
+    }
+}
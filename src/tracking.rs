@@ -0,0 +1,773 @@
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+
+use crate::{
+    coordinate_systems::{formatting, CoordinateSettings},
+    events::{LineCreatedEvent, TrackingEvent},
+    expression::{self, Context},
+    grid::GridSettings,
+    input_field::FieldInput,
+    keybindings::{ActionInput, InputAction},
+    line_drawing::LineDrawingState,
+    render_origin::RenderOrigin,
+    snapping::{GridSnapSettings, SnapState},
+    ucs::{CoordinateFrame, UcsManager},
+};
+
+/// Набор углов полярного отслеживания
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PolarAngleMode {
+    /// Лучи через каждые N градусов
+    Increment(f32),
+    /// Пользовательский список углов
+    Custom,
+}
+
+/// Предустановленные шаги полярного отслеживания (в градусах)
+pub const POLAR_TRACKING_INCREMENTS: [f32; 3] = [15.0, 30.0, 45.0];
+
+/// Настройки ОРТО, полярного и объектного отслеживания
+#[derive(Resource)]
+pub struct TrackingSettings {
+    pub ortho: bool,
+    pub polar_tracking: bool,
+    pub angle_mode: PolarAngleMode,
+    pub custom_angles: Vec<f32>, // в градусах
+    pub tolerance: f32,          // расстояние захвата луча в мировых единицах
+    pub ray_length: f32,         // длина отображаемого луча за курсором
+    // Объектное отслеживание
+    pub object_tracking: bool,
+    pub acquire_delay: f32, // время наведения для захвата точки (сек)
+    pub max_acquired: usize,
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            ortho: false,
+            polar_tracking: false,
+            angle_mode: PolarAngleMode::Increment(15.0),
+            custom_angles: vec![0.0, 22.5, 67.5, 112.5],
+            tolerance: 0.3,
+            ray_length: 5.0,
+            object_tracking: false,
+            acquire_delay: 0.5,
+            max_acquired: 7,
+        }
+    }
+}
+
+impl TrackingSettings {
+    /// Все углы отслеживания в градусах в диапазоне [0, 360)
+    pub fn tracking_angles(&self) -> Vec<f32> {
+        match self.angle_mode {
+            PolarAngleMode::Increment(step) if step > 0.0 => {
+                let count = (360.0 / step).ceil() as usize;
+                (0..count)
+                    .map(|i| i as f32 * step)
+                    .filter(|angle| *angle < 360.0)
+                    .collect()
+            }
+            PolarAngleMode::Increment(_) => vec![0.0],
+            PolarAngleMode::Custom => self
+                .custom_angles
+                .iter()
+                .map(|angle| angle.rem_euclid(360.0))
+                .collect(),
+        }
+    }
+
+    /// Подпись текущего набора углов
+    pub fn angles_label(&self) -> String {
+        match self.angle_mode {
+            PolarAngleMode::Increment(step) => format!("{}°", step),
+            PolarAngleMode::Custom => {
+                let angles: Vec<String> = self
+                    .custom_angles
+                    .iter()
+                    .map(|angle| format!("{}", angle))
+                    .collect();
+                format!("[{}]°", angles.join(", "))
+            }
+        }
+    }
+
+    /// Следующий набор углов: 15° -> 30° -> 45° -> пользовательский -> 15°
+    fn next_angle_mode(&self) -> PolarAngleMode {
+        match self.angle_mode {
+            PolarAngleMode::Increment(step) => POLAR_TRACKING_INCREMENTS
+                .iter()
+                .copied()
+                .find(|value| *value > step + f32::EPSILON)
+                .map_or(PolarAngleMode::Custom, PolarAngleMode::Increment),
+            PolarAngleMode::Custom => PolarAngleMode::Increment(POLAR_TRACKING_INCREMENTS[0]),
+        }
+    }
+}
+
+/// Разбор пользовательского списка углов в градусах: выражения через запятую
+pub fn parse_custom_angles(text: &str, context: &Context) -> Result<Vec<f32>, String> {
+    if text.trim().is_empty() {
+        return Err("список углов пуст".to_string());
+    }
+    let mut angles = Vec::new();
+    for part in expression::split_top_level(text, ',') {
+        let angle = expression::evaluate(part, context).map_err(|e| e.to_string())?;
+        if !angle.is_finite() {
+            return Err(format!("некорректный угол: {}", part.trim()));
+        }
+        angles.push(angle as f32);
+    }
+    Ok(angles)
+}
+
+/// Поле пользовательского списка углов полярного отслеживания
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let angles = parse_custom_angles(input.text, &input.context)?;
+    let labels: Vec<String> = angles.iter().map(|angle| format!("{}", angle)).collect();
+    input
+        .events
+        .tracking
+        .write(TrackingEvent::SetCustomAngles(angles));
+    Ok(format!("[{}]°", labels.join(", ")))
+}
+
+/// Вид ограничения, примененного к точке
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TrackingKind {
+    Ortho,
+    /// Полярный луч от базовой точки (угол в радианах)
+    Polar {
+        distance: f64,
+        angle: f64,
+    },
+    /// Путь выравнивания через захваченную точку
+    Alignment {
+        distance: f64,
+        angle: f64,
+    },
+    /// Пересечение двух путей, проведенных из base и other
+    Intersection {
+        other: DVec3,
+    },
+}
+
+/// Захваченные точки объектного отслеживания
+#[derive(Resource, Default)]
+pub struct TrackingAcquisition {
+    pub points: Vec<DVec3>,
+    pub hover: Option<(DVec3, f32)>, // точка привязки под курсором и время наведения
+}
+
+/// Путь выравнивания - бесконечная прямая через точку
+#[derive(Clone, Copy, Debug)]
+pub struct AlignmentPath {
+    pub origin: DVec3,
+    pub angle: f64, // в радианах
+}
+
+/// Результат отслеживания относительно базовой точки
+#[derive(Clone, Copy, Debug)]
+pub struct TrackingPoint {
+    pub position: DVec3,
+    pub base: DVec3,
+    pub kind: TrackingKind,
+}
+
+/// Ограничение ОРТО: направление вдоль оси X или Y ПСК (угол оси X в радианах)
+pub fn ortho_constrain(base: DVec3, point: DVec3, axis_angle: f64) -> DVec3 {
+    let axis = DVec2::from_angle(axis_angle);
+    let delta = DVec2::new(point.x - base.x, point.z - base.z);
+    let along = delta.dot(axis);
+    let across = delta.dot(axis.perp());
+    let offset = if along.abs() >= across.abs() {
+        axis * along
+    } else {
+        axis.perp() * across
+    };
+    DVec3::new(base.x + offset.x, 0.0, base.z + offset.y)
+}
+
+/// Поиск луча полярного отслеживания рядом с точкой.
+/// Возвращает проекцию точки на луч, расстояние и угол луча (в радианах).
+pub fn polar_track(
+    base: DVec3,
+    point: DVec3,
+    angles_deg: &[f64],
+    tolerance: f64,
+) -> Option<(DVec3, f64, f64)> {
+    let delta = DVec2::new(point.x - base.x, point.z - base.z);
+    if delta.length() < tolerance {
+        return None;
+    }
+
+    angles_deg
+        .iter()
+        .map(|angle| {
+            let angle = angle.to_radians();
+            let direction = DVec2::from_angle(angle);
+            let distance = delta.dot(direction);
+            let offset = (delta - direction * distance).length();
+            (angle, direction, distance, offset)
+        })
+        .filter(|(_, _, distance, offset)| *distance > 0.0 && *offset <= tolerance)
+        .min_by(|a, b| a.3.partial_cmp(&b.3).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(angle, direction, distance, _)| {
+            let projected = DVec2::new(base.x, base.z) + direction * distance;
+            (DVec3::new(projected.x, 0.0, projected.y), distance, angle)
+        })
+}
+
+/// Пути выравнивания: оси ПСК через каждую захваченную точку, а при полярном
+/// отслеживании - еще и полярные углы (в том числе от базовой точки).
+/// Все углы - мировые, в градусах.
+pub fn alignment_paths(
+    acquired: &[DVec3],
+    base: Option<DVec3>,
+    axis_angle_deg: f64,
+    polar_angles_deg: Option<&[f64]>,
+) -> Vec<AlignmentPath> {
+    // Пути - прямые, поэтому углы достаточно брать в диапазоне [0, 180)
+    let mut angles: Vec<f64> = vec![
+        axis_angle_deg.rem_euclid(180.0),
+        (axis_angle_deg + 90.0).rem_euclid(180.0),
+    ];
+    if let Some(polar_angles) = polar_angles_deg {
+        for angle in polar_angles {
+            let angle = angle.rem_euclid(180.0);
+            if !angles.iter().any(|known| (known - angle).abs() < 1e-3) {
+                angles.push(angle);
+            }
+        }
+    }
+
+    let origins = acquired
+        .iter()
+        .copied()
+        .chain(base.filter(|_| polar_angles_deg.is_some()));
+    origins
+        .flat_map(|origin| {
+            angles.iter().map(move |angle| AlignmentPath {
+                origin,
+                angle: angle.to_radians(),
+            })
+        })
+        .collect()
+}
+
+/// Поиск пути выравнивания (или пересечения двух путей) рядом с точкой
+pub fn object_track(
+    point: DVec3,
+    paths: &[AlignmentPath],
+    tolerance: f64,
+) -> Option<TrackingPoint> {
+    let flat = |p: DVec3| DVec2::new(p.x, p.z);
+    let cursor = flat(point);
+
+    // Пути, проходящие рядом с курсором: (путь, направление, проекция, отклонение)
+    let near: Vec<(AlignmentPath, DVec2, f64, f64)> = paths
+        .iter()
+        .map(|path| {
+            let direction = DVec2::from_angle(path.angle);
+            let delta = cursor - flat(path.origin);
+            let along = delta.dot(direction);
+            let offset = (delta - direction * along).length();
+            (*path, direction, along, offset)
+        })
+        .filter(|(_, _, _, offset)| *offset <= tolerance)
+        .collect();
+
+    // Сначала ищем пересечения путей
+    let mut best_intersection: Option<(f64, TrackingPoint)> = None;
+    for (i, (first, first_dir, _, _)) in near.iter().enumerate() {
+        for (second, second_dir, _, _) in &near[i + 1..] {
+            let denominator = first_dir.perp_dot(*second_dir);
+            if denominator.abs() < 1e-6 {
+                continue; // параллельные пути
+            }
+            let t = (flat(second.origin) - flat(first.origin)).perp_dot(*second_dir) / denominator;
+            let intersection = flat(first.origin) + *first_dir * t;
+            let distance = intersection.distance(cursor);
+            if distance <= tolerance * 2.0
+                && best_intersection.is_none_or(|(best, _)| distance < best)
+            {
+                best_intersection = Some((
+                    distance,
+                    TrackingPoint {
+                        position: DVec3::new(intersection.x, 0.0, intersection.y),
+                        base: first.origin,
+                        kind: TrackingKind::Intersection {
+                            other: second.origin,
+                        },
+                    },
+                ));
+            }
+        }
+    }
+    if let Some((_, tracking)) = best_intersection {
+        return Some(tracking);
+    }
+
+    // Иначе - ближайший путь
+    near.iter()
+        .min_by(|a, b| a.3.partial_cmp(&b.3).unwrap_or(std::cmp::Ordering::Equal))
+        .filter(|(_, _, along, _)| along.abs() > f64::EPSILON)
+        .map(|(path, direction, along, _)| {
+            let projected = flat(path.origin) + *direction * *along;
+            // Направление пути выбираем в сторону курсора
+            let angle = if *along < 0.0 {
+                path.angle + std::f64::consts::PI
+            } else {
+                path.angle
+            };
+            TrackingPoint {
+                position: DVec3::new(projected.x, 0.0, projected.y),
+                base: path.origin,
+                kind: TrackingKind::Alignment {
+                    distance: along.abs(),
+                    angle,
+                },
+            }
+        })
+}
+
+/// Система ОРТО, полярного и объектного отслеживания
+pub fn tracking_system(
+    settings: Res<TrackingSettings>,
+    acquisition: Res<TrackingAcquisition>,
+    grid_snap_settings: Res<GridSnapSettings>,
+    grid_settings: Res<GridSettings>,
+    line_state: Res<LineDrawingState>,
+    ucs_manager: Res<UcsManager>,
+    mut snap_state: ResMut<SnapState>,
+) {
+    snap_state.tracking = None;
+
+    // Объектная привязка имеет приоритет над отслеживанием
+    if snap_state.snap.is_some() {
+        return;
+    }
+    let Some(cursor) = snap_state.cursor else {
+        return;
+    };
+    let base = line_state.start_point.filter(|_| line_state.is_drawing);
+
+    // Углы отслеживания задаются в ПСК, лучи строятся в мировых углах
+    let frame = ucs_manager.frame(snap_state.last_point);
+    let polar_angles: Vec<f64> = settings
+        .tracking_angles()
+        .iter()
+        .map(|angle| frame.world_angle((*angle as f64).to_radians()).to_degrees())
+        .collect();
+
+    if let Some(base) = base {
+        apply_base_tracking(
+            &settings,
+            &frame,
+            &polar_angles,
+            &grid_snap_settings,
+            &grid_settings,
+            base,
+            cursor,
+            &mut snap_state,
+        );
+    }
+
+    if settings.object_tracking && !acquisition.points.is_empty() {
+        let paths = alignment_paths(
+            &acquisition.points,
+            base,
+            frame.angle.to_degrees(),
+            settings.polar_tracking.then_some(polar_angles.as_slice()),
+        );
+        if let Some(found) = object_track(cursor, &paths, settings.tolerance as f64) {
+            // Пересечение путей важнее полярного луча, одиночный путь - нет
+            let is_intersection = matches!(found.kind, TrackingKind::Intersection { .. });
+            if is_intersection || snap_state.tracking.is_none() {
+                snap_state.tracking = Some(found);
+            }
+        }
+    }
+}
+
+/// ОРТО и полярное отслеживание от базовой точки
+fn apply_base_tracking(
+    settings: &TrackingSettings,
+    frame: &CoordinateFrame,
+    polar_angles: &[f64],
+    grid_snap_settings: &GridSnapSettings,
+    grid_settings: &GridSettings,
+    base: DVec3,
+    cursor: DVec3,
+    snap_state: &mut SnapState,
+) {
+    if settings.ortho {
+        let point = snap_state.grid.unwrap_or(cursor);
+        snap_state.tracking = Some(TrackingPoint {
+            position: ortho_constrain(base, point, frame.angle),
+            base,
+            kind: TrackingKind::Ortho,
+        });
+    } else if settings.polar_tracking {
+        if let Some((mut position, mut distance, angle)) =
+            polar_track(base, cursor, polar_angles, settings.tolerance as f64)
+        {
+            // При включенной привязке к сетке расстояние по лучу округляется до шага
+            if grid_snap_settings.enabled {
+                let step = grid_snap_settings.effective_step(grid_settings) as f64;
+                if step > f64::EPSILON {
+                    distance = ((distance / step).round() * step).max(step);
+                    position = base + DVec3::new(angle.cos(), 0.0, angle.sin()) * distance;
+                }
+            }
+            snap_state.tracking = Some(TrackingPoint {
+                position,
+                base,
+                kind: TrackingKind::Polar { distance, angle },
+            });
+        }
+    }
+}
+
+/// Захват точек объектного отслеживания наведением на точку привязки
+pub fn acquire_tracking_points_system(
+    time: Res<Time>,
+    settings: Res<TrackingSettings>,
+    snap_state: Res<SnapState>,
+    mut acquisition: ResMut<TrackingAcquisition>,
+    mut line_events: EventReader<LineCreatedEvent>,
+) {
+    // После построения отрезка захваченные точки сбрасываются
+    if line_events.read().count() > 0 || !settings.object_tracking {
+        acquisition.points.clear();
+        acquisition.hover = None;
+        return;
+    }
+
+    let Some(snap) = snap_state.snap else {
+        acquisition.hover = None;
+        return;
+    };
+
+    let elapsed = match acquisition.hover {
+        Some((point, elapsed)) if point.distance(snap.position) < 1e-4 => {
+            elapsed + time.delta_secs()
+        }
+        _ => 0.0,
+    };
+    if elapsed < settings.acquire_delay {
+        acquisition.hover = Some((snap.position, elapsed));
+        return;
+    }
+
+    // Повторное наведение на захваченную точку снимает захват
+    if let Some(index) = acquisition
+        .points
+        .iter()
+        .position(|point| point.distance(snap.position) < 1e-4)
+    {
+        acquisition.points.remove(index);
+        info!("Tracking point released: {:?}", snap.position);
+    } else {
+        if acquisition.points.len() >= settings.max_acquired {
+            acquisition.points.remove(0);
+        }
+        acquisition.points.push(snap.position);
+        info!("Tracking point acquired: {:?}", snap.position);
+    }
+    // Таймер запускается заново только после ухода с точки
+    acquisition.hover = Some((snap.position, f32::NEG_INFINITY));
+}
+
+/// Отрисовка пунктирных путей отслеживания и захваченных точек
+pub fn draw_tracking_system(
+    snap_state: Res<SnapState>,
+    settings: Res<TrackingSettings>,
+    acquisition: Res<TrackingAcquisition>,
+    render_origin: Res<RenderOrigin>,
+    mut gizmos: Gizmos,
+) {
+    let lift = Vec3::Y * 0.02;
+    let color = Color::srgb(0.3, 0.9, 0.3);
+
+    // Захваченные точки отмечаются небольшим крестиком
+    for point in &acquisition.points {
+        let c = render_origin.to_render(*point) + lift;
+        gizmos.line(c - Vec3::X * 0.1, c + Vec3::X * 0.1, color);
+        gizmos.line(c - Vec3::Z * 0.1, c + Vec3::Z * 0.1, color);
+    }
+
+    let Some(tracking) = snap_state.tracking else {
+        return;
+    };
+
+    match tracking.kind {
+        TrackingKind::Polar { distance, angle } | TrackingKind::Alignment { distance, angle } => {
+            let direction = DVec3::new(angle.cos(), 0.0, angle.sin());
+            let end = tracking.base + direction * (distance + settings.ray_length as f64);
+            draw_dashed_line(
+                &mut gizmos,
+                render_origin.to_render(tracking.base) + lift,
+                render_origin.to_render(end) + lift,
+                0.15,
+                0.1,
+                color,
+            );
+        }
+        TrackingKind::Intersection { other } => {
+            for origin in [tracking.base, other] {
+                let direction = (tracking.position - origin).normalize_or_zero();
+                let end = tracking.position + direction * settings.ray_length as f64 * 0.2;
+                draw_dashed_line(
+                    &mut gizmos,
+                    render_origin.to_render(origin) + lift,
+                    render_origin.to_render(end) + lift,
+                    0.15,
+                    0.1,
+                    color,
+                );
+            }
+        }
+        TrackingKind::Ortho => {}
+    }
+}
+
+/// Пунктирная линия из гизмо-отрезков
+pub fn draw_dashed_line(
+    gizmos: &mut Gizmos,
+    start: Vec3,
+    end: Vec3,
+    dash: f32,
+    gap: f32,
+    color: Color,
+) {
+    let length = start.distance(end);
+    if length < f32::EPSILON || dash <= 0.0 {
+        return;
+    }
+    let direction = (end - start) / length;
+    let mut position = 0.0;
+    while position < length {
+        let dash_end = (position + dash).min(length);
+        gizmos.line(
+            start + direction * position,
+            start + direction * dash_end,
+            color,
+        );
+        position += dash + gap;
+    }
+}
+
+/// Текст подсказки отслеживания, например "Полярный: 5.00 < 30.0°"
+pub fn tracking_tooltip(
+    tracking: &TrackingPoint,
+    coordinate_settings: &CoordinateSettings,
+    frame: &CoordinateFrame,
+) -> String {
+    match tracking.kind {
+        TrackingKind::Ortho => {
+            format!(
+                "Орто: {}",
+                formatting::format_line_length(
+                    tracking.base,
+                    tracking.position,
+                    coordinate_settings
+                )
+            )
+        }
+        TrackingKind::Polar { distance, angle } => format!(
+            "Полярный: {} < {}",
+            formatting::format_length(distance, coordinate_settings),
+            formatting::format_direction(frame.local_angle(angle), coordinate_settings)
+        ),
+        TrackingKind::Alignment { distance, angle } => format!(
+            "Отслеживание: {} < {}",
+            formatting::format_length(distance, coordinate_settings),
+            formatting::format_direction(frame.local_angle(angle), coordinate_settings)
+        ),
+        TrackingKind::Intersection { .. } => format!(
+            "Пересечение: {}",
+            formatting::format_cartesian(
+                frame.local_cartesian(tracking.position),
+                coordinate_settings
+            )
+        ),
+    }
+}
+
+/// F8 - ОРТО, F10 - полярное отслеживание, F11 - объектное отслеживание
+pub fn keyboard_tracking_system(
+    input: ActionInput,
+    mut tracking_events: EventWriter<TrackingEvent>,
+) {
+    if input.just_pressed(InputAction::ToggleOrtho) {
+        tracking_events.write(TrackingEvent::ToggleOrtho);
+    }
+    if input.just_pressed(InputAction::TogglePolarTracking) {
+        tracking_events.write(TrackingEvent::TogglePolar);
+    }
+    if input.just_pressed(InputAction::ToggleObjectTracking) {
+        tracking_events.write(TrackingEvent::ToggleObjectTracking);
+    }
+}
+
+/// Система обработки событий отслеживания
+pub fn handle_tracking_events(
+    mut tracking_events: EventReader<TrackingEvent>,
+    mut settings: ResMut<TrackingSettings>,
+) {
+    for event in tracking_events.read() {
+        match event {
+            TrackingEvent::ToggleOrtho => {
+                settings.ortho = !settings.ortho;
+                // ОРТО и полярное отслеживание взаимоисключающие
+                if settings.ortho {
+                    settings.polar_tracking = false;
+                }
+                info!("Ortho mode: {}", settings.ortho);
+            }
+            TrackingEvent::TogglePolar => {
+                settings.polar_tracking = !settings.polar_tracking;
+                if settings.polar_tracking {
+                    settings.ortho = false;
+                }
+                info!("Polar tracking: {}", settings.polar_tracking);
+            }
+            TrackingEvent::ToggleObjectTracking => {
+                settings.object_tracking = !settings.object_tracking;
+                info!("Object snap tracking: {}", settings.object_tracking);
+            }
+            TrackingEvent::CycleAngles => {
+                settings.angle_mode = settings.next_angle_mode();
+                info!("Polar tracking angles: {}", settings.angles_label());
+            }
+            TrackingEvent::SetCustomAngles(angles) => {
+                settings.custom_angles = angles.clone();
+                settings.angle_mode = PolarAngleMode::Custom;
+                info!("Polar tracking angles: {}", settings.angles_label());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coordinate_systems::AngleUnit, expression::Variables, render_origin::FAR_POINT};
+
+    #[test]
+    fn custom_angles_are_parsed_as_expressions() {
+        let mut vars = Variables::default();
+        vars.set("A", 30.0);
+        let context = Context::new(&vars, AngleUnit::Degrees);
+
+        // Запятые внутри вызовов функций не делят список
+        let angles =
+            parse_custom_angles("0, 22.5, 90 - 22.5, A*2, max(10, 20), 400", &context).unwrap();
+        assert_eq!(angles, vec![0.0, 22.5, 67.5, 60.0, 20.0, 400.0]);
+
+        let settings = TrackingSettings {
+            angle_mode: PolarAngleMode::Custom,
+            custom_angles: angles,
+            ..default()
+        };
+        assert_eq!(
+            settings.tracking_angles(),
+            vec![0.0, 22.5, 67.5, 60.0, 20.0, 40.0]
+        );
+        assert_eq!(settings.angles_label(), "[0, 22.5, 67.5, 60, 20, 400]°");
+
+        assert!(parse_custom_angles("", &context).is_err());
+        assert!(parse_custom_angles("15, , 30", &context).is_err());
+        assert!(parse_custom_angles("15, B", &context).is_err());
+    }
+
+    #[test]
+    fn ortho_and_polar_follow_axes_and_rays() {
+        let base = FAR_POINT;
+        // ОРТО выбирает ближайшую ось ПСК, в том числе повернутой
+        let point = base + DVec3::new(3.0, 0.0, 1.0);
+        assert!(ortho_constrain(base, point, 0.0).abs_diff_eq(base + DVec3::X * 3.0, 1e-9));
+        let point = base + DVec3::new(-1.0, 0.0, 4.0);
+        assert!(ortho_constrain(base, point, 0.0).abs_diff_eq(base + DVec3::Z * 4.0, 1e-9));
+        let rotated = ortho_constrain(base, base + DVec3::new(2.0, 0.0, 2.1), 45f64.to_radians());
+        assert!(rotated.abs_diff_eq(base + DVec3::new(2.05, 0.0, 2.05), 1e-9));
+
+        let mut settings = TrackingSettings {
+            angle_mode: PolarAngleMode::Increment(45.0),
+            ..default()
+        };
+        let angles: Vec<f64> = settings
+            .tracking_angles()
+            .into_iter()
+            .map(f64::from)
+            .collect();
+        assert_eq!(angles, [0.0, 45.0, 90.0, 135.0, 180.0, 225.0, 270.0, 315.0]);
+
+        // Точка рядом с лучом 45° проецируется на него
+        let (position, distance, angle) =
+            polar_track(base, base + DVec3::new(5.0, 0.0, 5.2), &angles, 0.3).unwrap();
+        assert!(position.abs_diff_eq(base + DVec3::new(5.1, 0.0, 5.1), 1e-9));
+        assert!((distance - 5.1 * 2f64.sqrt()).abs() < 1e-9);
+        assert!((angle - 45f64.to_radians()).abs() < 1e-12);
+        // Далеко от лучей и у самой базовой точки отслеживания нет
+        assert!(polar_track(base, base + DVec3::new(5.0, 0.0, 2.0), &angles, 0.3).is_none());
+        assert!(polar_track(base, base + DVec3::new(0.1, 0.0, 0.1), &angles, 0.3).is_none());
+
+        // Наборы углов сменяются по кругу
+        let mut modes = Vec::new();
+        for _ in 0..4 {
+            settings.angle_mode = settings.next_angle_mode();
+            modes.push(settings.angle_mode);
+        }
+        assert_eq!(
+            modes,
+            [
+                PolarAngleMode::Custom,
+                PolarAngleMode::Increment(15.0),
+                PolarAngleMode::Increment(30.0),
+                PolarAngleMode::Increment(45.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn object_tracking_finds_paths_and_their_intersections() {
+        let a = FAR_POINT;
+        let b = a + DVec3::new(10.0, 0.0, 5.0);
+        // Оси ПСК через каждую захваченную точку
+        let paths = alignment_paths(&[a, b], None, 0.0, None);
+        assert_eq!(paths.len(), 4);
+
+        // Горизонталь через a и вертикаль через b пересекаются в (10, 0)
+        let tracked = object_track(a + DVec3::new(10.1, 0.0, 0.1), &paths, 0.3).unwrap();
+        assert!(tracked
+            .position
+            .abs_diff_eq(a + DVec3::new(10.0, 0.0, 0.0), 1e-9));
+        assert!(matches!(tracked.kind, TrackingKind::Intersection { other } if other == b));
+
+        // Вдоль одного пути: расстояние от захваченной точки и направление к курсору
+        let tracked = object_track(a + DVec3::new(-4.0, 0.0, 0.1), &paths, 0.3).unwrap();
+        assert!(tracked.position.abs_diff_eq(a - DVec3::X * 4.0, 1e-9));
+        assert_eq!(tracked.base, a);
+        let TrackingKind::Alignment { distance, angle } = tracked.kind else {
+            panic!("ожидается путь выравнивания");
+        };
+        assert!((distance - 4.0).abs() < 1e-9);
+        assert!((angle - std::f64::consts::PI).abs() < 1e-12);
+        assert!(object_track(a + DVec3::new(4.0, 0.0, 2.0), &paths, 0.3).is_none());
+
+        // С полярным отслеживанием добавляются его углы (прямые без повторов)
+        // и пути из базовой точки
+        let polar = [0.0, 45.0, 90.0, 225.0];
+        let paths = alignment_paths(&[a], Some(b), 0.0, Some(&polar));
+        assert_eq!(paths.len(), 6);
+        let tracked = object_track(b + DVec3::new(3.0, 0.0, 3.2), &paths, 0.3).unwrap();
+        assert_eq!(tracked.base, b);
+        assert!(tracked
+            .position
+            .abs_diff_eq(b + DVec3::new(3.1, 0.0, 3.1), 1e-9));
+    }
+}
@@ -0,0 +1,704 @@
+use bevy::{
+    math::{DMat2, DMat4, DVec2, DVec3},
+    prelude::*,
+};
+
+use crate::{
+    attributes::AttributeDefinition,
+    blocks::BlockReference,
+    coordinate_systems::{
+        conversions, formatting, AngleDirection, CartesianPoint3D, CoordinateSettings,
+    },
+    events::{TransformEvent, TypedPointEvent},
+    input_field::{FieldInput, InputFieldKind},
+    keybindings::{ActionInput, InputAction},
+    line_drawing::{self, DrawableLine, LineEndpoint},
+    render_origin::{DocumentPosition, RenderOrigin},
+    selection::{ActiveCommandsMut, Selection},
+    snapping::SnapState,
+    ucs::CoordinateFrame,
+};
+
+/// Вид преобразования, подбираемого по парам точек
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FitKind {
+    /// Перенос, поворот и единый масштаб
+    Similarity,
+    /// Произвольное аффинное преобразование (с перекосом и разными масштабами)
+    Affine,
+}
+
+impl FitKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FitKind::Similarity => "Подобие",
+            FitKind::Affine => "Аффинное",
+        }
+    }
+
+    /// Наименьшее число пар точек для подбора
+    pub fn min_pairs(self) -> usize {
+        match self {
+            FitKind::Similarity => 2,
+            FitKind::Affine => 3,
+        }
+    }
+}
+
+/// Преобразования в координатах чертежа: x, y — план, z — высота.
+/// Матрицы 4×4 действуют на столбец (x, y, z, 1).
+pub mod affine {
+    use super::*;
+    use crate::{
+        coordinate_systems::parsing,
+        expression::{self, Context},
+    };
+
+    /// Подобранное преобразование и невязки по каждой паре точек
+    #[derive(Clone, Debug)]
+    pub struct Fit {
+        pub matrix: DMat4,
+        pub residuals: Vec<f64>,
+    }
+
+    impl Fit {
+        /// Среднеквадратическая невязка
+        pub fn rms(&self) -> f64 {
+            if self.residuals.is_empty() {
+                return 0.0;
+            }
+            let sum: f64 = self.residuals.iter().map(|r| r * r).sum();
+            (sum / self.residuals.len() as f64).sqrt()
+        }
+    }
+
+    /// Плоское преобразование `x' = a·x + b·y + c`, `y' = d·x + e·y + f` (высота не меняется)
+    pub fn planar(rows: [[f64; 3]; 2]) -> DMat4 {
+        let [[a, b, c], [d, e, f]] = rows;
+        DMat4::from_cols_array_2d(&[
+            [a, d, 0.0, 0.0],
+            [b, e, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [c, f, 0.0, 1.0],
+        ])
+    }
+
+    /// Применение преобразования к мировой точке документа
+    pub fn transform_world_point(matrix: &DMat4, point: DVec3) -> DVec3 {
+        let cartesian = conversions::world_to_cartesian_3d(point);
+        let moved = matrix.transform_point3(DVec3::new(cartesian.x, cartesian.y, cartesian.z));
+        conversions::cartesian_3d_to_world(CartesianPoint3D::new(moved.x, moved.y, moved.z))
+    }
+
+    /// Разбор матрицы 3×3 (плоское преобразование) или 4×4 по строкам:
+    /// `a, b, c; d, e, f; 0, 0, 1`. Элементы могут быть выражениями.
+    pub fn parse_matrix(input: &str, context: &Context) -> Result<DMat4, String> {
+        let rows = expression::split_top_level(input.trim(), ';')
+            .into_iter()
+            .map(|row| {
+                expression::split_top_level(row, ',')
+                    .into_iter()
+                    .map(|value| expression::evaluate(value, context).map_err(|e| e.to_string()))
+                    .collect::<Result<Vec<f64>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let size = rows.len();
+        if !(size == 3 || size == 4) || rows.iter().any(|row| row.len() != size) {
+            return Err("ожидается матрица 3×3 или 4×4: строки через «;»".to_string());
+        }
+        // Последняя строка аффинной матрицы — (0, …, 0, 1)
+        let last = &rows[size - 1];
+        let affine_row = last[..size - 1].iter().all(|value| value.abs() < 1e-12)
+            && (last[size - 1] - 1.0).abs() < 1e-12;
+        if !affine_row {
+            return Err(format!(
+                "последняя строка должна быть ({}1): перспектива не поддерживается",
+                "0, ".repeat(size - 1)
+            ));
+        }
+
+        let matrix = if size == 3 {
+            planar([
+                [rows[0][0], rows[0][1], rows[0][2]],
+                [rows[1][0], rows[1][1], rows[1][2]],
+            ])
+        } else {
+            let mut columns = [[0.0; 4]; 4];
+            for (i, row) in rows.iter().enumerate() {
+                for (j, value) in row.iter().enumerate() {
+                    columns[j][i] = *value;
+                }
+            }
+            DMat4::from_cols_array_2d(&columns)
+        };
+        if matrix.determinant().abs() < 1e-12 {
+            return Err("вырожденная матрица".to_string());
+        }
+        Ok(matrix)
+    }
+
+    /// Разбор цепочки шагов через «;», которые выполняются слева направо:
+    /// - `move dx, dy[, dz]` — перенос;
+    /// - `rotate θ[, cx, cy]` — поворот вокруг точки (по умолчанию — начала координат);
+    /// - `scale k` или `scale kx, ky[, kz]` — масштаб относительно начала координат;
+    /// - `shear kx, ky` — сдвиг: `x + kx·y`, `y + ky·x`.
+    ///
+    /// Угол θ — в текущих единицах, положительное направление — из настроек углов.
+    pub fn parse_steps(
+        input: &str,
+        context: &Context,
+        settings: &CoordinateSettings,
+    ) -> Result<DMat4, String> {
+        let mut matrix = DMat4::IDENTITY;
+        for step in expression::split_top_level(input.trim(), ';') {
+            let step = step.trim();
+            let (name, arguments) = step.split_once(char::is_whitespace).unwrap_or((step, ""));
+            let arguments: Vec<&str> = expression::split_top_level(arguments, ',');
+            let values = |from: usize| {
+                arguments[from..]
+                    .iter()
+                    .map(|value| expression::evaluate(value, context).map_err(|e| e.to_string()))
+                    .collect::<Result<Vec<f64>, String>>()
+            };
+
+            let step_matrix = match name.to_lowercase().as_str() {
+                "move" | "перенос" => match values(0)?.as_slice() {
+                    [dx, dy] => DMat4::from_translation(DVec3::new(*dx, *dy, 0.0)),
+                    [dx, dy, dz] => DMat4::from_translation(DVec3::new(*dx, *dy, *dz)),
+                    _ => return Err("move: ожидается «dx, dy[, dz]»".to_string()),
+                },
+                "rotate" | "поворот" => {
+                    let Some(angle) = arguments.first() else {
+                        return Err("rotate: ожидается «θ[, cx, cy]»".to_string());
+                    };
+                    let mut angle = parsing::parse_angle(angle, context)?;
+                    if settings.angle_direction == AngleDirection::Clockwise {
+                        angle = -angle;
+                    }
+                    let center = match values(1)?.as_slice() {
+                        [] => DVec3::ZERO,
+                        [cx, cy] => DVec3::new(*cx, *cy, 0.0),
+                        _ => return Err("rotate: ожидается «θ[, cx, cy]»".to_string()),
+                    };
+                    DMat4::from_translation(center)
+                        * DMat4::from_rotation_z(angle)
+                        * DMat4::from_translation(-center)
+                }
+                "scale" | "масштаб" => match values(0)?.as_slice() {
+                    [k] => DMat4::from_scale(DVec3::new(*k, *k, *k)),
+                    [kx, ky] => DMat4::from_scale(DVec3::new(*kx, *ky, 1.0)),
+                    [kx, ky, kz] => DMat4::from_scale(DVec3::new(*kx, *ky, *kz)),
+                    _ => return Err("scale: ожидается «k» или «kx, ky[, kz]»".to_string()),
+                },
+                "shear" | "сдвиг" => match values(0)?.as_slice() {
+                    [kx, ky] => planar([[1.0, *kx, 0.0], [*ky, 1.0, 0.0]]),
+                    _ => return Err("shear: ожидается «kx, ky»".to_string()),
+                },
+                "" => return Err("пустой шаг".to_string()),
+                other => {
+                    return Err(format!(
+                        "неизвестный шаг «{}»: move, rotate, scale, shear",
+                        other
+                    ))
+                }
+            };
+            matrix = step_matrix * matrix;
+        }
+        if matrix.determinant().abs() < 1e-12 {
+            return Err("вырожденное преобразование".to_string());
+        }
+        Ok(matrix)
+    }
+
+    /// Подбор преобразования по парам (исходная, целевая) точек в плане
+    /// методом наименьших квадратов. Высота точек не учитывается и не меняется.
+    pub fn fit(kind: FitKind, pairs: &[(DVec2, DVec2)]) -> Result<Fit, String> {
+        if pairs.len() < kind.min_pairs() {
+            return Err(format!(
+                "нужно не меньше {} пар точек, указано {}",
+                kind.min_pairs(),
+                pairs.len()
+            ));
+        }
+        let matrix = match kind {
+            FitKind::Similarity => fit_similarity(pairs)?,
+            FitKind::Affine => fit_affine(pairs)?,
+        };
+        let residuals = pairs
+            .iter()
+            .map(|(source, destination)| {
+                let moved = matrix.transform_point3(source.extend(0.0));
+                moved.truncate().distance(*destination)
+            })
+            .collect();
+        Ok(Fit { matrix, residuals })
+    }
+
+    /// Подобие по Умеямe: поворот и масштаб по центрированным точкам
+    fn fit_similarity(pairs: &[(DVec2, DVec2)]) -> Result<DMat4, String> {
+        let count = pairs.len() as f64;
+        let source_center = pairs.iter().map(|(s, _)| *s).sum::<DVec2>() / count;
+        let destination_center = pairs.iter().map(|(_, d)| *d).sum::<DVec2>() / count;
+
+        let (mut dot, mut cross, mut spread) = (0.0, 0.0, 0.0);
+        for (source, destination) in pairs {
+            let a = *source - source_center;
+            let b = *destination - destination_center;
+            dot += a.dot(b);
+            cross += a.perp_dot(b);
+            spread += a.length_squared();
+        }
+        if spread < 1e-18 {
+            return Err("исходные точки совпадают".to_string());
+        }
+
+        let angle = cross.atan2(dot);
+        let scale = dot.hypot(cross) / spread;
+        let rotation = DVec2::from_angle(angle) * scale;
+        let offset = destination_center - rotation.rotate(source_center);
+        Ok(planar([
+            [rotation.x, -rotation.y, offset.x],
+            [rotation.y, rotation.x, offset.y],
+        ]))
+    }
+
+    /// Аффинное преобразование: нормальные уравнения по центрированным точкам
+    /// (без центрирования координаты порядка 10⁶ делают систему плохо обусловленной)
+    fn fit_affine(pairs: &[(DVec2, DVec2)]) -> Result<DMat4, String> {
+        let count = pairs.len() as f64;
+        let source_center = pairs.iter().map(|(s, _)| *s).sum::<DVec2>() / count;
+        let destination_center = pairs.iter().map(|(_, d)| *d).sum::<DVec2>() / count;
+
+        let mut normal = DMat2::ZERO;
+        let (mut right_x, mut right_y) = (DVec2::ZERO, DVec2::ZERO);
+        for (source, destination) in pairs {
+            let a = *source - source_center;
+            let b = *destination - destination_center;
+            normal += DMat2::from_cols(a * a.x, a * a.y);
+            right_x += a * b.x;
+            right_y += a * b.y;
+        }
+        // Определитель сравнивается с произведением диагональных элементов,
+        // чтобы проверка не зависела от разброса точек
+        let diagonal = normal.x_axis.x * normal.y_axis.y;
+        if diagonal <= 1e-18 || normal.determinant() <= 1e-12 * diagonal {
+            return Err("исходные точки лежат на одной прямой".to_string());
+        }
+        let inverse = normal.inverse();
+        let x = inverse * right_x;
+        let y = inverse * right_y;
+        let linear = DMat2::from_cols(DVec2::new(x.x, y.x), DVec2::new(x.y, y.y));
+        let offset = destination_center - linear * source_center;
+        Ok(planar([[x.x, x.y, offset.x], [y.x, y.y, offset.y]]))
+    }
+}
+
+/// Выравнивание по парам точек: исходная, целевая, исходная, целевая…
+#[derive(Clone, Debug)]
+pub struct AlignPick {
+    pub kind: FitKind,
+    pub points: Vec<DVec3>,
+    /// Клик по кнопке, начавший указание, не должен стать точкой
+    armed: bool,
+}
+
+impl AlignPick {
+    /// Полные пары точек в плане
+    pub fn pairs(&self) -> Vec<(DVec2, DVec2)> {
+        self.points
+            .chunks_exact(2)
+            .map(|pair| {
+                let source = conversions::world_to_cartesian(pair[0]);
+                let destination = conversions::world_to_cartesian(pair[1]);
+                (
+                    DVec2::new(source.x, source.y),
+                    DVec2::new(destination.x, destination.y),
+                )
+            })
+            .collect()
+    }
+
+    /// Подсказка для текущей точки
+    pub fn prompt(&self) -> String {
+        let pair = self.points.len() / 2 + 1;
+        let what = if self.points.len().is_multiple_of(2) {
+            "исходную"
+        } else {
+            "целевую"
+        };
+        format!(
+            "{}: укажите {} точку пары {} (пар не меньше {})",
+            self.kind.label(),
+            what,
+            pair,
+            self.kind.min_pairs()
+        )
+    }
+}
+
+/// Состояние команды преобразования выделенного
+#[derive(Resource, Default)]
+pub struct TransformTool {
+    pub align: Option<AlignPick>,
+    /// Итог последнего преобразования (невязки выравнивания)
+    pub report: Option<String>,
+}
+
+impl TransformTool {
+    pub fn is_picking(&self) -> bool {
+        self.align.is_some()
+    }
+}
+
+/// Указание пар точек для выравнивания (клики и ввод с клавиатуры)
+pub fn align_pick_system(
+    input: ActionInput,
+    mut snap_state: ResMut<SnapState>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
+    mut tool: ResMut<TransformTool>,
+) {
+    let Some(mut pick) = tool.align.clone() else {
+        return;
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        tool.align = None;
+        info!("Align cancelled");
+        return;
+    }
+    if !pick.armed {
+        typed_point_events.clear();
+        pick.armed = true;
+        tool.align = Some(pick);
+        return;
+    }
+
+    let mut points: Vec<DVec3> = typed_point_events.read().map(|event| event.point).collect();
+    if input.just_pressed(InputAction::PickPoint) {
+        if let Some(point) = snap_state.point() {
+            points.push(point);
+        }
+    }
+    snap_state.remember(points.iter().copied());
+    if !points.is_empty() {
+        pick.points.extend(points);
+        tool.align = Some(pick);
+    }
+}
+
+/// Система обработки событий преобразования выделенного
+pub fn handle_transform_events(
+    mut transform_events: EventReader<TransformEvent>,
+    mut active: ActiveCommandsMut,
+    selection: Res<Selection>,
+    coordinate_settings: Res<CoordinateSettings>,
+    snap_state: Res<SnapState>,
+    mut editor: LineEditor,
+) {
+    for event in transform_events.read() {
+        match *event {
+            TransformEvent::Apply(matrix) => {
+                let count = editor.transform(
+                    &selection,
+                    &matrix,
+                    &active.ucs_manager.frame(snap_state.last_point),
+                );
+                active.transform_tool.report = Some(format!("Преобразовано объектов: {}", count));
+            }
+            TransformEvent::StartAlign(kind) => {
+                // Новая команда прерывает рисование и другие указания точек
+                active.cancel_all();
+                active.transform_tool.align = Some(AlignPick {
+                    kind,
+                    points: Vec::new(),
+                    armed: false,
+                });
+                active.transform_tool.report = None;
+            }
+            TransformEvent::FinishAlign => {
+                let Some(pick) = active.transform_tool.align.take() else {
+                    continue;
+                };
+                active.transform_tool.report = Some(match affine::fit(pick.kind, &pick.pairs()) {
+                    Ok(fit) => {
+                        let count = editor.transform(
+                            &selection,
+                            &fit.matrix,
+                            &active.ucs_manager.frame(snap_state.last_point),
+                        );
+                        let residuals: Vec<String> = fit
+                            .residuals
+                            .iter()
+                            .enumerate()
+                            .map(|(i, residual)| {
+                                format!(
+                                    "{}: {}",
+                                    i + 1,
+                                    formatting::format_length(*residual, &coordinate_settings)
+                                )
+                            })
+                            .collect();
+                        info!(
+                            "Align ({:?}) applied to {} objects, residuals: {}",
+                            pick.kind,
+                            count,
+                            residuals.join(", ")
+                        );
+                        format!(
+                            "{}: объектов {}, СКО {}\nНевязки: {}",
+                            pick.kind.label(),
+                            count,
+                            formatting::format_length(fit.rms(), &coordinate_settings),
+                            residuals.join(", ")
+                        )
+                    }
+                    Err(error) => {
+                        warn!("Align failed: {}", error);
+                        format!("Выравнивание не выполнено: {}", error)
+                    }
+                });
+            }
+            TransformEvent::CancelAlign => active.transform_tool.align = None,
+        }
+    }
+}
+
+/// Поля матрицы и шагов преобразования выделенного
+pub fn submit_field(input: &mut FieldInput) -> Result<String, String> {
+    let matrix = if input.kind == InputFieldKind::TransformMatrix {
+        affine::parse_matrix(input.text, &input.context)?
+    } else {
+        affine::parse_steps(input.text, &input.context, input.coordinate_settings)?
+    };
+    input.events.transform.write(TransformEvent::Apply(matrix));
+    Ok("применено к выделенному".to_string())
+}
+
+/// Изменение геометрии отрезков вместе с их маркерами концов, а также вставок
+/// блоков и определений атрибутов. Меш отрезка новой длины с его типом линии
+/// строит `apply_styles_system`.
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct LineEditor<'w, 's> {
+    line_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut DrawableLine,
+            &'static mut DocumentPosition,
+            &'static mut Transform,
+        ),
+        Without<LineEndpoint>,
+    >,
+    endpoint_query:
+        Query<'w, 's, &'static mut DocumentPosition, (With<LineEndpoint>, Without<DrawableLine>)>,
+    reference_query: Query<'w, 's, (Entity, &'static mut BlockReference)>,
+    definition_query: Query<'w, 's, (Entity, &'static mut AttributeDefinition)>,
+}
+
+impl LineEditor<'_, '_> {
+    /// Преобразование выделенных отрезков; возвращает число измененных объектов.
+    /// Маркер конца переносится, только если он не принадлежит невыделенному отрезку.
+    pub fn transform(
+        &mut self,
+        selection: &Selection,
+        matrix: &DMat4,
+        frame: &CoordinateFrame,
+    ) -> usize {
+        let mut moved_ends = Vec::new();
+        let mut fixed_ends = Vec::new();
+
+        for (entity, mut line, mut position, mut transform) in self.line_query.iter_mut() {
+            if !selection.contains(entity) {
+                fixed_ends.extend([line.start, line.end]);
+                continue;
+            }
+            let (start, end) = (
+                affine::transform_world_point(matrix, line.start),
+                affine::transform_world_point(matrix, line.end),
+            );
+            moved_ends.extend([(line.start, start), (line.end, end)]);
+
+            let (center, rotation, _) = line_drawing::line_placement(start, end);
+            line.start = start;
+            line.end = end;
+            line.start_coord = frame.coordinate_point(start);
+            line.end_coord = frame.coordinate_point(end);
+            position.0 = center;
+            transform.rotation = rotation;
+        }
+
+        let same = |a: DVec3, b: DVec3| a.distance(b) < 1e-9;
+        for mut position in self.endpoint_query.iter_mut() {
+            if fixed_ends.iter().any(|end| same(*end, position.0)) {
+                continue;
+            }
+            if let Some((_, new)) = moved_ends.iter().find(|(old, _)| same(*old, position.0)) {
+                position.0 = *new;
+            }
+        }
+
+        // Вставка остается подобной определению: перенос, поворот и масштаб
+        // берутся по образу ее оси X, сдвиг и неравномерный масштаб отбрасываются
+        let mut others = 0;
+        for (entity, mut reference) in self.reference_query.iter_mut() {
+            if !selection.contains(entity) {
+                continue;
+            }
+            let position = affine::transform_world_point(matrix, reference.position);
+            let axis = affine::transform_world_point(matrix, reference.place(DVec3::X)) - position;
+            reference.position = position;
+            reference.rotation = axis.z.atan2(axis.x);
+            reference.scale = DVec2::new(axis.x, axis.z).length();
+            others += 1;
+        }
+        for (entity, mut definition) in self.definition_query.iter_mut() {
+            if selection.contains(entity) {
+                definition.position = affine::transform_world_point(matrix, definition.position);
+                others += 1;
+            }
+        }
+        moved_ends.len() / 2 + others
+    }
+}
+
+/// Исходные и целевые точки выравнивания и связывающие их стрелки
+pub fn draw_align_pick_system(
+    tool: Res<TransformTool>,
+    render_origin: Res<RenderOrigin>,
+    snap_state: Res<SnapState>,
+    mut gizmos: Gizmos,
+) {
+    let Some(pick) = &tool.align else {
+        return;
+    };
+    let source_color = Color::srgb(1.0, 0.6, 0.2);
+    let destination_color = Color::srgb(0.2, 0.9, 1.0);
+    for (index, point) in pick.points.iter().enumerate() {
+        let color = if index.is_multiple_of(2) {
+            source_color
+        } else {
+            destination_color
+        };
+        gizmos.sphere(render_origin.to_render(*point), 0.08, color);
+    }
+    for pair in pick.points.chunks_exact(2) {
+        gizmos.arrow(
+            render_origin.to_render(pair[0]),
+            render_origin.to_render(pair[1]),
+            destination_color,
+        );
+    }
+    // Незавершенная пара тянется к курсору
+    if let (true, Some(source), Some(cursor)) = (
+        !pick.points.len().is_multiple_of(2),
+        pick.points.last(),
+        snap_state.point(),
+    ) {
+        gizmos.line(
+            render_origin.to_render(*source),
+            render_origin.to_render(cursor),
+            source_color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::affine::*;
+    use super::*;
+    use crate::expression::{Context, Variables};
+    use bevy::math::DMat3;
+
+    fn assert_maps(matrix: &DMat4, from: DVec3, to: DVec3) {
+        let moved = matrix.transform_point3(from);
+        assert!(
+            moved.abs_diff_eq(to, 1e-9),
+            "{from} -> {moved}, ожидалось {to}"
+        );
+    }
+
+    #[test]
+    fn matrices_and_steps_are_parsed() {
+        let vars = Variables::default();
+        let context = Context::new(&vars, crate::coordinate_systems::AngleUnit::Degrees);
+        let settings = CoordinateSettings::default();
+
+        let matrix = parse_matrix("0, -1, 10; 1, 0, 5; 0, 0, 1", &context).unwrap();
+        assert_maps(
+            &matrix,
+            DVec3::new(1.0, 0.0, 2.0),
+            DVec3::new(10.0, 6.0, 2.0),
+        );
+        let matrix =
+            parse_matrix("2, 0, 0, 1; 0, 2, 0, 2; 0, 0, 2, 3; 0, 0, 0, 1", &context).unwrap();
+        assert_maps(&matrix, DVec3::ONE, DVec3::new(3.0, 4.0, 5.0));
+        assert!(parse_matrix("1, 0, 0; 0, 1, 0; 1, 0, 1", &context).is_err());
+        assert!(parse_matrix("1, 0; 0, 1", &context).is_err());
+        assert!(parse_matrix("1, 2, 0; 2, 4, 0; 0, 0, 1", &context).is_err());
+
+        // Поворот вокруг (1, 0), затем перенос
+        let steps = parse_steps("rotate 90, 1, 0; move 0, 0, 5", &context, &settings).unwrap();
+        assert_maps(&steps, DVec3::new(2.0, 0.0, 0.0), DVec3::new(1.0, 1.0, 5.0));
+        let steps = parse_steps("shear 1, 0; scale 2, 3", &context, &settings).unwrap();
+        assert_maps(&steps, DVec3::new(1.0, 1.0, 1.0), DVec3::new(4.0, 3.0, 1.0));
+        assert!(parse_steps("spin 30", &context, &settings).is_err());
+    }
+
+    /// Перенос проверяется невязками: при координатах порядка 10⁶
+    /// он восстанавливается с точностью лишь около 10⁻⁵
+    fn assert_same_linear_part(actual: &DMat4, expected: &DMat4) {
+        let linear = |m: &DMat4| DMat3::from_mat4(*m);
+        assert!(
+            linear(actual).abs_diff_eq(linear(expected), 1e-9),
+            "{actual} vs {expected}"
+        );
+    }
+
+    /// Пары точек, переведенные известным преобразованием, с небольшим шумом
+    fn pairs(matrix: &DMat4, noise: f64) -> Vec<(DVec2, DVec2)> {
+        let sources = [
+            DVec2::new(500_000.0, 6_100_000.0),
+            DVec2::new(500_120.5, 6_100_010.0),
+            DVec2::new(500_080.0, 6_100_210.25),
+            DVec2::new(499_950.0, 6_100_150.0),
+        ];
+        sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let moved = matrix.transform_point3(source.extend(0.0)).truncate();
+                let sign = if i.is_multiple_of(2) { 1.0 } else { -1.0 };
+                (*source, moved + DVec2::splat(noise * sign))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn similarity_fit_recovers_transform() {
+        let expected = DMat4::from_translation(DVec3::new(-499_000.0, -6_099_000.0, 0.0))
+            * DMat4::from_rotation_z(0.3)
+            * DMat4::from_scale(DVec3::new(1.0005, 1.0005, 1.0));
+        let fit = fit(FitKind::Similarity, &pairs(&expected, 0.0)).unwrap();
+        assert!(fit.rms() < 1e-6, "rms {}", fit.rms());
+        assert_same_linear_part(&fit.matrix, &expected);
+
+        let noisy = affine::fit(FitKind::Similarity, &pairs(&expected, 0.01)).unwrap();
+        assert!(noisy.rms() > 1e-3 && noisy.rms() < 0.05);
+        assert_eq!(noisy.residuals.len(), 4);
+        assert!(affine::fit(FitKind::Similarity, &pairs(&expected, 0.0)[..1]).is_err());
+    }
+
+    #[test]
+    fn affine_fit_recovers_shear() {
+        let expected = planar([[1.2, 0.3, 15.0], [-0.1, 0.9, -40.0]]);
+        let fit = affine::fit(FitKind::Affine, &pairs(&expected, 0.0)).unwrap();
+        assert!(fit.rms() < 1e-6, "rms {}", fit.rms());
+        assert_same_linear_part(&fit.matrix, &expected);
+
+        let collinear: Vec<(DVec2, DVec2)> = (0..3)
+            .map(|i| (DVec2::splat(i as f64), DVec2::splat(i as f64)))
+            .collect();
+        assert!(affine::fit(FitKind::Affine, &collinear).is_err());
+    }
+}
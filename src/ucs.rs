@@ -0,0 +1,572 @@
+use bevy::{
+    math::{DVec2, DVec3},
+    prelude::*,
+};
+
+use crate::{
+    coordinate_systems::{
+        conversions, formatting, CartesianPoint, CartesianPoint3D, CoordinatePoint,
+        CoordinateSettings, CylindricalPoint, PolarPoint,
+    },
+    events::{TypedPointEvent, UcsEvent},
+    input_field::FieldInput,
+    keybindings::{ActionInput, InputAction},
+    render_origin::RenderOrigin,
+    selection::ActiveCommandsMut,
+    snapping::SnapState,
+};
+
+/// Именованная пользовательская система координат (ПСК) на плоскости XZ
+#[derive(Clone, Debug)]
+pub struct Ucs {
+    pub name: String,
+    pub origin: DVec3,
+    pub angle: f64,     // поворот оси X относительно мировой (радианы)
+    pub mirrored: bool, // ось Y направлена в другую сторону (задается третьей точкой)
+}
+
+impl Ucs {
+    pub fn world() -> Self {
+        Self {
+            name: "Мировая".to_string(),
+            origin: DVec3::ZERO,
+            angle: 0.0,
+            mirrored: false,
+        }
+    }
+
+    /// ПСК по точкам: 1 - начало, 2 - направление оси X, 3 - сторона оси Y
+    pub fn from_points(name: String, points: &[DVec3]) -> Result<Self, String> {
+        let Some(origin) = points.first().copied() else {
+            return Err("не указано начало ПСК".to_string());
+        };
+
+        let mut ucs = Self {
+            name,
+            origin,
+            angle: 0.0,
+            mirrored: false,
+        };
+        if let Some(x_point) = points.get(1) {
+            let x_axis = DVec2::new(x_point.x - origin.x, x_point.z - origin.z);
+            if x_axis.length() < 1e-6 {
+                return Err("точка на оси X совпадает с началом".to_string());
+            }
+            ucs.angle = x_axis.to_angle();
+
+            if let Some(y_point) = points.get(2) {
+                let y_side =
+                    x_axis.perp_dot(DVec2::new(y_point.x - origin.x, y_point.z - origin.z));
+                if y_side.abs() < 1e-6 {
+                    return Err("третья точка лежит на оси X".to_string());
+                }
+                ucs.mirrored = y_side < 0.0;
+            }
+        }
+        Ok(ucs)
+    }
+}
+
+/// Полюс полярных координат
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum PolarPole {
+    /// Начало активной ПСК
+    #[default]
+    Origin,
+    /// Последняя указанная точка
+    LastPoint,
+    /// Произвольная точка
+    Fixed(DVec3),
+}
+
+/// Система отсчета для ввода и вывода координат: активная ПСК и полюс
+#[derive(Clone, Copy, Debug)]
+pub struct CoordinateFrame {
+    pub origin: DVec3,
+    pub angle: f64,
+    pub mirrored: bool,
+    pub pole: DVec3,
+}
+
+impl Default for CoordinateFrame {
+    fn default() -> Self {
+        Self {
+            origin: DVec3::ZERO,
+            angle: 0.0,
+            mirrored: false,
+            pole: DVec3::ZERO,
+        }
+    }
+}
+
+impl CoordinateFrame {
+    fn y_sign(&self) -> f64 {
+        if self.mirrored {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Вектор в осях ПСК -> мировой вектор
+    pub fn vector_to_world(&self, local: DVec2) -> DVec3 {
+        let rotated =
+            DVec2::from_angle(self.angle).rotate(DVec2::new(local.x, local.y * self.y_sign()));
+        conversions::cartesian_to_world(CartesianPoint::new(rotated.x, rotated.y))
+    }
+
+    /// Вектор в осях ПСК с высотой -> мировой вектор
+    pub fn vector_3d_to_world(&self, local: CartesianPoint3D) -> DVec3 {
+        let planar =
+            conversions::world_to_cartesian(self.vector_to_world(DVec2::new(local.x, local.y)));
+        conversions::cartesian_3d_to_world(CartesianPoint3D::new(planar.x, planar.y, local.z))
+    }
+
+    /// Мировой вектор -> вектор в осях ПСК
+    pub fn vector_to_local(&self, world: DVec3) -> DVec2 {
+        let flat = conversions::world_to_cartesian(world);
+        let rotated = DVec2::from_angle(-self.angle).rotate(DVec2::new(flat.x, flat.y));
+        DVec2::new(rotated.x, rotated.y * self.y_sign())
+    }
+
+    /// Декартовы координаты точки в ПСК
+    pub fn local_cartesian(&self, world: DVec3) -> CartesianPoint {
+        let local = self.vector_to_local(world - self.origin);
+        CartesianPoint::new(local.x, local.y)
+    }
+
+    /// Мировая точка по декартовым координатам в ПСК
+    pub fn cartesian_point(&self, point: CartesianPoint) -> DVec3 {
+        self.origin + self.vector_to_world(DVec2::new(point.x, point.y))
+    }
+
+    /// Полярные координаты относительно полюса, угол - от оси X ПСК
+    pub fn local_polar(&self, world: DVec3) -> PolarPoint {
+        let local = self.vector_to_local(world - self.pole);
+        conversions::cartesian_to_polar(CartesianPoint::new(local.x, local.y))
+    }
+
+    /// Мировая точка по полярным координатам относительно полюса
+    pub fn polar_point(&self, point: PolarPoint) -> DVec3 {
+        let local = conversions::polar_to_cartesian(point);
+        self.pole + self.vector_to_world(DVec2::new(local.x, local.y))
+    }
+
+    /// Угол в ПСК -> мировой угол (оба в радианах)
+    pub fn world_angle(&self, local_angle: f64) -> f64 {
+        self.angle + self.y_sign() * local_angle
+    }
+
+    /// Мировой угол -> угол в ПСК в диапазоне (-π, π]
+    pub fn local_angle(&self, world_angle: f64) -> f64 {
+        let angle = self.y_sign() * (world_angle - self.angle);
+        angle.sin().atan2(angle.cos())
+    }
+
+    /// Угол наклона отрезка относительно оси X ПСК
+    pub fn line_angle(&self, start: DVec3, end: DVec3) -> f64 {
+        let direction = self.vector_to_local(end - start);
+        direction.y.atan2(direction.x)
+    }
+
+    /// Точка в декартовых координатах ПСК (высота - от начала ПСК) и в полярных,
+    /// цилиндрических и сферических относительно полюса
+    pub fn coordinate_point(&self, world: DVec3) -> CoordinatePoint {
+        let cartesian = self.local_cartesian(world);
+        let polar = self.local_polar(world);
+        let cylindrical = CylindricalPoint {
+            r: polar.r,
+            theta: polar.theta,
+            h: world.y - self.pole.y,
+        };
+        CoordinatePoint {
+            world_position: world,
+            cartesian,
+            polar,
+            cartesian_3d: CartesianPoint3D::new(cartesian.x, cartesian.y, world.y - self.origin.y),
+            cylindrical,
+            spherical: conversions::cartesian_3d_to_spherical(
+                conversions::cylindrical_to_cartesian_3d(cylindrical),
+            ),
+        }
+    }
+}
+
+/// Что задается указанием точек
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PickTarget {
+    /// Новая ПСК по заданному числу точек (1-3)
+    Ucs(usize),
+    /// Полюс полярных координат
+    Pole,
+}
+
+impl PickTarget {
+    fn points_needed(&self) -> usize {
+        match self {
+            PickTarget::Ucs(count) => *count,
+            PickTarget::Pole => 1,
+        }
+    }
+}
+
+/// Указание точек для ПСК или полюса
+#[derive(Clone, Debug)]
+pub struct UcsPick {
+    pub target: PickTarget,
+    pub points: Vec<DVec3>,
+    /// Клик по кнопке, начавший указание, не должен стать точкой
+    armed: bool,
+}
+
+impl UcsPick {
+    /// Подсказка для текущей точки
+    pub fn prompt(&self) -> String {
+        match self.target {
+            PickTarget::Pole => "Укажите полюс (Esc - отмена)".to_string(),
+            PickTarget::Ucs(count) => {
+                let what = match self.points.len() {
+                    0 => "начало ПСК",
+                    1 => "точку на оси X",
+                    _ => "точку на стороне оси Y",
+                };
+                format!(
+                    "Укажите {} ({}/{}, Esc - отмена)",
+                    what,
+                    self.points.len() + 1,
+                    count
+                )
+            }
+        }
+    }
+}
+
+/// Список ПСК, активная ПСК и полюс полярных координат
+#[derive(Resource)]
+pub struct UcsManager {
+    /// Нулевая ПСК - мировая, ее нельзя удалить
+    pub systems: Vec<Ucs>,
+    pub active: usize,
+    pub pole: PolarPole,
+    pub picking: Option<UcsPick>,
+    created: usize,
+}
+
+impl Default for UcsManager {
+    fn default() -> Self {
+        Self {
+            systems: vec![Ucs::world()],
+            active: 0,
+            pole: PolarPole::Origin,
+            picking: None,
+            created: 0,
+        }
+    }
+}
+
+impl UcsManager {
+    pub fn active(&self) -> &Ucs {
+        &self.systems[self.active]
+    }
+
+    pub fn is_picking(&self) -> bool {
+        self.picking.is_some()
+    }
+
+    /// Положение полюса; без последней точки полюс совпадает с началом ПСК
+    pub fn pole_position(&self, last_point: Option<DVec3>) -> DVec3 {
+        match self.pole {
+            PolarPole::Origin => self.active().origin,
+            PolarPole::LastPoint => last_point.unwrap_or(self.active().origin),
+            PolarPole::Fixed(point) => point,
+        }
+    }
+
+    /// Система отсчета для ввода и вывода координат
+    pub fn frame(&self, last_point: Option<DVec3>) -> CoordinateFrame {
+        let ucs = self.active();
+        CoordinateFrame {
+            origin: ucs.origin,
+            angle: ucs.angle,
+            mirrored: ucs.mirrored,
+            pole: self.pole_position(last_point),
+        }
+    }
+
+    /// Описание полюса для панели настроек
+    pub fn pole_label(&self, settings: &CoordinateSettings) -> String {
+        match self.pole {
+            PolarPole::Origin => "начало ПСК".to_string(),
+            PolarPole::LastPoint => "последняя точка".to_string(),
+            PolarPole::Fixed(point) => {
+                formatting::format_cartesian(conversions::world_to_cartesian(point), settings)
+            }
+        }
+    }
+
+    fn start_pick(&mut self, target: PickTarget) {
+        self.picking = Some(UcsPick {
+            target,
+            points: Vec::new(),
+            armed: false,
+        });
+    }
+
+    /// Завершение указания: создание ПСК или установка полюса
+    fn finish_pick(&mut self, pick: UcsPick) {
+        match pick.target {
+            PickTarget::Pole => {
+                self.pole = PolarPole::Fixed(pick.points[0]);
+                info!("Polar pole set to {:?}", pick.points[0]);
+            }
+            PickTarget::Ucs(_) => {
+                let name = format!("ПСК{}", self.created + 1);
+                match Ucs::from_points(name, &pick.points) {
+                    Ok(ucs) => {
+                        info!(
+                            "UCS '{}' created: origin {:?}, angle {:.1}°",
+                            ucs.name,
+                            ucs.origin,
+                            ucs.angle.to_degrees()
+                        );
+                        self.created += 1;
+                        self.systems.push(ucs);
+                        self.active = self.systems.len() - 1;
+                    }
+                    Err(error) => warn!("UCS not created: {}", error),
+                }
+            }
+        }
+    }
+}
+
+/// Система обработки событий ПСК
+pub fn handle_ucs_events(mut ucs_events: EventReader<UcsEvent>, mut active: ActiveCommandsMut) {
+    for event in ucs_events.read() {
+        match event {
+            UcsEvent::SetWorld => active.ucs_manager.active = 0,
+            UcsEvent::CycleNext => {
+                active.ucs_manager.active =
+                    (active.ucs_manager.active + 1) % active.ucs_manager.systems.len();
+            }
+            UcsEvent::DeleteActive => {
+                if active.ucs_manager.active == 0 {
+                    warn!("World coordinate system cannot be deleted");
+                } else {
+                    let index = active.ucs_manager.active;
+                    let removed = active.ucs_manager.systems.remove(index);
+                    active.ucs_manager.active = 0;
+                    info!("UCS '{}' deleted", removed.name);
+                }
+            }
+            UcsEvent::Rename(name) => {
+                let name = name.trim();
+                if active.ucs_manager.active == 0 {
+                    warn!("World coordinate system cannot be renamed");
+                } else if !name.is_empty() {
+                    let index = active.ucs_manager.active;
+                    active.ucs_manager.systems[index].name = name.to_string();
+                }
+            }
+            UcsEvent::Define(count) => {
+                // Новая команда прерывает рисование и другие указания точек
+                active.cancel_all();
+                active
+                    .ucs_manager
+                    .start_pick(PickTarget::Ucs((*count).clamp(1, 3)));
+            }
+            UcsEvent::PoleAtOrigin => active.ucs_manager.pole = PolarPole::Origin,
+            UcsEvent::PoleAtLastPoint => active.ucs_manager.pole = PolarPole::LastPoint,
+            UcsEvent::PickPole => {
+                active.cancel_all();
+                active.ucs_manager.start_pick(PickTarget::Pole);
+            }
+        }
+    }
+}
+
+/// Поле имени активной ПСК
+pub fn submit_field(input: &mut FieldInput, manager: &UcsManager) -> Result<String, String> {
+    let name = input.text.trim();
+    if name.is_empty() {
+        return Err("пустое имя".to_string());
+    }
+    if manager.active == 0 {
+        return Err("мировую систему нельзя переименовать".to_string());
+    }
+    input.events.ucs.write(UcsEvent::Rename(name.to_string()));
+    Ok(format!("ПСК: {}", name))
+}
+
+/// Указание точек для ПСК или полюса (клики и ввод с клавиатуры).
+/// Пока идет указание, рисование линий не получает точки.
+pub fn ucs_pick_system(
+    input: ActionInput,
+    mut snap_state: ResMut<SnapState>,
+    mut typed_point_events: EventReader<TypedPointEvent>,
+    mut manager: ResMut<UcsManager>,
+) {
+    let Some(mut pick) = manager.picking.clone() else {
+        return;
+    };
+    if input.just_pressed(InputAction::Cancel) {
+        manager.picking = None;
+        info!("UCS point picking cancelled");
+        return;
+    }
+    if !pick.armed {
+        typed_point_events.clear();
+        pick.armed = true;
+        manager.picking = Some(pick);
+        return;
+    }
+
+    let mut points: Vec<DVec3> = typed_point_events.read().map(|event| event.point).collect();
+    if input.just_pressed(InputAction::PickPoint) {
+        if let Some(point) = snap_state.point() {
+            points.push(point);
+        }
+    }
+    snap_state.remember(points.iter().copied());
+    if points.is_empty() {
+        return;
+    }
+
+    for point in points {
+        pick.points.push(point);
+        if pick.points.len() >= pick.target.points_needed() {
+            manager.picking = None;
+            manager.finish_pick(pick);
+            return;
+        }
+    }
+    manager.picking = Some(pick);
+}
+
+/// Значок ПСК в ее начале, маркер полюса и выбранные при указании точки
+pub fn draw_ucs_icon_system(
+    manager: Res<UcsManager>,
+    snap_state: Res<SnapState>,
+    render_origin: Res<RenderOrigin>,
+    mut gizmos: Gizmos,
+) {
+    let frame = manager.frame(snap_state.last_point);
+    let icon_point =
+        |local: DVec2| render_origin.to_render(frame.origin + frame.vector_to_world(local));
+    let size = 1.0;
+    let origin = render_origin.to_render(frame.origin);
+
+    gizmos.arrow(
+        origin,
+        icon_point(DVec2::X * size),
+        Color::srgb(1.0, 0.3, 0.3),
+    );
+    gizmos.arrow(
+        origin,
+        icon_point(DVec2::Y * size),
+        Color::srgb(0.3, 1.0, 0.3),
+    );
+
+    // Квадрат в углу значка отличает мировую систему от пользовательских
+    if manager.active == 0 {
+        let corner = size * 0.2;
+        let a = icon_point(DVec2::new(corner, 0.0));
+        let b = icon_point(DVec2::new(corner, corner));
+        let c = icon_point(DVec2::new(0.0, corner));
+        gizmos.linestrip([a, b, c], Color::srgb(0.8, 0.8, 0.8));
+    }
+
+    if frame.pole.distance(frame.origin) > 1e-4 {
+        let pole_color = Color::srgb(1.0, 0.4, 1.0);
+        let pole = render_origin.to_render(frame.pole);
+        let pole_isometry =
+            Isometry3d::new(pole, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+        gizmos.circle(pole_isometry, 0.15, pole_color);
+        gizmos.line(pole - Vec3::X * 0.25, pole + Vec3::X * 0.25, pole_color);
+        gizmos.line(pole - Vec3::Z * 0.25, pole + Vec3::Z * 0.25, pole_color);
+    }
+
+    if let Some(pick) = &manager.picking {
+        let pick_color = Color::srgb(1.0, 0.8, 0.2);
+        let mut previous = None;
+        for point in &pick.points {
+            let point = render_origin.to_render(*point);
+            gizmos.sphere(point, 0.08, pick_color);
+            previous = Some(point);
+        }
+        if let (Some(previous), Some(cursor)) = (previous, snap_state.point()) {
+            gizmos.line(previous, render_origin.to_render(cursor), pick_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render_origin::FAR_POINT;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn ucs_frames_convert_between_local_and_world() {
+        let origin = FAR_POINT;
+        let diagonal = DVec3::new(1.0, 0.0, 1.0);
+        let left = DVec3::new(-1.0, 0.0, 1.0);
+        let ucs = Ucs::from_points(
+            "Ось".to_string(),
+            &[origin, origin + diagonal, origin + left],
+        )
+        .unwrap();
+        assert!((ucs.angle - FRAC_PI_4).abs() < 1e-12);
+        assert!(!ucs.mirrored);
+        // Третья точка по другую сторону оси X зеркалит ось Y
+        let mirrored = Ucs::from_points(
+            "Зеркало".to_string(),
+            &[origin, origin + diagonal, origin - left],
+        )
+        .unwrap();
+        assert!(mirrored.mirrored);
+        assert!(Ucs::from_points(String::new(), &[origin, origin]).is_err());
+        assert!(Ucs::from_points(
+            String::new(),
+            &[origin, origin + diagonal, origin - diagonal]
+        )
+        .is_err());
+
+        let mut manager = UcsManager::default();
+        manager.systems.push(ucs);
+        manager.active = 1;
+        let frame = manager.frame(None);
+        let local = frame.local_cartesian(origin + diagonal * 2.0);
+        assert!((local.x - 8f64.sqrt()).abs() < 1e-9 && local.y.abs() < 1e-9);
+        let local = frame.local_cartesian(origin + left);
+        assert!(local.x.abs() < 1e-9 && (local.y - 2f64.sqrt()).abs() < 1e-9);
+        let back = frame.cartesian_point(CartesianPoint::new(3.0, -2.0));
+        let again = frame.local_cartesian(back);
+        assert!((again.x - 3.0).abs() < 1e-9 && (again.y + 2.0).abs() < 1e-9);
+
+        // Углы: ось Y ПСК смотрит на 135° мира, в зеркальной ПСК — на -45°
+        assert!((frame.world_angle(FRAC_PI_2) - 3.0 * FRAC_PI_4).abs() < 1e-12);
+        assert!((frame.local_angle(3.0 * FRAC_PI_4) - FRAC_PI_2).abs() < 1e-12);
+        assert!((frame.line_angle(origin, origin + left) - FRAC_PI_2).abs() < 1e-12);
+        manager.systems[1].mirrored = true;
+        let mirrored = manager.frame(None);
+        assert!((mirrored.world_angle(FRAC_PI_2) + FRAC_PI_4).abs() < 1e-12);
+        assert!((mirrored.local_cartesian(origin + left).y + 2f64.sqrt()).abs() < 1e-9);
+
+        // Полярные координаты отсчитываются от полюса, угол — от оси X ПСК
+        let last = origin + DVec3::new(10.0, 0.0, 0.0);
+        manager.systems[1].mirrored = false;
+        manager.pole = PolarPole::LastPoint;
+        // Полюс - последняя точка, указанная любой командой
+        let mut snap_state = SnapState::default();
+        snap_state.remember([origin, last]);
+        snap_state.remember([]);
+        let frame = manager.frame(snap_state.last_point);
+        assert_eq!(frame.pole, last);
+        let polar = frame.local_polar(last + diagonal);
+        assert!((polar.r - 2f64.sqrt()).abs() < 1e-9 && polar.theta.abs() < 1e-9);
+        let point = frame.polar_point(PolarPoint::from_degrees(2.0, 90.0));
+        assert!(point.abs_diff_eq(last + left * 2f64.sqrt(), 1e-9));
+        assert_eq!(manager.frame(None).pole, origin);
+    }
+}
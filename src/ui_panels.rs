@@ -1,17 +1,40 @@
 use crate::{
+    attributes::{AttributeTool, BlockAttributes},
+    blocks::{BlockCommand, BlockReference, BlockTool, Blocks},
     coordinate_systems::{
-        formatting, AngleUnit, CoordinatePoint, CoordinateSettings, CoordinateSystem,
+        conversions, formatting, AngleBase, AngleDirection, AngleUnit, CoordinateSettings,
+        CoordinateSystem, LinearUnit,
     },
+    dimension_style::{DimensionStyles, Tolerance},
+    dimensions::{DimensionCommand, DimensionTool, LinearKind, RadialKind},
     events::*,                 // Используем централизованный модуль событий
     font_resource::GlobalFont, // ИМПОРТ глобального шрифта
+    groups::Groups,
+    input_field::{InputField, InputFieldKind, InputFieldMessage, InputFieldText},
+    keybindings::{InputAction, KeyBindingEditor, KeyBindings},
+    layers::{LayerId, Layers, PALETTE},
+    leaders::{LeaderCommand, LeaderKind, LeaderTool, Multileader},
     line_drawing::DrawableLine,
+    linetypes::Linetypes,
+    properties::{EntityStyle, Property, PropertyTool, ResolvedStyle},
+    selection::Selection,
+    snapping::{GridSnapSettings, SnapSettings, SnapState, SnapType},
+    tracking::{tracking_tooltip, TrackingSettings},
+    transform::{FitKind, TransformTool},
+    ucs::{PolarPole, UcsManager},
+    Id,
+};
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    math::DVec3,
+    prelude::*,
+    ui::RelativeCursorPosition,
 };
-use bevy::prelude::*;
 
 /// Ресурс для отслеживания текущей позиции курсора
 #[derive(Resource, Default)]
 pub struct CursorInfo {
-    pub world_position: Option<Vec3>,
+    pub world_position: Option<DVec3>, // в координатах документа
     pub is_valid: bool,
 }
 
@@ -31,6 +54,91 @@ pub struct CoordinateDisplay;
 #[derive(Component)]
 pub struct LineInfoDisplay;
 
+#[derive(Component)]
+pub struct GridSnapDisplay;
+
+#[derive(Component)]
+pub struct TrackingDisplay;
+
+/// Всплывающая подсказка отслеживания рядом с курсором
+#[derive(Component)]
+pub struct TrackingTooltip;
+
+/// Строка текущих линейных единиц и точности
+#[derive(Component)]
+pub struct UnitsDisplay;
+
+/// Строка состояния геопривязки
+#[derive(Component)]
+pub struct GeoreferenceDisplay;
+
+/// Строка состояния ПСК и полюса
+#[derive(Component)]
+pub struct UcsDisplay;
+
+/// Строка состояния простановки размеров
+#[derive(Component)]
+pub struct DimensionDisplay;
+
+/// Описание текущего размерного стиля
+#[derive(Component)]
+pub struct DimensionStyleDisplay;
+
+/// Список слоев с их свойствами
+#[derive(Component)]
+pub struct LayerDisplay;
+
+/// Список типов линий и общий масштаб
+#[derive(Component)]
+pub struct LinetypeDisplay;
+
+/// Свойства выделенных объектов
+#[derive(Component)]
+pub struct PropertiesDisplay;
+
+/// Строка состояния команд выносок
+#[derive(Component)]
+pub struct LeaderDisplay;
+
+/// Маркер для палитры блоков
+#[derive(Component)]
+pub struct BlockDisplay;
+
+/// Маркер для строки атрибутов
+#[derive(Component)]
+pub struct AttributeDisplay;
+
+/// Маркер для списка групп
+#[derive(Component)]
+pub struct GroupDisplay;
+
+/// Строка выделения и итога преобразования
+#[derive(Component)]
+pub struct TransformDisplay;
+
+/// Подсказка горячих клавиш в строке состояния
+#[derive(Component)]
+pub struct HotkeyHint;
+
+/// Подпись с клавишей действия: `{}` в шаблоне заменяется назначением
+#[derive(Component)]
+pub struct HotkeyLabel {
+    pub action: InputAction,
+    pub template: &'static str,
+}
+
+/// Сворачиваемый список назначений клавиш
+#[derive(Component)]
+pub struct KeyBindingList;
+
+/// Текст кнопки назначения для действия
+#[derive(Component)]
+pub struct KeyBindingText(pub InputAction);
+
+/// Сообщение редактора назначений
+#[derive(Component)]
+pub struct KeyBindingMessage;
+
 /// Настройка основного UI для Bevy 0.15+ с глобальным шрифтом
 pub fn setup_ui_panels(
     mut commands: Commands,
@@ -67,6 +175,9 @@ pub fn setup_ui_panels(
     commands
         .entity(main_container)
         .insert_children(0, &[status_panel]);
+
+    // === ПОДСКАЗКА ОТСЛЕЖИВАНИЯ (поверх всего UI) ===
+    create_tracking_tooltip(&mut commands, &global_font);
 }
 
 /// Создание верхней панели инструментов
@@ -173,10 +284,12 @@ fn create_settings_panel(commands: &mut Commands, global_font: &Res<GlobalFont>)
                 flex_direction: FlexDirection::Column,
                 row_gap: Val::Px(10.0),
                 border: UiRect::right(Val::Px(1.0)),
+                overflow: Overflow::scroll_y(),
                 ..default()
             },
             BackgroundColor(Color::srgb(0.05, 0.05, 0.05)),
             BorderColor(Color::srgb(0.2, 0.2, 0.2)),
+            RelativeCursorPosition::default(),
             SettingsPanel,
             Name::new("SettingsPanel"),
         ))
@@ -199,12 +312,76 @@ fn create_settings_panel(commands: &mut Commands, global_font: &Res<GlobalFont>)
     // ДОБАВЛЯЕМ СЕКЦИЮ КАМЕРЫ
     let camera_section = create_camera_section(commands, global_font);
     let coord_section = create_coordinate_section(commands, global_font);
+    let units_section = create_units_section(commands, global_font);
+    let georeference_section = create_georeference_section(commands, global_font);
+    let ucs_section = create_ucs_section(commands, global_font);
+    let transform_section = create_transform_section(commands, global_font);
+    let layer_section = create_layer_section(commands, global_font);
+    let linetype_section = create_linetype_section(commands, global_font);
+    let properties_section = create_properties_section(commands, global_font);
+    let dimension_section = create_dimension_section(commands, global_font);
+    let dimension_style_section = create_dimension_style_section(commands, global_font);
+    let leader_section = create_leader_section(commands, global_font);
+    let block_section = create_block_section(commands, global_font);
+    let attribute_section = create_attribute_section(commands, global_font);
+    let group_section = create_group_section(commands, global_font);
     let grid_section = create_grid_section(commands, global_font);
+    let snap_section = create_snap_section(commands, global_font);
+    let tracking_section = create_tracking_section(commands, global_font);
+    let keybinding_section = create_keybinding_section(commands, global_font);
 
     // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (последний добавленный = первый показанный)
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[keybinding_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[tracking_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[snap_section]);
     commands
         .entity(settings_panel)
         .insert_children(0, &[grid_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[group_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[attribute_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[block_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[leader_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[dimension_style_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[dimension_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[properties_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[linetype_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[layer_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[transform_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[ucs_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[georeference_section]);
+    commands
+        .entity(settings_panel)
+        .insert_children(0, &[units_section]);
     commands
         .entity(settings_panel)
         .insert_children(0, &[coord_section]);
@@ -296,9 +473,17 @@ fn create_coordinate_section(commands: &mut Commands, global_font: &Res<GlobalFo
         .id();
 
     // Кнопки координатных систем с РУССКИМ ТЕКСТОМ
-    let cartesian_button =
-        create_small_button(commands, global_font, "Декартовы", UIAction::SetCartesian);
-    let polar_button = create_small_button(commands, global_font, "Полярные", UIAction::SetPolar);
+    let system_buttons: Vec<Entity> = CoordinateSystem::ALL
+        .iter()
+        .map(|system| {
+            create_small_button(
+                commands,
+                global_font,
+                system.label(),
+                UIAction::SetCoordinateSystem(*system),
+            )
+        })
+        .collect();
 
     // Заголовок углов с РУССКИМ ТЕКСТОМ
     let angle_title = commands
@@ -314,24 +499,148 @@ fn create_coordinate_section(commands: &mut Commands, global_font: &Res<GlobalFo
         .id();
 
     // Кнопки единиц углов с РУССКИМ ТЕКСТОМ
-    let degrees_button =
-        create_small_button(commands, global_font, "Градусы", UIAction::SetDegrees);
-    let radians_button =
-        create_small_button(commands, global_font, "Радианы", UIAction::SetRadians);
+    let unit_buttons: Vec<Entity> = AngleUnit::ALL
+        .iter()
+        .map(|unit| {
+            create_small_button(
+                commands,
+                global_font,
+                unit.label(),
+                UIAction::SetAngleUnit(*unit),
+            )
+        })
+        .collect();
+
+    // Отсчет углов: нулевое направление и сторона
+    let orientation_title = commands
+        .spawn((
+            Text::new("Отсчет углов:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let east_button = create_small_button(
+        commands,
+        global_font,
+        "0° — восток (X)",
+        UIAction::AngleOrientation(AngleOrientationEvent::SetBase(AngleBase::East)),
+    );
+    let north_button = create_small_button(
+        commands,
+        global_font,
+        "0° — север (Y)",
+        UIAction::AngleOrientation(AngleOrientationEvent::SetBase(AngleBase::North)),
+    );
+    let ccw_button = create_small_button(
+        commands,
+        global_font,
+        "Против часовой",
+        UIAction::AngleOrientation(AngleOrientationEvent::SetDirection(
+            AngleDirection::CounterClockwise,
+        )),
+    );
+    let cw_button = create_small_button(
+        commands,
+        global_font,
+        "По часовой",
+        UIAction::AngleOrientation(AngleOrientationEvent::SetDirection(
+            AngleDirection::Clockwise,
+        )),
+    );
 
     // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ
+    commands.entity(section).insert_children(
+        0,
+        &[
+            orientation_title,
+            east_button,
+            north_button,
+            ccw_button,
+            cw_button,
+        ],
+    );
+    commands.entity(section).insert_children(0, &unit_buttons);
+    commands.entity(section).insert_children(0, &[angle_title]);
+    commands.entity(section).insert_children(0, &system_buttons);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции линейных единиц и точности
+fn create_units_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("UnitsSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Единицы чертежа:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            UnitsDisplay,
+        ))
+        .id();
+
+    let unit_buttons: Vec<Entity> = LinearUnit::ALL
+        .iter()
+        .map(|unit| {
+            create_small_button(
+                commands,
+                global_font,
+                unit.label(),
+                UIAction::Units(UnitsEvent::SetLinearUnit(*unit)),
+            )
+        })
+        .collect();
+    let precision_buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Точнее",
+            UIAction::Units(UnitsEvent::IncreasePrecision),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Грубее",
+            UIAction::Units(UnitsEvent::DecreasePrecision),
+        ),
+    ];
+
     commands
         .entity(section)
-        .insert_children(0, &[radians_button]);
-    commands
-        .entity(section)
-        .insert_children(0, &[degrees_button]);
-    commands.entity(section).insert_children(0, &[angle_title]);
-    commands.entity(section).insert_children(0, &[polar_button]);
+        .insert_children(0, &precision_buttons);
+    commands.entity(section).insert_children(0, &unit_buttons);
     commands
         .entity(section)
-        .insert_children(0, &[cartesian_button]);
-    commands.entity(section).insert_children(0, &[title]);
+        .insert_children(0, &[title, status]);
 
     section
 }
@@ -375,7 +684,77 @@ fn create_grid_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -
         grid_buttons.push(button);
     }
 
+    // Привязка к сетке: шаг независим от отображаемой сетки, но может быть связан с ней
+    let snap_title = commands
+        .spawn((
+            Text::new("Привязка к сетке:"),
+            HotkeyLabel {
+                action: InputAction::ToggleGridSnap,
+                template: "Привязка к сетке ({}):",
+            },
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let snap_status = commands
+        .spawn((
+            Text::new("Выкл"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            GridSnapDisplay,
+        ))
+        .id();
+    let snap_buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Вкл/выкл привязку",
+            UIAction::GridSnap(GridSnapEvent::Toggle),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Связать с сеткой",
+            UIAction::GridSnap(GridSnapEvent::ToggleLink),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Шаг привязки",
+            UIAction::GridSnap(GridSnapEvent::CycleStep),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Угловой шаг",
+            UIAction::GridSnap(GridSnapEvent::CycleAngleStep),
+        ),
+    ];
+
+    // Произвольный шаг сетки и привязки - выражением
+    let grid_step_field = create_input_field(commands, global_font, InputFieldKind::GridStep);
+    let snap_step_field = create_input_field(commands, global_font, InputFieldKind::SnapStep);
+
     // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands
+        .entity(section)
+        .insert_children(0, &[snap_step_field]);
+    for button in snap_buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[snap_status]);
+    commands.entity(section).insert_children(0, &[snap_title]);
+    commands
+        .entity(section)
+        .insert_children(0, &[grid_step_field]);
     for button in grid_buttons.into_iter().rev() {
         commands.entity(section).insert_children(0, &[button]);
     }
@@ -384,307 +763,2452 @@ fn create_grid_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -
     section
 }
 
-/// Создание правой панели информации
-fn create_info_panel(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
-    let info_panel = commands
+/// Создание секции объектных привязок
+fn create_snap_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
         .spawn((
             Node {
-                width: Val::Percent(15.0),
-                height: Val::Percent(100.0),
-                padding: UiRect::all(Val::Px(10.0)),
                 flex_direction: FlexDirection::Column,
-                row_gap: Val::Px(10.0),
-                border: UiRect::left(Val::Px(1.0)),
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.05, 0.05, 0.05)),
-            BorderColor(Color::srgb(0.2, 0.2, 0.2)),
-            InfoPanel,
-            Name::new("InfoPanel"),
+            Name::new("SnapSection"),
         ))
         .id();
 
-    // Заголовок панели с РУССКИМ ТЕКСТОМ
     let title = commands
         .spawn((
-            Text::new("Информация"),
+            Text::new("Привязки:"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
-                font_size: 16.0,
+                font: global_font.handle.clone(),
+                font_size: 12.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
         ))
         .id();
 
-    // Координаты курсора с РУССКИМ ТЕКСТОМ
-    let cursor_coords = commands
+    let master_button = create_small_button(
+        commands,
+        global_font,
+        "Вкл/выкл привязки",
+        UIAction::ToggleObjectSnaps,
+    );
+
+    // По кнопке на каждый тип привязки
+    let snap_buttons: Vec<Entity> = SnapType::ALL
+        .iter()
+        .map(|snap_type| {
+            create_small_button(
+                commands,
+                global_font,
+                snap_type.label(),
+                UIAction::ToggleSnapType(*snap_type),
+            )
+        })
+        .collect();
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    for button in snap_buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands
+        .entity(section)
+        .insert_children(0, &[master_button]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции геопривязки: проекция, зона, начало и поворот чертежа
+fn create_georeference_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
         .spawn((
-            Text::new("X: -, Y: -"),
-            TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
-                font_size: 11.0,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            TextColor(Color::srgb(0.8, 0.8, 0.8)),
-            Name::new("CursorCoords"),
-            CoordinateDisplay,
+            Name::new("GeoreferenceSection"),
         ))
         .id();
 
-    // Информация о линиях с РУССКИМ ТЕКСТОМ
-    let line_info = commands
+    let title = commands
         .spawn((
-            Text::new("Линий: 0"),
+            Text::new("Геопривязка:"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
-                font_size: 11.0,
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new("Без геопривязки"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
                 ..default()
             },
             TextColor(Color::srgb(0.8, 0.8, 0.8)),
-            Name::new("LineInfo"),
-            LineInfoDisplay,
+            GeoreferenceDisplay,
         ))
         .id();
+    let buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Проекция",
+            UIAction::Georeference(GeoreferenceEvent::CycleProjection),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Зона -",
+            UIAction::Georeference(GeoreferenceEvent::ShiftZone(-1)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Зона +",
+            UIAction::Georeference(GeoreferenceEvent::ShiftZone(1)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Полушарие N/S",
+            UIAction::Georeference(GeoreferenceEvent::ToggleHemisphere),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Вывод у курсора",
+            UIAction::Georeference(GeoreferenceEvent::CycleReadout),
+        ),
+    ];
+    let origin_field = create_input_field(commands, global_font, InputFieldKind::GeoOrigin);
+    let rotation_field = create_input_field(commands, global_font, InputFieldKind::GeoRotation);
 
-    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ
-    commands.entity(info_panel).insert_children(0, &[line_info]);
-    commands
-        .entity(info_panel)
-        .insert_children(0, &[cursor_coords]);
-    commands.entity(info_panel).insert_children(0, &[title]);
-
-    info_panel
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands
+        .entity(section)
+        .insert_children(0, &[rotation_field]);
+    commands.entity(section).insert_children(0, &[origin_field]);
+    for button in buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
 }
 
-/// Создание нижней статус-панели
-fn create_status_panel(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
-    let status_panel = commands
+/// Создание секции пользовательских систем координат и полюса
+fn create_ucs_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
         .spawn((
             Node {
-                width: Val::Percent(100.0),
-                height: Val::Percent(7.0),
-                padding: UiRect::all(Val::Px(10.0)),
-                border: UiRect::top(Val::Px(1.0)),
-                flex_direction: FlexDirection::Row,
-                justify_content: JustifyContent::SpaceBetween,
-                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.08, 0.08, 0.08)),
-            BorderColor(Color::srgb(0.2, 0.2, 0.2)),
-            Name::new("StatusPanel"),
+            Name::new("UcsSection"),
         ))
         .id();
 
-    // Статус текст с РУССКИМ ТЕКСТОМ
-    let status_text = commands
+    let title = commands
         .spawn((
-            Text::new("Готов к работе"),
+            Text::new("ПСК:"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
-                font_size: 10.0,
+                font: global_font.handle.clone(),
+                font_size: 12.0,
                 ..default()
             },
             TextColor(Color::srgb(0.7, 0.7, 0.7)),
         ))
         .id();
-
-    // ОБНОВЛЕННЫЕ подсказки горячих клавиш с упоминанием камеры
-    let hotkeys_text = commands
+    let status = commands
         .spawn((
-            Text::new("F1-F4: Рендер | L: Линии | X: Координаты | U: Углы | C: Камера"),
+            Text::new("Мировая"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font: global_font.handle.clone(),
                 font_size: 10.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            UcsDisplay,
+        ))
+        .id();
+    let buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Мировая",
+            UIAction::Ucs(UcsEvent::SetWorld),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Следующая ПСК",
+            UIAction::Ucs(UcsEvent::CycleNext),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "ПСК: начало",
+            UIAction::Ucs(UcsEvent::Define(1)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "ПСК: 2 точки",
+            UIAction::Ucs(UcsEvent::Define(2)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "ПСК: 3 точки",
+            UIAction::Ucs(UcsEvent::Define(3)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Удалить ПСК",
+            UIAction::Ucs(UcsEvent::DeleteActive),
+        ),
+    ];
+    let name_field = create_input_field(commands, global_font, InputFieldKind::UcsName);
+    let pole_title = commands
+        .spawn((
+            Text::new("Полюс полярных координат:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
         ))
         .id();
+    let pole_buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Начало ПСК",
+            UIAction::Ucs(UcsEvent::PoleAtOrigin),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Последняя точка",
+            UIAction::Ucs(UcsEvent::PoleAtLastPoint),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Указать точку",
+            UIAction::Ucs(UcsEvent::PickPole),
+        ),
+    ];
 
-    // Добавляем тексты в панель
-    commands
-        .entity(status_panel)
-        .insert_children(0, &[status_text, hotkeys_text]);
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    for button in pole_buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[pole_title]);
+    commands.entity(section).insert_children(0, &[name_field]);
+    for button in buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
 
-    status_panel
+    section
 }
 
-/// Создание кнопки - Bevy 0.15+ синтаксис с глобальным шрифтом
-fn create_button(
-    commands: &mut Commands,
-    global_font: &Res<GlobalFont>,
-    text: &str,
-    action: UIAction,
-) -> Entity {
-    let button = commands
+/// Создание секции простановки размеров
+fn create_dimension_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
         .spawn((
-            Button,
             Node {
-                width: Val::Px(120.0),
-                height: Val::Px(30.0),
-                border: UiRect::all(Val::Px(1.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
-            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
-            BorderRadius::all(Val::Px(3.0)),
-            action,
-            Name::new(format!("Button_{}", text)),
+            Name::new("DimensionSection"),
         ))
         .id();
 
-    // Создаем текст как дочерний элемент с ГЛОБАЛЬНЫМ ШРИФТОМ
-    let text_entity = commands
+    let title = commands
         .spawn((
-            Text::new(text),
+            Text::new("Размеры:"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
-                font_size: 11.0,
+                font: global_font.handle.clone(),
+                font_size: 12.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            DimensionDisplay,
         ))
         .id();
+    let mut buttons: Vec<Entity> = [
+        DimensionCommand::Linear(LinearKind::Horizontal),
+        DimensionCommand::Linear(LinearKind::Vertical),
+        DimensionCommand::Linear(LinearKind::Aligned),
+        DimensionCommand::AngularLines,
+        DimensionCommand::AngularVertex,
+        DimensionCommand::Radial(RadialKind::Radius),
+        DimensionCommand::Radial(RadialKind::Diameter),
+        DimensionCommand::Radial(RadialKind::JoggedRadius),
+        DimensionCommand::ArcLength,
+    ]
+    .iter()
+    .map(|command| {
+        create_small_button(
+            commands,
+            global_font,
+            command.label(),
+            UIAction::Dimension(DimensionEvent::Start(*command)),
+        )
+    })
+    .collect();
+    buttons.push(create_small_button(
+        commands,
+        global_font,
+        "Отменить размер",
+        UIAction::Dimension(DimensionEvent::Cancel),
+    ));
+    buttons.push(create_small_button(
+        commands,
+        global_font,
+        "Помечать размеры без объекта",
+        UIAction::Dimension(DimensionEvent::ToggleFlagLost),
+    ));
 
-    // Добавляем текст к кнопке
-    commands.entity(button).insert_children(0, &[text_entity]);
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
 
-    button
+    section
 }
 
-/// Создание маленькой кнопки
-fn create_small_button(
+/// Создание секции размерных стилей
+fn create_dimension_style_section(
     commands: &mut Commands,
     global_font: &Res<GlobalFont>,
-    text: &str,
-    action: UIAction,
 ) -> Entity {
-    let button = commands
+    let section = commands
         .spawn((
-            Button,
             Node {
-                width: Val::Percent(100.0),
-                height: Val::Px(25.0),
-                border: UiRect::all(Val::Px(1.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-            BorderColor(Color::srgb(0.3, 0.3, 0.3)),
-            BorderRadius::all(Val::Px(2.0)),
-            action,
-            Name::new(format!("SmallButton_{}", text)),
+            Name::new("DimensionStyleSection"),
         ))
         .id();
 
-    // Создаем текст как дочерний элемент с ГЛОБАЛЬНЫМ ШРИФТОМ
-    let text_entity = commands
+    let title = commands
         .spawn((
-            Text::new(text),
+            Text::new("Размерный стиль:"),
             TextFont {
-                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
                 font_size: 10.0,
                 ..default()
             },
-            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            DimensionStyleDisplay,
         ))
         .id();
+    let buttons = [
+        ("Следующий стиль", DimensionStyleEvent::Next),
+        ("Тип стрелки", DimensionStyleEvent::CycleArrow),
+        ("Положение текста", DimensionStyleEvent::CycleTextPlacement),
+        ("Точнее", DimensionStyleEvent::IncreasePrecision),
+        ("Грубее", DimensionStyleEvent::DecreasePrecision),
+        ("Масштаб ×2", DimensionStyleEvent::MultiplyScale(2.0)),
+        ("Масштаб ×½", DimensionStyleEvent::MultiplyScale(0.5)),
+    ]
+    .map(|(label, event)| {
+        create_small_button(
+            commands,
+            global_font,
+            label,
+            UIAction::DimensionStyle(event),
+        )
+    });
+    let fields = [
+        InputFieldKind::DimensionSizes,
+        InputFieldKind::DimensionText,
+        InputFieldKind::DimensionTolerance,
+        InputFieldKind::DimensionAngularTolerance,
+    ]
+    .map(|kind| create_input_field(commands, global_font, kind));
 
-    // Добавляем текст к кнопке
-    commands.entity(button).insert_children(0, &[text_entity]);
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &fields);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
 
-    button
+    section
 }
 
-/// Создание разделителя
-fn create_separator(commands: &mut Commands) -> Entity {
-    commands
+/// Создание секции слоев
+fn create_layer_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
         .spawn((
             Node {
-                width: Val::Px(2.0),
-                height: Val::Percent(80.0),
-                margin: UiRect::horizontal(Val::Px(5.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.3, 0.3, 0.3)),
-            Name::new("Separator"),
+            Name::new("LayerSection"),
         ))
-        .id()
-}
+        .id();
 
-/// Унифицированная система обработки UI взаимодействий
-pub fn handle_ui_interactions(
-    interaction_query: Query<(&Interaction, &UIAction), (Changed<Interaction>, With<Button>)>,
-    mut coordinate_events: EventWriter<CoordinateSystemChangeEvent>,
-    mut angle_events: EventWriter<AngleUnitChangeEvent>,
-    mut line_events: EventWriter<CreateLineEvent>,
-    mut delete_events: EventWriter<DeleteAllLinesEvent>,
-    mut grid_events: EventWriter<GridStepChangeEvent>,
-    mut camera_toggle_events: EventWriter<CameraToggleEvent>,
-    mut camera_reset_events: EventWriter<CameraResetEvent>,
-    coordinate_settings: Res<CoordinateSettings>,
-) {
-    for (interaction, action) in interaction_query.iter() {
-        if *interaction == Interaction::Pressed {
-            match action {
-                UIAction::ToggleCoordinateSystem => {
-                    let new_system = match coordinate_settings.coordinate_system {
-                        CoordinateSystem::Cartesian => CoordinateSystem::Polar,
-                        CoordinateSystem::Polar => CoordinateSystem::Cartesian,
-                    };
-                    coordinate_events.write(CoordinateSystemChangeEvent { new_system });
-                }
+    let title = commands
+        .spawn((
+            Text::new("Слои:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            LayerDisplay,
+        ))
+        .id();
+    let buttons = [
+        ("Новый слой", LayerEvent::New),
+        ("Следующий слой", LayerEvent::EditNext),
+        ("Сделать текущим", LayerEvent::MakeCurrent),
+        ("Цвет", LayerEvent::CycleColor),
+        ("Тип линий", LayerEvent::CycleLinetype),
+        ("Толще", LayerEvent::Thicker),
+        ("Тоньше", LayerEvent::Thinner),
+        ("Прозрачность", LayerEvent::CycleTransparency),
+        ("Включен", LayerEvent::ToggleOn),
+        ("Заморожен", LayerEvent::ToggleFrozen),
+        ("Заблокирован", LayerEvent::ToggleLocked),
+        ("Выделенное на текущий слой", LayerEvent::MoveSelection),
+    ]
+    .map(|(label, event)| {
+        create_small_button(commands, global_font, label, UIAction::Layer(event))
+    });
+    let name_field = create_input_field(commands, global_font, InputFieldKind::LayerName);
 
-                UIAction::ToggleAngleUnit => {
-                    let new_unit = match coordinate_settings.angle_unit {
-                        AngleUnit::Degrees => AngleUnit::Radians,
-                        AngleUnit::Radians => AngleUnit::Degrees,
-                    };
-                    angle_events.write(AngleUnitChangeEvent { new_unit });
-                }
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &[name_field]);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
 
-                _ => {
-                    // Для остальных действий используем централизованную логику
-                    action.emit_events(
-                        &mut coordinate_events,
-                        &mut angle_events,
-                        &mut line_events,
-                        &mut delete_events,
-                        &mut grid_events,
-                        &mut camera_toggle_events,
-                        &mut camera_reset_events,
-                    );
-                }
-            }
-        }
-    }
+    section
 }
 
-/// Система обновления информации о координатах курсора
-pub fn update_cursor_coordinates(
-    cursor_info: Res<CursorInfo>,
-    coordinate_settings: Res<CoordinateSettings>,
-    mut text_query: Query<&mut Text, With<CoordinateDisplay>>,
-) {
-    if !cursor_info.is_changed() {
-        return;
-    }
+/// Создание секции типов линий
+fn create_linetype_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("LinetypeSection"),
+        ))
+        .id();
 
-    for mut text in text_query.iter_mut() {
-        if let Some(world_pos) = cursor_info.world_position {
-            let coord_point = CoordinatePoint::from_world(world_pos);
-            let coords_text = match coordinate_settings.coordinate_system {
-                CoordinateSystem::Cartesian => formatting::format_cartesian(coord_point.cartesian),
-                CoordinateSystem::Polar => {
-                    formatting::format_polar(coord_point.polar, coordinate_settings.angle_unit)
+    let title = commands
+        .spawn((
+            Text::new("Типы линий:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            LinetypeDisplay,
+        ))
+        .id();
+    let buttons = [
+        ("Масштаб ×2", LinetypeEvent::MultiplyScale(2.0)),
+        ("Масштаб ×½", LinetypeEvent::MultiplyScale(0.5)),
+        ("Сохранить .lin", LinetypeEvent::Export),
+    ]
+    .map(|(label, event)| {
+        create_small_button(commands, global_font, label, UIAction::Linetype(event))
+    });
+    let fields = [
+        InputFieldKind::LinetypeScale,
+        InputFieldKind::LinetypeDefinition,
+    ]
+    .map(|kind| create_input_field(commands, global_font, kind));
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &fields);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции свойств выделенных объектов
+fn create_properties_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("PropertiesSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Свойства:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            PropertiesDisplay,
+        ))
+        .id();
+    let buttons = [
+        ("Цвет по слою", PropertyEvent::ColorByLayer),
+        ("Цвет", PropertyEvent::CycleColor),
+        ("Тип линий по слою", PropertyEvent::LinetypeByLayer),
+        ("Тип линий", PropertyEvent::CycleLinetype),
+        ("Толщина по слою", PropertyEvent::LineweightByLayer),
+        ("Толще", PropertyEvent::Thicker),
+        ("Тоньше", PropertyEvent::Thinner),
+        ("Прозрачность по слою", PropertyEvent::TransparencyByLayer),
+        ("Прозрачность", PropertyEvent::CycleTransparency),
+        ("Копировать свойства", PropertyEvent::StartMatch),
+        ("Завершить копирование", PropertyEvent::CancelMatch),
+    ]
+    .map(|(label, event)| {
+        create_small_button(commands, global_font, label, UIAction::Properties(event))
+    });
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции выносок
+fn create_leader_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("LeaderSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Выноски:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            LeaderDisplay,
+        ))
+        .id();
+    let mut buttons: Vec<Entity> = [
+        LeaderCommand::Create(LeaderKind::Text),
+        LeaderCommand::Create(LeaderKind::Balloon),
+        LeaderCommand::Create(LeaderKind::Block),
+        LeaderCommand::AddLeader,
+        LeaderCommand::Align,
+        LeaderCommand::Collect,
+    ]
+    .iter()
+    .map(|command| {
+        create_small_button(
+            commands,
+            global_font,
+            command.label(),
+            UIAction::Leader(LeaderEvent::Start(*command)),
+        )
+    })
+    .collect();
+    buttons.push(create_small_button(
+        commands,
+        global_font,
+        "Завершить",
+        UIAction::Leader(LeaderEvent::Finish),
+    ));
+    buttons.push(create_small_button(
+        commands,
+        global_font,
+        "Отменить",
+        UIAction::Leader(LeaderEvent::Cancel),
+    ));
+    let text_field = create_input_field(commands, global_font, InputFieldKind::LeaderText);
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &[text_field]);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание палитры блоков
+fn create_block_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("BlockSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Блоки:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            BlockDisplay,
+        ))
+        .id();
+    let buttons = [
+        ("Блок из выделенного", BlockEvent::StartDefine),
+        ("Вставить текущий", BlockEvent::StartInsert),
+        ("Следующий блок", BlockEvent::Next),
+        ("Расчленить", BlockEvent::Explode),
+        ("Отменить", BlockEvent::Cancel),
+    ]
+    .map(|(label, event)| {
+        create_small_button(commands, global_font, label, UIAction::Block(event))
+    });
+    let fields = [InputFieldKind::BlockName, InputFieldKind::BlockInsertion]
+        .map(|kind| create_input_field(commands, global_font, kind));
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &fields);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции групп объектов
+fn create_group_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("GroupSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Группы:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            GroupDisplay,
+        ))
+        .id();
+    let buttons = [
+        ("Группа из выделенного", GroupEvent::Create),
+        ("Разгруппировать", GroupEvent::Ungroup),
+        ("Следующая группа", GroupEvent::Next),
+        ("Выделить группу", GroupEvent::Select),
+        ("Выбор по одному", GroupEvent::ToggleIndividual),
+    ]
+    .map(|(label, event)| {
+        create_small_button(commands, global_font, label, UIAction::Group(event))
+    });
+    let field = create_input_field(commands, global_font, InputFieldKind::GroupName);
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &[field]);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции атрибутов блоков
+fn create_attribute_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("AttributeSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Атрибуты:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            AttributeDisplay,
+        ))
+        .id();
+    let buttons = [
+        (
+            "Определение атрибута",
+            UIAction::Block(BlockEvent::StartAttribute),
+        ),
+        (
+            "Видимый",
+            UIAction::Attribute(AttributeEvent::ToggleVisible),
+        ),
+        (
+            "Править атрибуты",
+            UIAction::Attribute(AttributeEvent::Edit),
+        ),
+        (
+            "Закончить ввод",
+            UIAction::Attribute(AttributeEvent::FinishEntry),
+        ),
+        (
+            "Извлечь в CSV",
+            UIAction::Attribute(AttributeEvent::Extract),
+        ),
+    ]
+    .map(|(label, action)| create_small_button(commands, global_font, label, action));
+    let fields = [
+        InputFieldKind::AttributeDefinition,
+        InputFieldKind::AttributeValue,
+        InputFieldKind::AttributeCsvPath,
+    ]
+    .map(|kind| create_input_field(commands, global_font, kind));
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &fields);
+    commands.entity(section).insert_children(0, &buttons);
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции выделения и преобразований
+fn create_transform_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("TransformSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Преобразование:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new("Выделено: 0"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            TransformDisplay,
+        ))
+        .id();
+    let selection_buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Выделить все",
+            UIAction::Selection(SelectionEvent::SelectAll),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Снять выделение",
+            UIAction::Selection(SelectionEvent::Clear),
+        ),
+    ];
+    let matrix_field = create_input_field(commands, global_font, InputFieldKind::TransformMatrix);
+    let steps_field = create_input_field(commands, global_font, InputFieldKind::TransformSteps);
+    let align_buttons = [
+        create_small_button(
+            commands,
+            global_font,
+            "Выравнивание: подобие",
+            UIAction::Transform(TransformEvent::StartAlign(FitKind::Similarity)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Выравнивание: аффинное",
+            UIAction::Transform(TransformEvent::StartAlign(FitKind::Affine)),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Применить выравнивание",
+            UIAction::Transform(TransformEvent::FinishAlign),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Отменить выравнивание",
+            UIAction::Transform(TransformEvent::CancelAlign),
+        ),
+    ];
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    for button in align_buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[steps_field]);
+    commands.entity(section).insert_children(0, &[matrix_field]);
+    for button in selection_buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции ОРТО и полярного отслеживания
+fn create_tracking_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("TrackingSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Отслеживание:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let status = commands
+        .spawn((
+            Text::new("Углы: 15°"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            TrackingDisplay,
+        ))
+        .id();
+    let buttons = [
+        create_hotkey_button(
+            commands,
+            global_font,
+            "ОРТО ({})",
+            InputAction::ToggleOrtho,
+            UIAction::Tracking(TrackingEvent::ToggleOrtho),
+        ),
+        create_hotkey_button(
+            commands,
+            global_font,
+            "Полярное ({})",
+            InputAction::TogglePolarTracking,
+            UIAction::Tracking(TrackingEvent::TogglePolar),
+        ),
+        create_hotkey_button(
+            commands,
+            global_font,
+            "Объектное ({})",
+            InputAction::ToggleObjectTracking,
+            UIAction::Tracking(TrackingEvent::ToggleObjectTracking),
+        ),
+        create_small_button(
+            commands,
+            global_font,
+            "Углы отслеживания",
+            UIAction::Tracking(TrackingEvent::CycleAngles),
+        ),
+    ];
+    let field = create_input_field(commands, global_font, InputFieldKind::TrackingAngles);
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ (в обратном порядке)
+    commands.entity(section).insert_children(0, &[field]);
+    for button in buttons.into_iter().rev() {
+        commands.entity(section).insert_children(0, &[button]);
+    }
+    commands.entity(section).insert_children(0, &[status]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание секции горячих клавиш: клик по действию ждет новую клавишу
+fn create_keybinding_section(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let section = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            Name::new("KeyBindingSection"),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("Горячие клавиши:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let toggle_button = create_small_button(
+        commands,
+        global_font,
+        "Показать/скрыть",
+        UIAction::KeyBinding(KeyBindingEvent::ToggleList),
+    );
+    let reset_button = create_small_button(
+        commands,
+        global_font,
+        "По умолчанию",
+        UIAction::KeyBinding(KeyBindingEvent::ResetDefaults),
+    );
+    let message = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 9.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.8, 0.6)),
+            KeyBindingMessage,
+        ))
+        .id();
+
+    // Список скрыт по умолчанию, чтобы не занимать панель
+    let list = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(3.0),
+                display: Display::None,
+                ..default()
+            },
+            KeyBindingList,
+            Name::new("KeyBindingList"),
+        ))
+        .id();
+
+    for action in InputAction::ALL.into_iter().rev() {
+        let row = commands
+            .spawn((
+                Button,
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(20.0),
+                    border: UiRect::all(Val::Px(1.0)),
+                    padding: UiRect::horizontal(Val::Px(4.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                BorderColor(Color::srgb(0.3, 0.3, 0.3)),
+                BorderRadius::all(Val::Px(2.0)),
+                UIAction::KeyBinding(KeyBindingEvent::StartRebind(action)),
+                Name::new(format!("KeyBinding_{:?}", action)),
+            ))
+            .id();
+        let text = commands
+            .spawn((
+                Text::new(action.label()),
+                TextFont {
+                    font: global_font.handle.clone(),
+                    font_size: 9.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                KeyBindingText(action),
+            ))
+            .id();
+        commands.entity(row).insert_children(0, &[text]);
+        commands.entity(list).insert_children(0, &[row]);
+    }
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ
+    commands.entity(section).insert_children(0, &[list]);
+    commands.entity(section).insert_children(0, &[message]);
+    commands.entity(section).insert_children(0, &[reset_button]);
+    commands
+        .entity(section)
+        .insert_children(0, &[toggle_button]);
+    commands.entity(section).insert_children(0, &[title]);
+
+    section
+}
+
+/// Создание всплывающей подсказки отслеживания
+fn create_tracking_tooltip(commands: &mut Commands, global_font: &Res<GlobalFont>) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: global_font.handle.clone(),
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.3, 0.9, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(3.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        TrackingTooltip,
+        Name::new("TrackingTooltip"),
+    ));
+}
+
+/// Создание правой панели информации
+fn create_info_panel(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let info_panel = commands
+        .spawn((
+            Node {
+                width: Val::Percent(15.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                border: UiRect::left(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.05, 0.05)),
+            BorderColor(Color::srgb(0.2, 0.2, 0.2)),
+            InfoPanel,
+            Name::new("InfoPanel"),
+        ))
+        .id();
+
+    // Заголовок панели с РУССКИМ ТЕКСТОМ
+    let title = commands
+        .spawn((
+            Text::new("Информация"),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        ))
+        .id();
+
+    // Координаты курсора с РУССКИМ ТЕКСТОМ
+    let cursor_coords = commands
+        .spawn((
+            Text::new("X: -, Y: -"),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            Name::new("CursorCoords"),
+            CoordinateDisplay,
+        ))
+        .id();
+
+    // Информация о линиях с РУССКИМ ТЕКСТОМ
+    let line_info = commands
+        .spawn((
+            Text::new("Линий: 0"),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            Name::new("LineInfo"),
+            LineInfoDisplay,
+        ))
+        .id();
+
+    // Ввод точки и калькулятор: поддерживают выражения и переменные
+    let point_title = commands
+        .spawn((
+            Text::new("Ввод точки:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let point_field = create_input_field(commands, global_font, InputFieldKind::Point);
+    let calculator_title = commands
+        .spawn((
+            Text::new("Калькулятор:"),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+    let calculator_field = create_input_field(commands, global_font, InputFieldKind::Calculator);
+
+    // ПРАВИЛЬНЫЙ ПОРЯДОК ДОБАВЛЕНИЯ
+    commands
+        .entity(info_panel)
+        .insert_children(0, &[calculator_field]);
+    commands
+        .entity(info_panel)
+        .insert_children(0, &[calculator_title]);
+    commands
+        .entity(info_panel)
+        .insert_children(0, &[point_field]);
+    commands
+        .entity(info_panel)
+        .insert_children(0, &[point_title]);
+    commands.entity(info_panel).insert_children(0, &[line_info]);
+    commands
+        .entity(info_panel)
+        .insert_children(0, &[cursor_coords]);
+    commands.entity(info_panel).insert_children(0, &[title]);
+
+    info_panel
+}
+
+/// Создание нижней статус-панели
+fn create_status_panel(commands: &mut Commands, global_font: &Res<GlobalFont>) -> Entity {
+    let status_panel = commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(7.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                border: UiRect::top(Val::Px(1.0)),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.08, 0.08, 0.08)),
+            BorderColor(Color::srgb(0.2, 0.2, 0.2)),
+            Name::new("StatusPanel"),
+        ))
+        .id();
+
+    // Статус текст с РУССКИМ ТЕКСТОМ
+    let status_text = commands
+        .spawn((
+            Text::new("Готов к работе"),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ))
+        .id();
+
+    // Подсказки горячих клавиш строятся из реестра назначений
+    let hotkeys_text = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            HotkeyHint,
+        ))
+        .id();
+
+    // Добавляем тексты в панель
+    commands
+        .entity(status_panel)
+        .insert_children(0, &[status_text, hotkeys_text]);
+
+    status_panel
+}
+
+/// Создание кнопки - Bevy 0.15+ синтаксис с глобальным шрифтом
+fn create_button(
+    commands: &mut Commands,
+    global_font: &Res<GlobalFont>,
+    text: &str,
+    action: UIAction,
+) -> Entity {
+    let button = commands
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(120.0),
+                height: Val::Px(30.0),
+                border: UiRect::all(Val::Px(1.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+            BorderRadius::all(Val::Px(3.0)),
+            action,
+            Name::new(format!("Button_{}", text)),
+        ))
+        .id();
+
+    // Создаем текст как дочерний элемент с ГЛОБАЛЬНЫМ ШРИФТОМ
+    let text_entity = commands
+        .spawn((
+            Text::new(text),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        ))
+        .id();
+
+    // Добавляем текст к кнопке
+    commands.entity(button).insert_children(0, &[text_entity]);
+
+    button
+}
+
+/// Создание маленькой кнопки
+fn create_small_button(
+    commands: &mut Commands,
+    global_font: &Res<GlobalFont>,
+    text: &str,
+    action: UIAction,
+) -> Entity {
+    create_labeled_button(commands, global_font, text, action, ())
+}
+
+/// Маленькая кнопка с клавишей действия в подписи (`{}` в шаблоне)
+fn create_hotkey_button(
+    commands: &mut Commands,
+    global_font: &Res<GlobalFont>,
+    template: &'static str,
+    hotkey: InputAction,
+    action: UIAction,
+) -> Entity {
+    let label = HotkeyLabel {
+        action: hotkey,
+        template,
+    };
+    create_labeled_button(commands, global_font, template, action, label)
+}
+
+/// Маленькая кнопка; `label` добавляется к тексту кнопки
+fn create_labeled_button(
+    commands: &mut Commands,
+    global_font: &Res<GlobalFont>,
+    text: &str,
+    action: UIAction,
+    label: impl Bundle,
+) -> Entity {
+    let button = commands
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(25.0),
+                border: UiRect::all(Val::Px(1.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+            BorderColor(Color::srgb(0.3, 0.3, 0.3)),
+            BorderRadius::all(Val::Px(2.0)),
+            action,
+            Name::new(format!("SmallButton_{}", text)),
+        ))
+        .id();
+
+    // Создаем текст как дочерний элемент с ГЛОБАЛЬНЫМ ШРИФТОМ
+    let text_entity = commands
+        .spawn((
+            Text::new(text),
+            TextFont {
+                font: global_font.handle.clone(), // ИСПОЛЬЗУЕМ глобальный шрифт
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            label,
+        ))
+        .id();
+
+    // Добавляем текст к кнопке
+    commands.entity(button).insert_children(0, &[text_entity]);
+
+    button
+}
+
+/// Создание поля ввода выражения со строкой сообщения под ним
+fn create_input_field(
+    commands: &mut Commands,
+    global_font: &Res<GlobalFont>,
+    kind: InputFieldKind,
+) -> Entity {
+    let container = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            Name::new(format!("InputField_{:?}", kind)),
+        ))
+        .id();
+
+    let field = commands
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(25.0),
+                border: UiRect::all(Val::Px(1.0)),
+                padding: UiRect::horizontal(Val::Px(4.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderColor(Color::srgb(0.3, 0.3, 0.3)),
+            BorderRadius::all(Val::Px(2.0)),
+            InputField::new(kind),
+        ))
+        .id();
+
+    let text = commands
+        .spawn((
+            Text::new(kind.placeholder()),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.45, 0.45, 0.45)),
+            InputFieldText { field },
+        ))
+        .id();
+
+    let message = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: global_font.handle.clone(),
+                font_size: 9.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.8, 0.6)),
+            InputFieldMessage { field },
+        ))
+        .id();
+
+    commands.entity(field).insert_children(0, &[text]);
+    commands
+        .entity(container)
+        .insert_children(0, &[field, message]);
+
+    container
+}
+
+/// Создание разделителя
+fn create_separator(commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(2.0),
+                height: Val::Percent(80.0),
+                margin: UiRect::horizontal(Val::Px(5.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.3, 0.3, 0.3)),
+            Name::new("Separator"),
+        ))
+        .id()
+}
+
+/// Унифицированная система обработки UI взаимодействий
+pub fn handle_ui_interactions(
+    interaction_query: Query<(&Interaction, &UIAction), (Changed<Interaction>, With<Button>)>,
+    mut events: UiEventWriters,
+    coordinate_settings: Res<CoordinateSettings>,
+) {
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            match action {
+                UIAction::ToggleCoordinateSystem => {
+                    let new_system = coordinate_settings.coordinate_system.next();
+                    events
+                        .coordinate
+                        .write(CoordinateSystemChangeEvent { new_system });
+                }
+
+                UIAction::ToggleAngleUnit => {
+                    let new_unit = coordinate_settings.angle_unit.next();
+                    events.angle.write(AngleUnitChangeEvent { new_unit });
+                }
+
+                _ => {
+                    // Для остальных действий используем централизованную логику
+                    action.emit_events(&mut events);
                 }
+            }
+        }
+    }
+}
+
+/// Подсветка кнопок привязок в соответствии с их состоянием
+pub fn update_snap_buttons(
+    snap_settings: Res<SnapSettings>,
+    grid_snap_settings: Res<GridSnapSettings>,
+    tracking_settings: Res<TrackingSettings>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !snap_settings.is_changed()
+        && !grid_snap_settings.is_changed()
+        && !tracking_settings.is_changed()
+    {
+        return;
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::ToggleObjectSnaps => snap_settings.enabled,
+            UIAction::ToggleSnapType(snap_type) => snap_settings.is_enabled(*snap_type),
+            UIAction::GridSnap(GridSnapEvent::Toggle) => grid_snap_settings.enabled,
+            UIAction::GridSnap(GridSnapEvent::ToggleLink) => grid_snap_settings.link_to_grid,
+            UIAction::Tracking(TrackingEvent::ToggleOrtho) => tracking_settings.ortho,
+            UIAction::Tracking(TrackingEvent::TogglePolar) => tracking_settings.polar_tracking,
+            UIAction::Tracking(TrackingEvent::ToggleObjectTracking) => {
+                tracking_settings.object_tracking
+            }
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Подсветка кнопок системы координат, единиц и отсчета углов, строка единиц чертежа
+pub fn update_units_display(
+    coordinate_settings: Res<CoordinateSettings>,
+    mut text_query: Query<&mut Text, With<UnitsDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !coordinate_settings.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        **text = format!(
+            "{}, точность {}",
+            coordinate_settings.linear_unit.label(),
+            coordinate_settings.precision_label()
+        );
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::SetCoordinateSystem(system) => {
+                coordinate_settings.coordinate_system == *system
+            }
+            UIAction::SetAngleUnit(unit) => coordinate_settings.angle_unit == *unit,
+            UIAction::Units(UnitsEvent::SetLinearUnit(unit)) => {
+                coordinate_settings.linear_unit == *unit
+            }
+            UIAction::AngleOrientation(AngleOrientationEvent::SetBase(base)) => {
+                coordinate_settings.angle_base == *base
+            }
+            UIAction::AngleOrientation(AngleOrientationEvent::SetDirection(direction)) => {
+                coordinate_settings.angle_direction == *direction
+            }
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление строки геопривязки
+pub fn update_georeference_display(
+    coordinate_settings: Res<CoordinateSettings>,
+    mut text_query: Query<&mut Text, With<GeoreferenceDisplay>>,
+) {
+    if !coordinate_settings.is_changed() {
+        return;
+    }
+
+    let georeference = &coordinate_settings.georeference;
+    for mut text in text_query.iter_mut() {
+        **text = match georeference.projection {
+            Some(projection) => format!(
+                "{} ({})\nНачало: {}, поворот {}\nУ курсора: {}",
+                projection.label(),
+                projection.datum_label(),
+                formatting::format_projected(georeference.origin),
+                formatting::format_angle(
+                    georeference.rotation,
+                    coordinate_settings.angle_unit,
+                    coordinate_settings.precision
+                ),
+                georeference.readout.label()
+            ),
+            None => "Без геопривязки".to_string(),
+        };
+    }
+}
+
+/// Обновление списка назначений, сообщения редактора и подсказки в строке состояния
+pub fn update_keybinding_display(
+    bindings: Res<KeyBindings>,
+    editor: Res<KeyBindingEditor>,
+    mut list_query: Query<&mut Node, With<KeyBindingList>>,
+    mut row_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+    mut row_text_query: Query<(&mut Text, &KeyBindingText)>,
+    mut message_query: Query<
+        (&mut Text, &mut TextColor),
+        (With<KeyBindingMessage>, Without<KeyBindingText>),
+    >,
+    mut hint_query: Query<
+        &mut Text,
+        (
+            With<HotkeyHint>,
+            Without<KeyBindingText>,
+            Without<KeyBindingMessage>,
+        ),
+    >,
+) {
+    if !bindings.is_changed() && !editor.is_changed() {
+        return;
+    }
+
+    for mut node in list_query.iter_mut() {
+        node.display = if editor.list_open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for (action, mut background) in row_query.iter_mut() {
+        let UIAction::KeyBinding(KeyBindingEvent::StartRebind(action)) = action else {
+            continue;
+        };
+        *background = if editor.waiting == Some(*action) {
+            BackgroundColor(Color::srgb(0.2, 0.25, 0.35))
+        } else if bindings.has_conflict(*action) {
+            BackgroundColor(Color::srgb(0.35, 0.12, 0.12))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+
+    for (mut text, KeyBindingText(action)) in row_text_query.iter_mut() {
+        let binding = if editor.waiting == Some(*action) {
+            "...".to_string()
+        } else {
+            bindings.label(*action)
+        };
+        **text = format!("{}: {}", action.label(), binding);
+    }
+
+    for (mut text, mut color) in message_query.iter_mut() {
+        match &editor.message {
+            Some((message, is_error)) => {
+                **text = message.clone();
+                *color = if *is_error {
+                    TextColor(Color::srgb(1.0, 0.4, 0.4))
+                } else {
+                    TextColor(Color::srgb(0.6, 0.8, 0.6))
+                };
+            }
+            None => **text = String::new(),
+        }
+    }
+
+    for mut text in hint_query.iter_mut() {
+        **text = bindings.status_bar_hint();
+    }
+}
+
+/// Подписи кнопок и заголовков следуют за переназначением клавиш
+pub fn update_hotkey_labels(
+    bindings: Res<KeyBindings>,
+    mut label_query: Query<(&mut Text, Ref<HotkeyLabel>)>,
+) {
+    for (mut text, label) in label_query.iter_mut() {
+        if bindings.is_changed() || label.is_added() {
+            **text = label.template.replace("{}", &bindings.label(label.action));
+        }
+    }
+}
+
+/// Прокрутка панели настроек колесом мыши
+pub fn scroll_settings_panel(
+    mut wheel_events: EventReader<MouseWheel>,
+    mut panel_query: Query<(&mut ScrollPosition, &RelativeCursorPosition), With<SettingsPanel>>,
+) {
+    let Ok((mut scroll, cursor)) = panel_query.single_mut() else {
+        return;
+    };
+
+    for event in wheel_events.read() {
+        if !cursor.mouse_over() {
+            continue;
+        }
+        let delta = match event.unit {
+            MouseScrollUnit::Line => event.y * 20.0,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        scroll.offset_y = (scroll.offset_y - delta).max(0.0);
+    }
+}
+
+/// Система обновления состояния ПСК: имя, начало, поворот, полюс и подсказка указания
+pub fn update_ucs_display(
+    ucs_manager: Res<UcsManager>,
+    coordinate_settings: Res<CoordinateSettings>,
+    mut text_query: Query<&mut Text, With<UcsDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !ucs_manager.is_changed() && !coordinate_settings.is_changed() {
+        return;
+    }
+
+    let ucs = ucs_manager.active();
+    for mut text in text_query.iter_mut() {
+        **text = match &ucs_manager.picking {
+            Some(pick) => pick.prompt(),
+            None => format!(
+                "{}: {} ∠{}{}\nПолюс: {}",
+                ucs.name,
+                formatting::format_cartesian(
+                    conversions::world_to_cartesian(ucs.origin),
+                    &coordinate_settings
+                ),
+                formatting::format_angle(
+                    ucs.angle,
+                    coordinate_settings.angle_unit,
+                    coordinate_settings.precision
+                ),
+                if ucs.mirrored {
+                    ", зеркальная"
+                } else {
+                    ""
+                },
+                ucs_manager.pole_label(&coordinate_settings)
+            ),
+        };
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::Ucs(UcsEvent::PoleAtOrigin) => ucs_manager.pole == PolarPole::Origin,
+            UIAction::Ucs(UcsEvent::PoleAtLastPoint) => ucs_manager.pole == PolarPole::LastPoint,
+            UIAction::Ucs(UcsEvent::PickPole) => {
+                matches!(ucs_manager.pole, PolarPole::Fixed(_))
+            }
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление подсказки простановки размеров и подсветки кнопок
+pub fn update_dimension_display(
+    tool: Res<DimensionTool>,
+    dimension_query: Query<&crate::dimensions::DimensionAssociation>,
+    mut text_query: Query<&mut Text, With<DimensionDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    let count = dimension_query.iter().count();
+    let lost = dimension_query
+        .iter()
+        .filter(|association| association.lost)
+        .count();
+    let status = match &tool.placing {
+        Some(pick) => pick.prompt(),
+        None if lost > 0 => format!("Размеров: {}, без объекта: {}", count, lost),
+        None => format!("Размеров: {}", count),
+    };
+    for mut text in text_query.iter_mut() {
+        if **text != status {
+            **text = status.clone();
+        }
+    }
+
+    if !tool.is_changed() {
+        return;
+    }
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::Dimension(DimensionEvent::Start(command)) => tool
+                .placing
+                .as_ref()
+                .is_some_and(|pick| pick.command == *command),
+            UIAction::Dimension(DimensionEvent::ToggleFlagLost) => tool.flag_lost,
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление описания текущего размерного стиля
+pub fn update_dimension_style_display(
+    styles: Res<DimensionStyles>,
+    mut text_query: Query<&mut Text, With<DimensionStyleDisplay>>,
+) {
+    if !styles.is_changed() {
+        return;
+    }
+    let style = styles.current();
+    // Угловые отклонения хранятся в радианах, показываем в градусах
+    let tolerance = |tolerance: Tolerance, scale: f64, unit: &str| {
+        let value = |value: f64| (value * scale * 1e6).round() / 1e6;
+        match tolerance {
+            Tolerance::None => "нет".to_string(),
+            Tolerance::Symmetric(tolerance) => format!("±{}{}", value(tolerance), unit),
+            Tolerance::Deviation { upper, lower } => {
+                format!("{:+}{unit} {:+}{unit}", value(upper), value(lower))
+            }
+        }
+    };
+    let status = format!(
+        "{} ({}/{})\n{} {}, текст {} {}\nвыносные: отступ {}, выход {}\nточность {}, масштаб {}\nдопуск {}, угловой {}\nтекст: {}<>{}",
+        style.name,
+        styles.current + 1,
+        styles.styles.len(),
+        style.arrow.label(),
+        style.arrow_size,
+        style.text_height,
+        style.text_placement.label(),
+        style.extension_offset,
+        style.extension_beyond,
+        style.precision,
+        style.scale,
+        tolerance(style.tolerance, 1.0, ""),
+        tolerance(style.angular_tolerance, 180.0 / std::f64::consts::PI, "°"),
+        style.prefix,
+        style.suffix,
+    );
+    for mut text in text_query.iter_mut() {
+        **text = status.clone();
+    }
+}
+
+/// Обновление списка слоев и подсветки переключателей открытого слоя.
+/// ▶ — слой, открытый в панели, ● — текущий слой.
+pub fn update_layer_display(
+    layers: Res<Layers>,
+    mut text_query: Query<&mut Text, With<LayerDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !layers.is_changed() {
+        return;
+    }
+    let status = layers
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(index, layer)| {
+            let mut flags = Vec::new();
+            if !layer.on {
+                flags.push("выкл");
+            }
+            if layer.frozen {
+                flags.push("заморожен");
+            }
+            if layer.locked {
+                flags.push("заблокирован");
+            }
+            format!(
+                "{}{} {}: {}, {}, {} мм, прозр. {}%{}",
+                if index == layers.edited { "▶" } else { " " },
+                if index == layers.current { "●" } else { " " },
+                layer.name,
+                PALETTE[layer.color % PALETTE.len()].0,
+                layer.linetype,
+                layer.lineweight,
+                (layer.transparency * 100.0).round(),
+                if flags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", flags.join(", "))
+                },
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    for mut text in text_query.iter_mut() {
+        **text = status.clone();
+    }
+
+    let layer = &layers.layers[layers.edited];
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::Layer(LayerEvent::ToggleOn) => layer.on,
+            UIAction::Layer(LayerEvent::ToggleFrozen) => layer.frozen,
+            UIAction::Layer(LayerEvent::ToggleLocked) => layer.locked,
+            UIAction::Layer(LayerEvent::MakeCurrent) => layers.edited == layers.current,
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление списка типов линий и общего масштаба
+pub fn update_linetype_display(
+    linetypes: Res<Linetypes>,
+    mut text_query: Query<&mut Text, With<LinetypeDisplay>>,
+) {
+    if !linetypes.is_changed() {
+        return;
+    }
+    let mut lines: Vec<String> = linetypes
+        .linetypes
+        .iter()
+        .map(|linetype| format!("{}: {}", linetype.name, linetype.description))
+        .collect();
+    lines.push(format!("масштаб: {}", linetypes.scale));
+    for mut text in text_query.iter_mut() {
+        **text = lines.join("\n");
+    }
+}
+
+/// Обновление свойств выделенных объектов: значение, если оно общее у всех,
+/// и «по слою», если свойство берется у слоя
+pub fn update_properties_display(
+    selection: Res<Selection>,
+    layers: Res<Layers>,
+    tool: Res<PropertyTool>,
+    style_query: Query<(Ref<EntityStyle>, &LayerId)>,
+    mut text_query: Query<&mut Text, With<PropertiesDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    let styles: Vec<_> = selection
+        .entities
+        .iter()
+        .filter_map(|entity| style_query.get(*entity).ok())
+        .collect();
+    let styles_changed = styles.iter().any(|(style, _)| style.is_changed());
+    if !selection.is_changed() && !layers.is_changed() && !tool.is_changed() && !styles_changed {
+        return;
+    }
+
+    let resolved: Vec<_> = styles
+        .iter()
+        .map(|(style, layer)| (style, style.resolve(layers.get(**layer))))
+        .collect();
+    // Значение, общее у всех выделенных объектов, или «разные»
+    let describe = |value: fn(&ResolvedStyle) -> String, by_layer: fn(&EntityStyle) -> bool| {
+        let mut values = resolved
+            .iter()
+            .map(|(style, resolved)| (value(resolved), by_layer(style)));
+        let first = values.next();
+        match first {
+            Some(first) if values.all(|value| value == first) => match first {
+                (value, true) => format!("{} (по слою)", value),
+                (value, false) => value,
+            },
+            _ => "разные".to_string(),
+        }
+    };
+    let status = match &tool.matching {
+        Some(pick) => pick.prompt().to_string(),
+        None if styles.is_empty() => "Выделите объекты".to_string(),
+        None => format!(
+            "Выделено: {}\nцвет: {}\nтип линий: {}\nтолщина: {}\nпрозрачность: {}",
+            styles.len(),
+            describe(
+                |style| PALETTE[style.color % PALETTE.len()].0.to_string(),
+                |style| style.color == Property::ByLayer,
+            ),
+            describe(
+                |style| style.linetype.clone(),
+                |style| style.linetype == Property::ByLayer,
+            ),
+            describe(
+                |style| format!("{} мм", style.lineweight),
+                |style| style.lineweight == Property::ByLayer,
+            ),
+            describe(
+                |style| format!("{}%", (style.transparency * 100.0).round()),
+                |style| style.transparency == Property::ByLayer,
+            ),
+        ),
+    };
+    for mut text in text_query.iter_mut() {
+        **text = status.clone();
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let UIAction::Properties(PropertyEvent::StartMatch) = action else {
+            continue;
+        };
+        *background = if tool.is_picking() {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление подсказки команд выносок и подсветки кнопок
+pub fn update_leader_display(
+    tool: Res<LeaderTool>,
+    leader_query: Query<&Multileader>,
+    mut text_query: Query<&mut Text, With<LeaderDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    let status = match &tool.pick {
+        Some(pick) => pick.prompt(),
+        None => format!("Выносок: {}", leader_query.iter().count()),
+    };
+    for mut text in text_query.iter_mut() {
+        if **text != status {
+            **text = status.clone();
+        }
+    }
+
+    if !tool.is_changed() {
+        return;
+    }
+    for (action, mut background) in button_query.iter_mut() {
+        let UIAction::Leader(LeaderEvent::Start(command)) = action else {
+            continue;
+        };
+        let active = tool
+            .pick
+            .as_ref()
+            .is_some_and(|pick| pick.command == *command);
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление списка групп: открытая группа, число объектов и режим выбора
+pub fn update_group_display(
+    groups: Res<Groups>,
+    selection: Res<Selection>,
+    id_query: Query<&Id>,
+    mut text_query: Query<&mut Text, With<GroupDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !groups.is_changed() && !selection.is_changed() {
+        return;
+    }
+    let selected: Vec<Id> = selection
+        .entities
+        .iter()
+        .filter_map(|entity| id_query.get(*entity).ok().copied())
+        .collect();
+    let mut lines: Vec<String> = groups
+        .groups
+        .iter()
+        .enumerate()
+        .map(|(index, group)| {
+            let selected = group
+                .members
+                .iter()
+                .filter(|id| selected.contains(id))
+                .count();
+            format!(
+                "{} {}: объектов {}, выделено {}",
+                if index == groups.current { "▶" } else { " " },
+                group.name,
+                group.members.len(),
+                selected
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push("Групп нет".to_string());
+    }
+    lines.push(if groups.individual {
+        "Выбор: по одному объекту".to_string()
+    } else {
+        "Выбор: группа целиком".to_string()
+    });
+    let status = lines.join("\n");
+    for mut text in text_query.iter_mut() {
+        **text = status.clone();
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let UIAction::Group(GroupEvent::ToggleIndividual) = action else {
+            continue;
+        };
+        *background = if groups.individual {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление строки атрибутов: параметры новых определений, запрос значения и итог извлечения
+pub fn update_attribute_display(
+    tool: Res<AttributeTool>,
+    blocks: Res<Blocks>,
+    reference_query: Query<(&BlockReference, &BlockAttributes)>,
+    mut text_query: Query<&mut Text, With<AttributeDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    let mut lines = vec![if tool.tag.is_empty() {
+        "Новый атрибут: задайте тег".to_string()
+    } else {
+        format!(
+            "Новый атрибут: {} «{}» = {}",
+            tool.tag, tool.prompt, tool.default
+        )
+    }];
+    // Запрос очередного значения с текущим значением в скобках
+    if let Some((definition, attributes)) = tool.entry.and_then(|entry| {
+        let (reference, attributes) = reference_query.get(entry.reference).ok()?;
+        let definition = blocks.definitions[reference.block]
+            .attributes
+            .get(entry.index)?;
+        Some((definition, attributes))
+    }) {
+        lines.push(format!(
+            "{} <{}>:",
+            definition.prompt,
+            attributes.value(definition)
+        ));
+    }
+    if let Some(report) = &tool.report {
+        lines.push(report.clone());
+    }
+    let status = lines.join("\n");
+    for mut text in text_query.iter_mut() {
+        if **text != status {
+            **text = status.clone();
+        }
+    }
+
+    if !tool.is_changed() {
+        return;
+    }
+    for (action, mut background) in button_query.iter_mut() {
+        let UIAction::Attribute(AttributeEvent::ToggleVisible) = action else {
+            continue;
+        };
+        *background = if tool.visible {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление палитры блоков: определения с числом вставок и подсказка команды
+pub fn update_block_display(
+    tool: Res<BlockTool>,
+    blocks: Res<Blocks>,
+    coordinate_settings: Res<CoordinateSettings>,
+    reference_query: Query<&BlockReference>,
+    mut text_query: Query<&mut Text, With<BlockDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    let mut lines: Vec<String> = blocks
+        .definitions
+        .iter()
+        .enumerate()
+        .map(|(index, definition)| {
+            let count = reference_query
+                .iter()
+                .filter(|reference| reference.block == index)
+                .count();
+            format!(
+                "{} {}: отрезков {}, вложенных {}, атрибутов {}, вставок {}",
+                if index == blocks.current { "▶" } else { " " },
+                definition.name,
+                definition.lines.len(),
+                definition.blocks.len(),
+                definition.attributes.len(),
+                count
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push("Блоков нет".to_string());
+    }
+    if let Some(pick) = &tool.pick {
+        lines.push(pick.prompt().to_string());
+    }
+    lines.push(format!(
+        "Вставка: масштаб {}, поворот {}",
+        tool.scale,
+        formatting::format_angle(
+            tool.rotation,
+            coordinate_settings.angle_unit,
+            coordinate_settings.precision
+        )
+    ));
+    let status = lines.join("\n");
+    for mut text in text_query.iter_mut() {
+        if **text != status {
+            **text = status.clone();
+        }
+    }
+
+    if !tool.is_changed() {
+        return;
+    }
+    for (action, mut background) in button_query.iter_mut() {
+        let command = match action {
+            UIAction::Block(BlockEvent::StartDefine) => BlockCommand::Define,
+            UIAction::Block(BlockEvent::StartInsert) => BlockCommand::Insert,
+            UIAction::Block(BlockEvent::StartAttribute) => BlockCommand::Attribute,
+            _ => continue,
+        };
+        let active = tool
+            .pick
+            .as_ref()
+            .is_some_and(|pick| pick.command == command);
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Обновление строки выделения, подсказки выравнивания и итога преобразования
+pub fn update_transform_display(
+    selection: Res<Selection>,
+    tool: Res<TransformTool>,
+    mut text_query: Query<&mut Text, With<TransformDisplay>>,
+    mut button_query: Query<(&UIAction, &mut BackgroundColor), With<Button>>,
+) {
+    if !selection.is_changed() && !tool.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        let details = match (&tool.align, &tool.report) {
+            (Some(pick), _) => format!("\n{}", pick.prompt()),
+            (None, Some(report)) => format!("\n{}", report),
+            (None, None) => String::new(),
+        };
+        **text = format!("Выделено: {}{}", selection.entities.len(), details);
+    }
+
+    for (action, mut background) in button_query.iter_mut() {
+        let active = match action {
+            UIAction::Transform(TransformEvent::StartAlign(kind)) => {
+                tool.align.as_ref().is_some_and(|pick| pick.kind == *kind)
+            }
+            _ => continue,
+        };
+        *background = if active {
+            BackgroundColor(Color::srgb(0.15, 0.3, 0.15))
+        } else {
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15))
+        };
+    }
+}
+
+/// Система обновления состояния привязки к сетке
+pub fn update_grid_snap_display(
+    grid_snap_settings: Res<GridSnapSettings>,
+    grid_settings: Res<crate::grid::GridSettings>,
+    coordinate_settings: Res<CoordinateSettings>,
+    mut text_query: Query<&mut Text, With<GridSnapDisplay>>,
+) {
+    if !grid_snap_settings.is_changed()
+        && !grid_settings.is_changed()
+        && !coordinate_settings.is_changed()
+    {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        **text = format!(
+            "{}: шаг {}{}, r {} / θ {}°",
+            if grid_snap_settings.enabled {
+                "Вкл"
+            } else {
+                "Выкл"
+            },
+            formatting::format_length(
+                grid_snap_settings.effective_step(&grid_settings) as f64,
+                &coordinate_settings
+            ),
+            if grid_snap_settings.link_to_grid {
+                " (сетка)"
+            } else {
+                ""
+            },
+            formatting::format_length(
+                grid_snap_settings.effective_radius_step(&grid_settings) as f64,
+                &coordinate_settings
+            ),
+            grid_snap_settings.polar_angle_step
+        );
+    }
+}
+
+/// Система обновления состояния отслеживания
+pub fn update_tracking_display(
+    tracking_settings: Res<TrackingSettings>,
+    mut text_query: Query<&mut Text, With<TrackingDisplay>>,
+) {
+    if !tracking_settings.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        **text = format!("Углы: {}", tracking_settings.angles_label());
+    }
+}
+
+/// Система позиционирования подсказки отслеживания у курсора
+pub fn update_tracking_tooltip(
+    snap_state: Res<SnapState>,
+    coordinate_settings: Res<CoordinateSettings>,
+    ucs_manager: Res<UcsManager>,
+    window_query: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    mut tooltip_query: Query<(&mut Text, &mut Node, &mut Visibility), With<TrackingTooltip>>,
+) {
+    let Ok((mut text, mut node, mut visibility)) = tooltip_query.single_mut() else {
+        return;
+    };
+    let cursor_position = window_query
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    match (snap_state.tracking, cursor_position) {
+        (Some(tracking), Some(cursor_position)) => {
+            let frame = ucs_manager.frame(snap_state.last_point);
+            **text = tracking_tooltip(&tracking, &coordinate_settings, &frame);
+            node.left = Val::Px(cursor_position.x + 16.0);
+            node.top = Val::Px(cursor_position.y + 16.0);
+            *visibility = Visibility::Visible;
+        }
+        _ => {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Система обновления информации о координатах курсора (с учетом привязок и ПСК)
+pub fn update_cursor_coordinates(
+    snap_state: Res<SnapState>,
+    coordinate_settings: Res<CoordinateSettings>,
+    ucs_manager: Res<UcsManager>,
+    mut text_query: Query<&mut Text, With<CoordinateDisplay>>,
+) {
+    if !snap_state.is_changed() && !ucs_manager.is_changed() && !coordinate_settings.is_changed() {
+        return;
+    }
+
+    let frame = ucs_manager.frame(snap_state.last_point);
+    for mut text in text_query.iter_mut() {
+        if let Some(world_pos) = snap_state.point() {
+            let coord_point = frame.coordinate_point(world_pos);
+            let coords_text =
+                formatting::format_coordinate_point(&coord_point, &coordinate_settings);
+            **text = match formatting::format_georeferenced(world_pos, &coordinate_settings) {
+                Some(geo_text) => format!("Курсор: {}\n{}", coords_text, geo_text),
+                None => format!("Курсор: {}", coords_text),
             };
-            **text = format!("Курсор: {}", coords_text);
         } else {
             **text = "Курсор: X: -, Y: -".to_string();
         }
@@ -706,6 +3230,7 @@ pub fn update_line_info(
 pub fn track_cursor_position(
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
     window_query: Query<&Window>,
+    render_origin: Res<crate::render_origin::RenderOrigin>,
     mut cursor_info: ResMut<CursorInfo>,
 ) {
     let Ok((camera, camera_transform)) = camera_query.single() else {
@@ -723,7 +3248,7 @@ pub fn track_cursor_position(
             Vec3::Y,
             0.0,
         ) {
-            cursor_info.world_position = Some(world_position);
+            cursor_info.world_position = Some(render_origin.to_document(world_position));
             cursor_info.is_valid = true;
         } else {
             cursor_info.is_valid = false;